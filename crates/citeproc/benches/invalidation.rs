@@ -0,0 +1,200 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! Regression suite for the shape of the salsa query graph.
+//!
+//! Each benchmark corresponds to something a word processor plugin does all the time: load a
+//! library, build a whole document, edit a single cluster, and switch styles. If a change to the
+//! queries makes one cluster edit recompute the whole document, `edit_one_cluster` is where it
+//! will show up.
+//!
+//! Run with `cargo bench -p citeproc --bench invalidation`, and compare against a baseline with
+//! `-- --save-baseline main` / `-- --baseline main`.
+
+use cfg_if::cfg_if;
+cfg_if! {
+    if #[cfg(feature="jemalloc")] {
+        use jemallocator::Jemalloc;
+        #[global_allocator]
+        static A: Jemalloc = Jemalloc;
+    } else {
+        use std::alloc::System;
+        #[global_allocator]
+        static A: System = System;
+    }
+}
+
+#[macro_use]
+extern crate criterion;
+
+use criterion::{BatchSize, Criterion};
+
+use citeproc::prelude::*;
+use citeproc_io::{DateOrRange, Name, PersonName};
+use csl::variables::*;
+use csl::CslType;
+
+use std::str::FromStr;
+
+static AGLC: &'static str = include_str!("./data/australian-guide-to-legal-citation.csl");
+static APA: &'static str = include_str!("./data/apa.csl");
+
+/// Size of the library for `load_references`.
+const LIBRARY_SIZE: u32 = 10_000;
+/// Number of clusters (and distinct references) in a "document".
+const DOCUMENT_SIZE: u32 = 2_000;
+
+/// A book with an author, title and year. Authors repeat every 500 references and years every
+/// 70, so that a document of any size has a realistic amount of disambiguation to do.
+fn book(n: u32) -> Reference {
+    let mut refr = Reference::empty(format!("id_{}", n).into(), CslType::Book);
+    refr.ordinary
+        .insert(Variable::Title, format!("A Book About Topic {}", n));
+    refr.ordinary
+        .insert(Variable::Publisher, String::from("Publishing House"));
+    refr.name.insert(
+        NameVariable::Author,
+        vec![Name::Person(PersonName {
+            family: Some(format!("Author{}", n % 500)),
+            given: Some(format!("Given{}", n % 7)),
+            ..Default::default()
+        })],
+    );
+    refr.date.insert(
+        DateVariable::Issued,
+        DateOrRange::from_str(&format!("{}-01-04", 1950 + n % 70)).unwrap(),
+    );
+    refr
+}
+
+fn library(size: u32) -> Vec<Reference> {
+    (0..size).map(book).collect()
+}
+
+fn new_processor(style: &str) -> Processor {
+    Processor::new(InitOptions {
+        style,
+        test_mode: true,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+/// One cluster per reference, each in its own footnote.
+fn init_document(proc: &mut Processor, size: u32) -> Vec<ClusterId> {
+    let clusters: Vec<Cluster> = (0..size)
+        .map(|n| Cluster {
+            id: proc.new_cluster(format!("cluster_{}", n)),
+            cites: vec![Cite::basic(format!("id_{}", n))],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        })
+        .collect();
+    let positions: Vec<ClusterPosition> = clusters
+        .iter()
+        .enumerate()
+        .map(|(ix, cluster)| ClusterPosition {
+            id: cluster.id,
+            note: Some(ix as u32 + 1),
+        })
+        .collect();
+    let ids = clusters.iter().map(|c| c.id).collect();
+    proc.init_clusters(clusters).unwrap();
+    proc.set_cluster_order(&positions).unwrap();
+    ids
+}
+
+/// A processor with a fully computed document, as it would be after the initial render.
+fn computed_document(style: &str) -> (Processor, Vec<ClusterId>) {
+    let mut proc = new_processor(style);
+    proc.reset_references(library(DOCUMENT_SIZE));
+    let ids = init_document(&mut proc, DOCUMENT_SIZE);
+    proc.compute();
+    (proc, ids)
+}
+
+fn bench_load_references(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_references");
+    for &(name, style) in &[("AGLC", AGLC), ("APA", APA)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || (new_processor(style), library(LIBRARY_SIZE)),
+                |(mut proc, refs)| {
+                    proc.reset_references(refs);
+                    proc
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_build_document(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_document");
+    for &(name, style) in &[("AGLC", AGLC), ("APA", APA)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let mut proc = new_processor(style);
+                    proc.reset_references(library(DOCUMENT_SIZE));
+                    proc
+                },
+                |mut proc| {
+                    init_document(&mut proc, DOCUMENT_SIZE);
+                    proc.compute();
+                    proc
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_edit_one_cluster(c: &mut Criterion) {
+    let mut group = c.benchmark_group("edit_one_cluster");
+    for &(name, style) in &[("AGLC", AGLC), ("APA", APA)] {
+        let (mut proc, ids) = computed_document(style);
+        // Edit a cluster in the middle of the document, so that both the clusters before and
+        // after it are candidates for (wrongly) being recomputed.
+        let target = ids[ids.len() / 2];
+        let cites = [
+            vec![Cite::basic("id_0")],
+            vec![Cite::basic(format!("id_{}", ids.len() / 2))],
+        ];
+        let mut toggle = 0;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                toggle = 1 - toggle;
+                proc.insert_cites(target, &cites[toggle]).unwrap();
+                proc.batched_updates()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_switch_style(c: &mut Criterion) {
+    let (mut proc, _ids) = computed_document(AGLC);
+    let styles = [APA, AGLC];
+    let mut toggle = 0;
+    c.bench_function("switch_style", |b| {
+        b.iter(|| {
+            toggle = 1 - toggle;
+            proc.set_style_text(styles[toggle]).unwrap();
+            proc.compute()
+        })
+    });
+}
+
+criterion_group! {
+    name = invalidation;
+    // Each iteration is expensive; the default of 100 samples takes far too long.
+    config = Criterion::default().sample_size(10);
+    targets = bench_load_references, bench_build_document, bench_edit_one_cluster, bench_switch_style
+}
+criterion_main!(invalidation);
@@ -67,7 +67,7 @@ fn basic_cluster_get_cite_id(proc: &mut Processor, cluster_id: u32, id: &str) ->
         id: cluster_id,
         cites: vec![Cite::basic(id)],
     };
-    proc.insert_cluster(cluster);
+    proc.insert_cluster(cluster).unwrap();
     let id = proc
         .cluster_cites(cluster_id)
         .iter()
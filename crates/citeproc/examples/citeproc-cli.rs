@@ -0,0 +1,129 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! Renders a document's clusters and bibliography from files on disk. Handy for reproducing bug
+//! reports, and a short tour of the public API.
+//!
+//! ```sh
+//! cargo run --example citeproc-cli -- \
+//!     --style style.csl --references refs.json --clusters clusters.json \
+//!     [--locales path/to/locales] [--format html|rtf|plain]
+//! ```
+//!
+//! `refs.json` is a CSL-JSON array. `clusters.json` is an array of clusters in document order, as
+//! you would pass to `Driver.initClusters` in the JavaScript API, each with an optional `note`
+//! number. Clusters without one are in-text. `--locales` is a directory of `locales-xx-XX.xml`
+//! files; without it, only the bundled en-US locale is available.
+
+use anyhow::{anyhow, bail, Context, Result};
+use citeproc::prelude::*;
+use citeproc::string_id;
+use serde::Deserialize;
+use std::fs;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const USAGE: &str = "usage: citeproc-cli --style FILE --references FILE --clusters FILE \
+                     [--locales DIR] [--format html|rtf|plain]";
+
+#[derive(Default)]
+struct Args {
+    style: Option<String>,
+    locales: Option<String>,
+    references: Option<String>,
+    clusters: Option<String>,
+    format: Option<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Args::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(flag) = iter.next() {
+            let slot = match flag.as_str() {
+                "--style" => &mut args.style,
+                "--locales" => &mut args.locales,
+                "--references" => &mut args.references,
+                "--clusters" => &mut args.clusters,
+                "--format" => &mut args.format,
+                "-h" | "--help" => bail!(USAGE),
+                _ => bail!("unknown argument `{}`\n{}", flag, USAGE),
+            };
+            *slot = Some(
+                iter.next()
+                    .ok_or_else(|| anyhow!("{} needs a value", flag))?,
+            );
+        }
+        Ok(args)
+    }
+}
+
+/// A cluster plus where it sits in the document.
+#[derive(Deserialize)]
+struct DocumentCluster {
+    #[serde(flatten)]
+    cluster: string_id::Cluster,
+    #[serde(default)]
+    note: Option<u32>,
+}
+
+fn read(path: &Option<String>, flag: &str) -> Result<String> {
+    let path = path
+        .as_ref()
+        .ok_or_else(|| anyhow!("missing {}\n{}", flag, USAGE))?;
+    fs::read_to_string(path).with_context(|| format!("could not read {}", path))
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let style = read(&args.style, "--style")?;
+    let references: Vec<Reference> = serde_json::from_str(&read(&args.references, "--references")?)
+        .context("could not parse the references as CSL-JSON")?;
+    let clusters: Vec<DocumentCluster> = serde_json::from_str(&read(&args.clusters, "--clusters")?)
+        .context("could not parse the clusters")?;
+    let format = match &args.format {
+        Some(format) => {
+            SupportedFormat::from_str(format).map_err(|_| anyhow!("unknown format `{}`", format))?
+        }
+        None => SupportedFormat::Html,
+    };
+    let fetcher = args
+        .locales
+        .as_ref()
+        .map(|dir| Arc::new(DirLocaleFetcher::new(dir)) as Arc<dyn LocaleFetcher>);
+
+    let mut processor = Processor::new(InitOptions {
+        style: &style,
+        format,
+        fetcher,
+        ..Default::default()
+    })?;
+    processor.reset_references(references);
+
+    let positions: Vec<_> = clusters
+        .iter()
+        .map(|c| string_id::ClusterPosition {
+            id: Some(c.cluster.id.clone()),
+            note: c.note,
+        })
+        .collect();
+    processor.init_clusters_str(clusters.into_iter().map(|c| c.cluster).collect())?;
+    processor.set_cluster_order_str(&positions)?;
+
+    for position in &positions {
+        let id = position.id.as_ref().unwrap();
+        if let Some(output) = processor.get_cluster_str(id) {
+            println!("{}: {}", id, output);
+        }
+    }
+    if let Some(bibliography) = processor.get_bibliography_if_any() {
+        println!();
+        for entry in bibliography {
+            println!("{}", entry.value);
+        }
+    }
+    Ok(())
+}
@@ -11,9 +11,11 @@ use super::processor::Interner;
 use citeproc_db::ClusterId as ClusterIdInternal;
 use citeproc_io::output::{markup::Markup, OutputFormat};
 use citeproc_io::{Cite, ClusterMode, SmartString};
+use citeproc_proc::{FallbackTerm, UndefinedTerm};
 use csl::Atom;
 use fnv::FnvHashMap;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// A symbol that identifies a cluster; a newtyped u32. This corresponds to an interned string
@@ -49,20 +51,24 @@ impl ClusterId {
 /// ]"#;
 /// let clusters: Vec<Cluster<Markup, i32>> = serde_json::from_str(json).unwrap();
 /// use pretty_assertions::assert_eq;
+/// let cluster = |id, cites, mode| Cluster {
+///     mode,
+///     ..Cluster::new(id, cites)
+/// };
 /// assert_eq!(clusters, vec![
-///     Cluster { id: 1, cites: vec![Cite::basic("smith")], mode: None, },
-///     Cluster { id: 2, cites: vec![Cite::basic("smith")], mode: Some(ClusterMode::AuthorOnly), },
-///     Cluster { id: 2, cites: vec![Cite::basic("smith")], mode: Some(ClusterMode::SuppressAuthor
-///     { suppress_first: 1 }), },
-///     Cluster { id: 3, cites: vec![Cite::basic("smith"), Cite::basic("jones")],
-///               mode: Some(ClusterMode::SuppressAuthor { suppress_first: 2 }), },
-///     Cluster { id: 4, cites: vec![Cite::basic("smith")], mode: Some(ClusterMode::Composite
-///     { infix: None, suppress_first: 1 }), },
-///     Cluster { id: 5, cites: vec![Cite::basic("smith"), Cite::basic("jones")],
-///               mode: Some(ClusterMode::Composite { infix: None, suppress_first: 2 }), },
+///     cluster(1, vec![Cite::basic("smith")], None),
+///     cluster(2, vec![Cite::basic("smith")], Some(ClusterMode::AuthorOnly)),
+///     cluster(2, vec![Cite::basic("smith")], Some(ClusterMode::SuppressAuthor
+///     { suppress_first: 1 })),
+///     cluster(3, vec![Cite::basic("smith"), Cite::basic("jones")],
+///             Some(ClusterMode::SuppressAuthor { suppress_first: 2 })),
+///     cluster(4, vec![Cite::basic("smith")], Some(ClusterMode::Composite
+///     { infix: None, suppress_first: 1 })),
+///     cluster(5, vec![Cite::basic("smith"), Cite::basic("jones")],
+///             Some(ClusterMode::Composite { infix: None, suppress_first: 2 })),
 /// ])
 /// ```
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(bound(
     serialize = "Id: serde::Serialize",
     deserialize = "Id: serde::Deserialize<'de>"
@@ -72,6 +78,23 @@ pub struct Cluster<O: OutputFormat = Markup, Id = ClusterId> {
     pub cites: Vec<Cite<O>>,
     #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
     pub mode: Option<ClusterMode>,
+    /// Strip the terminal punctuation from the rendered cluster, typically the full stop at the
+    /// end of a note, so that the cluster can be placed in the middle of a sentence.
+    #[serde(default, rename = "suppressTrailingPunctuation")]
+    pub suppress_trailing_punctuation: bool,
+}
+
+impl<O: OutputFormat, Id> Cluster<O, Id> {
+    /// A cluster with no mode and no other options set. Prefer this, with struct update syntax
+    /// for the rest, to writing out every field, as new fields may be added.
+    pub fn new(id: Id, cites: Vec<Cite<O>>) -> Self {
+        Cluster {
+            id,
+            cites,
+            mode: None,
+            suppress_trailing_punctuation: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -108,10 +131,49 @@ impl ReorderingError {
     }
 }
 
+/// A problem with the clusters given to the processor. The methods that add clusters return these
+/// before changing anything, and [Processor::validate_document](crate::Processor::validate_document)
+/// reports them for the document as a whole.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ClusterError {
+    #[error("cluster {0:?} was given more than once")]
+    DuplicateClusterId(ClusterId),
+    /// Only reported by [Processor::validate_document](crate::Processor::validate_document).
+    #[error("cite {index} of cluster {cluster:?} repeats an earlier cite's reference and locator")]
+    DuplicateCiteInCluster { cluster: ClusterId, index: u32 },
+    #[error("cite {index} of cluster {cluster:?} has an empty reference id")]
+    EmptyRefId { cluster: ClusterId, index: u32 },
+    #[error("cluster {cluster:?} has no cite {index}")]
+    NoSuchCite { cluster: ClusterId, index: u32 },
+    #[error("cluster {0:?} is not in the document")]
+    NoSuchCluster(ClusterId),
+}
+
+impl ClusterError {
+    pub(crate) fn to_external(self, interner: &Interner) -> string_id::ClusterError {
+        let resolve = |id: ClusterId| interner.resolve(id.raw()).map(SmartString::from);
+        let external = match self {
+            ClusterError::DuplicateClusterId(id) => {
+                resolve(id).map(string_id::ClusterError::DuplicateClusterId)
+            }
+            ClusterError::DuplicateCiteInCluster { cluster, index } => resolve(cluster)
+                .map(|cluster| string_id::ClusterError::DuplicateCiteInCluster { cluster, index }),
+            ClusterError::EmptyRefId { cluster, index } => resolve(cluster)
+                .map(|cluster| string_id::ClusterError::EmptyRefId { cluster, index }),
+            ClusterError::NoSuchCite { cluster, index } => resolve(cluster)
+                .map(|cluster| string_id::ClusterError::NoSuchCite { cluster, index }),
+            ClusterError::NoSuchCluster(id) => {
+                resolve(id).map(string_id::ClusterError::NoSuchCluster)
+            }
+        };
+        external.unwrap_or(string_id::ClusterError::Internal(self))
+    }
+}
+
 pub mod string_id {
     //! This is the API using string IDs only, useful for exposing citeproc-rs to non-Rust
     //! consumers.
-    use super::{BibEntry, BibliographyUpdate};
+    use super::{BibEntry, BibliographyUpdate, UpdateWarning};
     use serde::{Deserialize, Serialize};
     use citeproc_io::{
         output::{markup::Markup, OutputFormat},
@@ -135,12 +197,28 @@ pub mod string_id {
     pub struct UpdateSummary<O: OutputFormat = Markup> {
         /// A list of clusters that were updated, paired with the formatted output for each
         pub clusters: Vec<(SmartString, Arc<O::Output>)>,
+        /// Clusters that were rendered before, but are no longer in the document
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub removed_clusters: Vec<SmartString>,
         pub bibliography: Option<BibliographyUpdate>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub warnings: Vec<UpdateWarning>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub notes: Vec<NoteUpdate<O>>,
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NoteUpdate<O: OutputFormat = Markup> {
+        pub id: SmartString,
+        pub marker: Arc<O::Output>,
+        pub note_text: Arc<O::Output>,
     }
 
     #[derive(Serialize, Default, Debug, Clone, PartialEq, Eq)]
     #[serde(rename_all = "camelCase")]
     pub struct FullRender {
+        #[serde(serialize_with = "super::sorted_map")]
         pub all_clusters: FnvHashMap<SmartString, Arc<SmartString>>,
         pub bib_entries: Vec<BibEntry<Markup>>,
     }
@@ -152,6 +230,34 @@ pub mod string_id {
         #[error("non-existent cluster id {0:?}")]
         NonExistentCluster(SmartString),
     }
+
+    #[derive(Debug, thiserror::Error)]
+    pub enum ClusterError {
+        #[error("{0}")]
+        Internal(#[from] super::ClusterError),
+        #[error("cluster id {0:?} was given more than once")]
+        DuplicateClusterId(SmartString),
+        #[error(
+            "cite {index} of cluster {cluster:?} repeats an earlier cite's reference and locator"
+        )]
+        DuplicateCiteInCluster { cluster: SmartString, index: u32 },
+        #[error("cite {index} of cluster {cluster:?} has an empty reference id")]
+        EmptyRefId { cluster: SmartString, index: u32 },
+        #[error("cluster {cluster:?} has no cite {index}")]
+        NoSuchCite { cluster: SmartString, index: u32 },
+        #[error("cluster {0:?} is not in the document")]
+        NoSuchCluster(SmartString),
+    }
+
+    /// From [crate::Processor::process_citation_cluster] and
+    /// [crate::Processor::append_citation_cluster].
+    #[derive(Debug, thiserror::Error)]
+    pub enum ProcessClusterError {
+        #[error("{0}")]
+        Cluster(#[from] ClusterError),
+        #[error("{0}")]
+        Reordering(#[from] ReorderingError),
+    }
 }
 
 #[derive(Clone, Serialize)]
@@ -184,14 +290,37 @@ pub struct BibliographyMeta<O: OutputFormat = Markup> {
     pub format_meta: O::BibMeta,
 }
 
+/// Serializes a map with its keys in order, so that the output is the same from run to run no
+/// matter what order the map was filled in.
+fn sorted_map<K, V, S>(map: &FnvHashMap<K, V>, s: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: serde::Serializer,
+{
+    let sorted: std::collections::BTreeMap<&K, &V> = map.iter().collect();
+    sorted.serialize(s)
+}
+
 #[derive(Clone, Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct BibliographyUpdate<O: OutputFormat = Markup> {
     /// Contains Reference Ids mapped to their bibliography outputs
+    #[serde(serialize_with = "sorted_map")]
     pub updated_entries: FnvHashMap<Atom, Arc<O::Output>>,
+    /// [BibEntry::content] for each of the `updated_entries`, when the bibliography layout has a
+    /// prefix or suffix for it to leave out.
+    #[serde(
+        serialize_with = "sorted_map",
+        skip_serializing_if = "FnvHashMap::is_empty"
+    )]
+    pub updated_content: FnvHashMap<Atom, Arc<O::Output>>,
     /// None if the sort is the same, otherwise contains all entries in order
-    /// Entries that cease to be present in the list between updates are considered to have been removed.
     pub entry_ids: Option<Vec<Atom>>,
+    /// Reference ids that had a bibliography entry before this update, but no longer do, sorted.
+    /// These are also missing from `entry_ids`, so you don't have to diff it to find them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_entries: Vec<Atom>,
 }
 
 impl BibliographyUpdate {
@@ -204,7 +333,60 @@ impl BibliographyUpdate {
 pub struct UpdateSummary<O: OutputFormat = Markup> {
     /// A list of clusters that were updated, paired with the formatted output for each
     pub clusters: Vec<(ClusterId, Arc<O::Output>)>,
+    /// Clusters that were in a previous summary, but have since been removed or left out of the
+    /// cluster order. A cluster that comes back is reported in `clusters` again.
+    pub removed_clusters: Vec<ClusterId>,
     pub bibliography: Option<BibliographyUpdate>,
+    /// Problems with the document as a whole, which an editor may want to prompt the user about
+    pub warnings: Vec<UpdateWarning>,
+    /// With [InitOptions::note_markers](crate::InitOptions::note_markers) and a note style, the
+    /// in-text marker and the note content for each cluster that changed, or whose note number did
+    pub notes: Vec<NoteUpdate>,
+}
+
+impl<O: OutputFormat> UpdateSummary<O> {
+    /// Whether there is nothing to re-render. The warnings are about the document as a whole
+    /// rather than what changed, so they are not counted.
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+            && self.removed_clusters.is_empty()
+            && self.bibliography.is_none()
+            && self.notes.is_empty()
+    }
+}
+
+/// A cluster in a note style, split into the superscripted note number that goes in the text, and
+/// the note itself.
+#[derive(Debug, Clone)]
+pub struct NoteUpdate<O: OutputFormat = Markup> {
+    pub id: ClusterId,
+    pub marker: Arc<O::Output>,
+    pub note_text: Arc<O::Output>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum UpdateWarning {
+    /// The document cites these reference ids, but they are not in the library. See
+    /// [`Processor::missing_references`](crate::Processor::missing_references).
+    #[serde(rename_all = "camelCase")]
+    MissingReferences { ids: Vec<Atom> },
+    /// The style renders these terms, but the locale doesn't define them, so they come out empty.
+    /// Usually a locale file that is missing a term, or a term the style author meant to define
+    /// in the style's own `<locale>`. See
+    /// [`Processor::undefined_terms`](crate::Processor::undefined_terms).
+    #[serde(rename_all = "camelCase")]
+    UndefinedTerms { terms: Vec<UndefinedTerm> },
+    /// The locale doesn't define these terms, so they were taken from en-US instead. Worth
+    /// reporting to the locale's maintainers. See
+    /// [`Processor::fallback_terms`](crate::Processor::fallback_terms).
+    #[serde(rename_all = "camelCase")]
+    FallbackTerms { terms: Vec<FallbackTerm> },
+    /// The style disambiguates with year suffixes, but its citation layout renders no date and no
+    /// `year-suffix` variable, so suffixes are appended to the end of each cite that needs one.
+    /// See
+    /// [`StyleRequirements::year_suffix_without_date`](crate::StyleRequirements::year_suffix_without_date).
+    YearSuffixWithoutDate,
 }
 
 #[derive(Serialize, Default, Debug, Clone, PartialEq, Eq)]
@@ -212,6 +394,115 @@ pub struct UpdateSummary<O: OutputFormat = Markup> {
 pub struct BibEntry<O: OutputFormat = Markup> {
     pub id: Atom,
     pub value: Arc<O::Output>,
+    /// The entry without the bibliography layout's prefix and suffix (usually the final full
+    /// stop), nor any [BibEntryTemplate], for a host that runs entries together into flowing text
+    /// with its own punctuation. None when the layout has no affixes, as `value` is then the same.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Arc<O::Output>>,
+    /// Only for RTF output, where the host has to build the paragraphs itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paragraph_props: Option<ParagraphProps>,
+}
+
+/// Markup to put around every bibliography entry, e.g. an anchor to link back to, or a list of
+/// back-links. Set with [InitOptions::bib_entry_template](crate::InitOptions::bib_entry_template)
+/// or [Processor::set_bib_entry_template](crate::Processor::set_bib_entry_template).
+///
+/// The prefix and suffix are written as-is, so they must already be in the output format. In
+/// them, `{id}` is replaced with the reference id and `{citation-number}` with the entry's
+/// citation number, both escaped for the output format.
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct BibEntryTemplate {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl BibEntryTemplate {
+    pub(crate) fn decorate(
+        &self,
+        fmt: &Markup,
+        id: &Atom,
+        citation_number: Option<u32>,
+        value: &str,
+    ) -> SmartString {
+        let escape = |s: &str| fmt.output(fmt.plain(s), false);
+        let id = escape(id);
+        let number = citation_number.map_or_else(SmartString::new, |n| escape(&n.to_string()));
+        // One pass, so an id can't introduce a placeholder of its own
+        let fill = |template: &str| {
+            let mut filled = String::with_capacity(template.len());
+            let mut rest = template;
+            while let Some(start) = rest.find('{') {
+                filled.push_str(&rest[..start]);
+                rest = &rest[start..];
+                if let Some(after) = rest.strip_prefix("{id}") {
+                    filled.push_str(&id);
+                    rest = after;
+                } else if let Some(after) = rest.strip_prefix("{citation-number}") {
+                    filled.push_str(&number);
+                    rest = after;
+                } else {
+                    filled.push('{');
+                    rest = &rest[1..];
+                }
+            }
+            filled.push_str(rest);
+            filled
+        };
+        let mut decorated = SmartString::from(fill(&self.prefix));
+        decorated.push_str(value);
+        decorated.push_str(&fill(&self.suffix));
+        decorated
+    }
+}
+
+/// How to lay out the paragraph for a bibliography entry, worked out from the style's
+/// `hanging-indent`, `second-field-align` and spacing options. Indents are in twips (1/1440 inch).
+///
+/// With `second-field-align`, the first field is followed by a tab (`\tab` in RTF), and the
+/// widest first field in the bibliography decides where the tab stop goes. Character widths are
+/// estimated for a 12pt font.
+#[derive(Serialize, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParagraphProps {
+    /// The indent of every line except the first, from the page margin
+    pub left_indent: i32,
+    /// The first line's indent relative to `left_indent`. Negative for a hanging indent.
+    pub first_line_indent: i32,
+    /// Relative to the page margin
+    pub tab_stop: Option<i32>,
+    /// Line spacing within the entry, as a multiple of single spacing
+    pub line_spacing: u32,
+    /// Lines of space between entries, as a multiple of single spacing
+    pub entry_spacing: u32,
+}
+
+impl ParagraphProps {
+    /// Half an inch, as in Word's default hanging indent
+    const HANGING_INDENT: i32 = 720;
+    /// About half an em at 12pt
+    const CHAR_WIDTH: i32 = 120;
+
+    pub(crate) fn new(bib: &csl::Bibliography, max_offset: u32) -> Self {
+        use csl::style::SecondFieldAlign as Sfa;
+        // One character's gap after the widest first field, so it never runs into the tab stop
+        let offset = (max_offset as i32 + 1) * Self::CHAR_WIDTH;
+        // second-field-align wins over hanging-indent; the first field already hangs
+        let (left_indent, first_line_indent, tab_stop) = match bib.second_field_align {
+            Some(Sfa::Flush) => (offset, -offset, Some(offset)),
+            Some(Sfa::Margin) => (0, -offset, Some(0)),
+            None if bib.hanging_indent => (Self::HANGING_INDENT, -Self::HANGING_INDENT, None),
+            None => (0, 0, None),
+        };
+        ParagraphProps {
+            left_indent,
+            first_line_indent,
+            tab_stop,
+            line_spacing: bib.line_spaces,
+            entry_spacing: bib.entry_spacing,
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -220,6 +511,80 @@ pub struct FullRender {
     pub bib_entries: Vec<BibEntry<Markup>>,
 }
 
+/// Counts of the work a [Processor](crate::Processor) has done since it was created or since
+/// [Processor::reset_metrics](crate::Processor::reset_metrics). Reset before an edit and read
+/// afterwards to see how much of the document the edit caused to be recomputed.
+#[derive(Serialize, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Metrics {
+    /// Clusters rendered, as opposed to taken from the cache
+    pub built_clusters: u64,
+    /// Cites rendered before any disambiguation
+    pub ir_gen_passes: u64,
+    /// Disambiguation passes run over single cites, i.e. adding given names and then everything
+    /// else the style allows
+    pub disamb_passes: u64,
+    /// Locale files parsed
+    pub locale_parses: u64,
+    /// Every query the processor ran, including all of the above
+    pub queries_executed: u64,
+    /// Cached results from before an edit that were checked and found to be still valid, and so
+    /// did not have to be recomputed
+    pub queries_reused: u64,
+}
+
+/// The counters behind [Metrics]. These are bumped from inside queries, possibly on several
+/// snapshots at once, so they are atomics rather than a locked [Metrics].
+#[derive(Default, Debug)]
+pub(crate) struct MetricCounters {
+    pub built_clusters: AtomicU64,
+    pub ir_gen_passes: AtomicU64,
+    pub disamb_passes: AtomicU64,
+    pub locale_parses: AtomicU64,
+    pub queries_executed: AtomicU64,
+    pub queries_reused: AtomicU64,
+}
+
+impl MetricCounters {
+    pub(crate) fn get(&self) -> Metrics {
+        let get = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Metrics {
+            built_clusters: get(&self.built_clusters),
+            ir_gen_passes: get(&self.ir_gen_passes),
+            disamb_passes: get(&self.disamb_passes),
+            locale_parses: get(&self.locale_parses),
+            queries_executed: get(&self.queries_executed),
+            queries_reused: get(&self.queries_reused),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        for counter in &[
+            &self.built_clusters,
+            &self.ir_gen_passes,
+            &self.disamb_passes,
+            &self.locale_parses,
+            &self.queries_executed,
+            &self.queries_reused,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Metrics {
+    /// Of the cached results that an edit made it necessary to check, the fraction that could be
+    /// reused. 1.0 if nothing was checked.
+    pub fn reuse_rate(&self) -> f64 {
+        let total = self.queries_executed + self.queries_reused;
+        if total == 0 {
+            1.0
+        } else {
+            self.queries_reused as f64 / total as f64
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialOrd, PartialEq)]
 pub enum IncludeUncited {
     /// The default
@@ -236,15 +601,49 @@ impl Default for IncludeUncited {
     }
 }
 
+/// An output format. These can be parsed from their names (`"html"`, `"rtf"`, `"plain"`,
+/// `"test-html"`) or from a MIME type (`"text/html"`, `"application/rtf"`, `"text/plain"`), e.g.
+/// to pick a format for a clipboard flavour.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum SupportedFormat {
     Html,
     Rtf,
     Plain,
+    /// HTML as the CSL test suite expects it, with `<b>` for bold and URLs left as plain text.
     TestHtml,
 }
 
 impl SupportedFormat {
+    /// Every format, in a stable order suitable for showing to a user.
+    pub fn all() -> &'static [SupportedFormat] {
+        &[
+            SupportedFormat::Html,
+            SupportedFormat::Rtf,
+            SupportedFormat::Plain,
+            SupportedFormat::TestHtml,
+        ]
+    }
+
+    /// The name [FromStr] accepts for this format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SupportedFormat::Html => "html",
+            SupportedFormat::Rtf => "rtf",
+            SupportedFormat::Plain => "plain",
+            SupportedFormat::TestHtml => "test-html",
+        }
+    }
+
+    /// The MIME type of this format's output. `TestHtml` shares `text/html` with `Html`, and
+    /// parsing `text/html` gives `Html`.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            SupportedFormat::Html | SupportedFormat::TestHtml => "text/html",
+            SupportedFormat::Rtf => "application/rtf",
+            SupportedFormat::Plain => "text/plain",
+        }
+    }
+
     pub fn make_markup(&self) -> Markup {
         match self {
             SupportedFormat::Html => Markup::html(),
@@ -258,10 +657,13 @@ impl SupportedFormat {
 impl FromStr for SupportedFormat {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "html" => Ok(SupportedFormat::Html),
-            "rtf" => Ok(SupportedFormat::Rtf),
-            "plain" => Ok(SupportedFormat::Plain),
+        // Ignore MIME parameters like `; charset=utf-8`
+        let essence = s.split(';').next().unwrap_or(s).trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "html" | "text/html" => Ok(SupportedFormat::Html),
+            "rtf" | "application/rtf" | "text/rtf" => Ok(SupportedFormat::Rtf),
+            "plain" | "text/plain" => Ok(SupportedFormat::Plain),
+            "test-html" => Ok(SupportedFormat::TestHtml),
             _ => Err(()),
         }
     }
@@ -9,27 +9,40 @@
 
 pub(crate) mod api;
 pub(crate) mod processor;
+#[cfg(feature = "unstable-api")]
+pub mod unstable;
 
 #[cfg(test)]
 mod test;
 
 pub use self::api::*;
 
-pub use self::processor::{InitOptions, Processor};
+pub use self::processor::{InitOptions, Processor, Subscriber};
 
 pub mod prelude {
     pub use crate::api::*;
-    pub use crate::processor::{InitOptions, Processor};
+    pub use crate::processor::{InitOptions, Processor, Subscriber};
+    #[cfg(feature = "bundled-locales")]
+    pub use citeproc_db::BundledLocaleFetcher;
     pub use citeproc_db::{
-        CiteDatabase, CiteId, ClusterNumber, IntraNote, LocaleDatabase, LocaleFetchError,
-        LocaleFetcher, StyleDatabase,
+        CiteDatabase, CiteId, ClusterNumber, DirLocaleFetcher, IntraNote, LocaleDatabase,
+        LocaleFetchError, LocaleFetcher, StyleDatabase,
     };
-    pub use citeproc_io::output::{markup::Markup, OutputFormat};
-    pub use citeproc_io::{Cite, Reference, SmartString};
+    pub use citeproc_io::output::{
+        markup::{InlineElement, LinkOptions, Markup, MarkupInput, SmallCapsFallback},
+        OutputFormat,
+    };
+    pub use citeproc_io::{Cite, LangPrefs, MultiForm, Reference, SmartString};
     pub use citeproc_proc::db::{ImplementationDetails, IrDatabase};
+    pub use citeproc_proc::{
+        AuthorOnlyOutcome, AuthorOnlySubstitute, CiteCapitalization, FallbackTerm,
+        MissingReferencePolicy, NameLimit, NameTruncation, SortKeyValues, StyleRequirements,
+        TextCaseLocales, UndefinedTerm, UsedVariables,
+    };
     pub use csl::Atom;
 }
 
+#[cfg(feature = "random-cluster-id")]
 pub fn random_cluster_id() -> citeproc_io::SmartString {
     use rand::distributions::Alphanumeric;
     use rand::{thread_rng, Rng};
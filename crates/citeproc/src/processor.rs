@@ -11,14 +11,15 @@
 use crate::prelude::*;
 
 use crate::api::{
-    string_id, BibEntry, BibliographyMeta, BibliographyUpdate, ClusterId, ClusterPosition,
-    IncludeUncited, ReorderingError, SecondFieldAlign, UpdateSummary,
+    string_id, BibEntry, BibEntryTemplate, BibliographyMeta, BibliographyUpdate, ClusterId,
+    ClusterPosition, IncludeUncited, MetricCounters, Metrics, NoteUpdate, ReorderingError,
+    SecondFieldAlign, UpdateSummary, UpdateWarning,
 };
 use citeproc_db::{
     CiteData, CiteDatabaseStorage, ClusterId as ClusterIdInternal, HasFetcher,
     LocaleDatabaseStorage, StyleDatabaseStorage, Uncited,
 };
-use citeproc_proc::db::IrDatabaseStorage;
+use citeproc_proc::db::{IrDatabaseStorage, Work};
 use citeproc_proc::BibNumber;
 use indexmap::set::IndexSet;
 
@@ -26,12 +27,16 @@ use parking_lot::{Mutex, RwLock};
 use salsa::{Database, Durability, SweepStrategy};
 #[cfg(feature = "rayon")]
 use salsa::{ParallelDatabase, Snapshot};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
-use csl::{Lang, Style, StyleError};
+use csl::{
+    AnyVariable, CslType, Formatting, Lang, Style, StyleClass, StyleError, TextTermSelector,
+    VerticalAlignment,
+};
 
 use citeproc_io::output::{markup::Markup, OutputFormat};
-use citeproc_io::{Cite, ClusterMode, Reference, SmartString};
+use citeproc_io::{Cite, ClusterMode, LangPrefs, Name, Reference, SmartString};
 use csl::Atom;
 
 use string_interner::{backend::StringBackend, DefaultSymbol, StringInterner};
@@ -45,7 +50,7 @@ pub(crate) type Interner = StringInterner<
 type MarkupBuild = <Markup as OutputFormat>::Build;
 #[allow(dead_code)]
 type MarkupOutput = <Markup as OutputFormat>::Output;
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 
 struct SavedBib {
     sorted_refs: Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)>,
@@ -75,9 +80,35 @@ pub struct Processor {
     last_clusters: Arc<Mutex<FnvHashMap<ClusterId, Arc<SmartString>>>>,
     interner: Arc<RwLock<Interner>>,
     preview_cluster_id: ClusterId,
+    unknown_type_fallback: Option<CslType>,
+    normalize_initials: bool,
+    note_markers: bool,
+    bib_entry_template: Option<BibEntryTemplate>,
+    /// From [InitOptions::test_mode] and [InitOptions::csl_features], for parsing any later style
+    /// the same way as the first.
+    parse_options: csl::ParseOptions,
+    last_markers: Arc<Mutex<FnvHashMap<ClusterId, Arc<SmartString>>>>,
+    /// How many [Processor::begin_batch] calls are still waiting for their `end_batch`.
+    batch_depth: u32,
+    subscribers: Arc<Mutex<Vec<Arc<Subscriber>>>>,
+    metrics: Arc<MetricCounters>,
 }
 
-impl Database for Processor {}
+/// A callback registered with [Processor::subscribe].
+pub type Subscriber = Box<dyn Fn(&UpdateSummary) + Send + Sync>;
+
+impl Database for Processor {
+    fn salsa_event(&self, event: salsa::Event) {
+        // The more specific counts come from the queries themselves, through
+        // ImplementationDetails::count_work and HasFetcher::locale_file_parsed.
+        let counter = match event.kind {
+            salsa::EventKind::WillExecute { .. } => &self.metrics.queries_executed,
+            salsa::EventKind::DidValidateMemoizedValue { .. } => &self.metrics.queries_reused,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
 
 #[cfg(feature = "rayon")]
 impl ParallelDatabase for Processor {
@@ -90,6 +121,15 @@ impl ParallelDatabase for Processor {
             last_clusters: self.last_clusters.clone(),
             interner: self.interner.clone(),
             preview_cluster_id: self.preview_cluster_id,
+            unknown_type_fallback: self.unknown_type_fallback,
+            normalize_initials: self.normalize_initials,
+            note_markers: self.note_markers,
+            bib_entry_template: self.bib_entry_template.clone(),
+            parse_options: self.parse_options.clone(),
+            last_markers: self.last_markers.clone(),
+            batch_depth: self.batch_depth,
+            subscribers: self.subscribers.clone(),
+            metrics: self.metrics.clone(),
         })
     }
 }
@@ -98,6 +138,9 @@ impl HasFetcher for Processor {
     fn get_fetcher(&self) -> Arc<dyn LocaleFetcher> {
         self.fetcher.clone()
     }
+    fn locale_file_parsed(&self) {
+        self.metrics.locale_parses.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 impl ImplementationDetails for Processor {
@@ -111,6 +154,14 @@ impl ImplementationDetails for Processor {
         let reader = self.interner.read();
         reader.resolve(symbol).map(SmartString::from)
     }
+    fn count_work(&self, work: Work) {
+        let counter = match work {
+            Work::BuiltCluster => &self.metrics.built_clusters,
+            Work::IrGen => &self.metrics.ir_gen_passes,
+            Work::Disambiguation => &self.metrics.disamb_passes,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 // need a Clone impl for map_with
@@ -140,11 +191,26 @@ pub struct InitOptions<'a> {
     pub format: SupportedFormat,
     /// A full independent style.
     pub style: &'a str,
-    /// You might get this from a dependent style via `StyleMeta::parse(dependent_xml_string)`
+    /// Renders with this locale instead of the style's `default-locale`, like citeproc-js's `lang`
+    /// parameter. The style's own `<locale>` overrides still apply to it the usual way, by
+    /// matching `xml:lang` along the fallback chain: with `de-AT`, the style's `de-AT`, `de` and
+    /// unlabelled overrides are used, but not its `en` ones.
+    ///
+    /// You might get this from a dependent style via `StyleMeta::parse(dependent_xml_string)`, or
+    /// from the document itself.
     pub locale_override: Option<Lang>,
     /// Mechanism for fetching the locale you provide, if necessary.
     pub fetcher: Option<Arc<dyn LocaleFetcher>>,
 
+    /// CSL-M and other extensions to allow, one by one, on top of CSL 1.0.1. A style can enable
+    /// more with its own `<features>` element. Anything that needs a feature that is not enabled
+    /// is a style error, with a hint naming the feature.
+    ///
+    /// ```
+    /// let mut features = csl::Features::new();
+    /// features.try_set_feature("condition-date-parts").unwrap();
+    /// features.try_set_feature("multiple-locators").unwrap();
+    /// ```
     pub csl_features: Option<csl::Features>,
 
     /// Disables some formalities for test suite operation
@@ -153,6 +219,68 @@ pub struct InitOptions<'a> {
     /// Disables sorting on the bibliography
     pub bibliography_no_sort: bool,
 
+    /// How to degrade `font-variant="small-caps"` when `format` is plain text or RTF.
+    pub small_caps: SmallCapsFallback,
+
+    /// Extra attributes for the `<a>` tags written for URLs and DOIs when `format` is HTML, e.g.
+    /// `target="_blank"` and `rel="noopener"`.
+    pub links: LinkOptions,
+
+    /// What to render for cites to references that are not in the library. Defaults to `???`.
+    pub missing_reference_policy: MissingReferencePolicy,
+
+    /// The type to give references whose CSL-JSON `type` isn't a CSL type. If `None`, they are
+    /// treated as `article`, the same as references with no type at all. Either way,
+    /// [Reference::original_type] keeps the type as it was given.
+    pub unknown_type_fallback: Option<CslType>,
+
+    /// Rewrite given names made of initials the same way, whether they were typed `J.R.R.`,
+    /// `J. R. R.` or `JRR`, so they sort and disambiguate as one name and `initialize-with` gives
+    /// the same output for all of them. See [citeproc_io::PersonName::normalize_initials].
+    pub normalize_initials: bool,
+
+    /// For note styles, also render the superscripted note number that marks each cluster's
+    /// place in the text, and return it alongside the note in [UpdateSummary::notes].
+    pub note_markers: bool,
+
+    /// Markup to wrap each bibliography entry in, with the reference id and citation number
+    /// filled in. See [BibEntryTemplate].
+    pub bib_entry_template: Option<BibEntryTemplate>,
+
+    /// Use cite prefixes and suffixes exactly as given. By default, as in citeproc-js, a space is
+    /// put between an affix and its cite if it doesn't already have one.
+    pub raw_cite_affixes: bool,
+
+    /// Pass [MarkupInput::Verbatim](crate::prelude::MarkupInput::Verbatim) cite prefixes and suffixes through to the output unescaped.
+    /// Off by default, so cite JSON from an untrusted source can't inject markup: verbatim affixes
+    /// are then escaped like plain-text ones. Only turn this on if every cluster comes from code
+    /// that constructs its own markup.
+    pub verbatim_affixes: bool,
+
+    /// Which styles capitalize the first term of a cite whose prefix ends in a full stop (`ibid.`
+    /// becomes `Ibid.`), or of the first cite in a note. By default, only note styles do. See
+    /// [CiteCapitalization].
+    pub cite_capitalization: CiteCapitalization,
+
+    /// Never give cites in in-text clusters an ibid position; a cite that would be ibid is only
+    /// subsequent, like any other repeated cite. In-text positions are otherwise tracked just like
+    /// note positions, so an author-date style that tests for `ibid` can render, e.g., `(ibid.
+    /// 45)` for consecutive cites to the same work.
+    pub in_text_no_ibid: bool,
+
+    /// Render at most this many names from any one name variable, however many the style's et-al
+    /// settings allow, for references with hundreds or thousands of authors. See [NameLimit].
+    pub name_limit: Option<NameLimit>,
+
+    /// For author-only cites of references with no author, whether to show whatever the style's
+    /// `<substitute>` rendered instead, even a title, or only the names it fell back to. See
+    /// [Processor::author_only_outcome].
+    pub author_only_substitute: AuthorOnlySubstitute,
+
+    /// Whether `text-case="title"` applies to items without a `language`, when the style's
+    /// locale is not English. By default it doesn't, as the spec says; see [TextCaseLocales].
+    pub text_case_locales: TextCaseLocales,
+
     #[doc(hidden)]
     pub use_default_default: private::CannotConstruct,
 }
@@ -176,11 +304,21 @@ impl Processor {
             // This uses DefaultBackend, which is
             interner: Arc::new(RwLock::new(interner)),
             preview_cluster_id,
+            unknown_type_fallback: None,
+            normalize_initials: false,
+            note_markers: false,
+            bib_entry_template: None,
+            parse_options: Default::default(),
+            last_markers: Arc::new(Mutex::new(Default::default())),
+            batch_depth: 0,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            metrics: Arc::new(MetricCounters::default()),
         };
         citeproc_db::safe_default(&mut db);
         citeproc_proc::safe_default(&mut db);
         // XXX: currently impossible to preview a cluster with a ClusterMode applied
         db.set_cluster_mode(preview_cluster_id.raw(), None);
+        db.set_cluster_suppress_trailing_punctuation(preview_cluster_id.raw(), false);
         db
     }
 
@@ -195,24 +333,51 @@ impl Processor {
             csl_features,
             test_mode,
             bibliography_no_sort,
+            small_caps,
+            links,
+            missing_reference_policy,
+            unknown_type_fallback,
+            normalize_initials,
+            note_markers,
+            bib_entry_template,
+            raw_cite_affixes,
+            verbatim_affixes,
+            cite_capitalization,
+            in_text_no_ibid,
+            name_limit,
+            author_only_substitute,
+            text_case_locales,
             use_default_default: _,
         } = options;
 
         let fetcher =
             fetcher.unwrap_or_else(|| Arc::new(citeproc_db::PredefinedLocales::bundled_en_us()));
         let mut db = Processor::safe_default(fetcher);
-        db.formatter = format.make_markup();
-        let style = Style::parse_with_opts(
-            &style,
-            csl::ParseOptions {
-                allow_no_info: test_mode,
-                features: csl_features,
-                ..Default::default()
-            },
-        )?;
+        db.formatter = format
+            .make_markup()
+            .with_small_caps(small_caps)
+            .with_links(links);
+        db.unknown_type_fallback = unknown_type_fallback;
+        db.normalize_initials = normalize_initials;
+        db.note_markers = note_markers;
+        db.bib_entry_template = bib_entry_template;
+        db.parse_options = csl::ParseOptions {
+            allow_no_info: test_mode,
+            features: csl_features,
+            ..Default::default()
+        };
+        let style = Style::parse_with_opts(&style, db.parse_options.clone())?;
         db.set_style_with_durability(Arc::new(style), Durability::HIGH);
         db.set_default_lang_override_with_durability(locale_override, Durability::HIGH);
         db.set_bibliography_no_sort_with_durability(bibliography_no_sort, Durability::HIGH);
+        db.set_missing_reference_policy_with_durability(missing_reference_policy, Durability::HIGH);
+        db.set_raw_cite_affixes_with_durability(raw_cite_affixes, Durability::HIGH);
+        db.set_verbatim_affixes_with_durability(verbatim_affixes, Durability::HIGH);
+        db.set_cite_capitalization_with_durability(cite_capitalization, Durability::HIGH);
+        db.set_in_text_no_ibid_with_durability(in_text_no_ibid, Durability::HIGH);
+        db.set_name_limit_with_durability(name_limit, Durability::HIGH);
+        db.set_author_only_substitute_with_durability(author_only_substitute, Durability::HIGH);
+        db.set_text_case_locales_with_durability(text_case_locales, Durability::HIGH);
         Ok(db)
     }
 
@@ -222,6 +387,114 @@ impl Processor {
         Ok(())
     }
 
+    /// Makes a second processor for the same document in another style, e.g. to preview a style
+    /// side by side before switching to it. References, locales, clusters and their order are
+    /// carried over, as are the rest of the [InitOptions]. The reference library and locales are
+    /// shared rather than copied, and both processors start with the same cluster ids.
+    ///
+    /// After this, the two are independent: edits to one are not seen by the other, and neither
+    /// are cluster ids interned by it.
+    pub fn clone_with_style(&self, style_text: &str) -> Result<Self, StyleError> {
+        let style = Style::parse_with_opts(style_text, self.parse_options.clone())?;
+        Ok(self.fork(Arc::new(style)))
+    }
+
+    /// Makes a disposable copy of the processor, to try out edits without touching the real
+    /// document, e.g. to show how it would look with a section deleted. The style, references
+    /// and locales are shared with this processor rather than copied, as with
+    /// [Processor::clone_with_style], and edits to the copy are not seen here.
+    ///
+    /// The copy also starts from this processor's last [UpdateSummary], so its first
+    /// [Processor::batched_updates] only reports what the edits changed. Subscribers are not
+    /// carried over.
+    pub fn clone_for_preview(&self) -> Self {
+        let db = self.fork(self.style());
+        *db.last_clusters.lock() = self.last_clusters.lock().clone();
+        *db.last_markers.lock() = self.last_markers.lock().clone();
+        {
+            let saved = self.last_bibliography.lock();
+            let mut copy = db.last_bibliography.lock();
+            copy.sorted_refs = saved.sorted_refs.clone();
+            copy.bib_entries = saved.bib_entries.clone();
+        }
+        db
+    }
+
+    fn fork(&self, style: Arc<Style>) -> Self {
+        let mut db = Processor::safe_default(self.fetcher.clone());
+        db.formatter = self.formatter.clone();
+        db.interner = Arc::new(RwLock::new(self.interner.read().clone()));
+        db.preview_cluster_id = self.preview_cluster_id;
+        db.unknown_type_fallback = self.unknown_type_fallback;
+        db.normalize_initials = self.normalize_initials;
+        db.note_markers = self.note_markers;
+        db.bib_entry_template = self.bib_entry_template.clone();
+        db.parse_options = self.parse_options.clone();
+        let preview = self.preview_cluster_id.raw();
+        db.set_cluster_mode(preview, None);
+        db.set_cluster_suppress_trailing_punctuation(preview, false);
+
+        db.set_style_with_durability(style, Durability::HIGH);
+        db.set_default_lang_override_with_durability(
+            self.default_lang_override(),
+            Durability::HIGH,
+        );
+        db.set_bibliography_no_sort_with_durability(self.bibliography_no_sort(), Durability::HIGH);
+        db.set_missing_reference_policy_with_durability(
+            self.missing_reference_policy(),
+            Durability::HIGH,
+        );
+        db.set_raw_cite_affixes_with_durability(self.raw_cite_affixes(), Durability::HIGH);
+        db.set_verbatim_affixes_with_durability(self.verbatim_affixes(), Durability::HIGH);
+        db.set_cite_capitalization_with_durability(self.cite_capitalization(), Durability::HIGH);
+        db.set_in_text_no_ibid_with_durability(self.in_text_no_ibid(), Durability::HIGH);
+        db.set_name_limit_with_durability(self.name_limit(), Durability::HIGH);
+        db.set_author_only_substitute_with_durability(
+            self.author_only_substitute(),
+            Durability::HIGH,
+        );
+        db.set_text_case_locales_with_durability(self.text_case_locales(), Durability::HIGH);
+        db.set_citation_number_offset_with_durability(
+            self.citation_number_offset(),
+            Durability::MEDIUM,
+        );
+
+        let langs = self.locale_input_langs();
+        for lang in langs.iter() {
+            let xml = self.locale_input_xml(lang.clone());
+            db.set_locale_input_xml_with_durability(lang.clone(), xml, Durability::HIGH);
+        }
+        db.set_locale_input_langs_with_durability(langs, Durability::HIGH);
+
+        let keys = self.all_keys();
+        for key in keys.iter() {
+            let refr = self.reference_input(key.clone());
+            db.set_reference_input_with_durability(key.clone(), refr, Durability::MEDIUM);
+        }
+        db.set_all_keys_with_durability(keys, Durability::MEDIUM);
+        db.set_all_uncited(self.all_uncited());
+        db.set_lang_prefs_with_durability(self.lang_prefs(), Durability::MEDIUM);
+
+        // CiteIds are interned per database, so the cites have to be interned again.
+        let cluster_ids = self.cluster_ids();
+        for &raw in cluster_ids.iter() {
+            let cites = self
+                .cluster_cites(raw)
+                .iter()
+                .map(|&cite_id| db.cite(self.lookup_cite(cite_id)))
+                .collect();
+            db.set_cluster_cites(raw, Arc::new(cites));
+            db.set_cluster_note_number(raw, self.cluster_note_number(raw));
+            db.set_cluster_mode(raw, self.cluster_mode(raw));
+            db.set_cluster_suppress_trailing_punctuation(
+                raw,
+                self.cluster_suppress_trailing_punctuation(raw),
+            );
+        }
+        db.set_cluster_ids(cluster_ids);
+        db
+    }
+
     #[cfg(feature = "rayon")]
     fn snap(&self) -> Snap {
         Snap(self.snapshot())
@@ -296,26 +569,239 @@ impl Processor {
     }
 
     pub fn batched_updates(&self) -> UpdateSummary {
+        if self.batch_depth > 0 {
+            return UpdateSummary::default();
+        }
         let delta = self.compute();
-        UpdateSummary {
+        let removed_clusters = self.forget_removed_clusters();
+        let notes = self.note_updates(&delta);
+        let summary = UpdateSummary {
             clusters: delta,
+            removed_clusters,
             bibliography: self.save_and_diff_bibliography(),
+            warnings: self.update_warnings(),
+            notes,
+        };
+        if !summary.is_empty() {
+            // Not called with the lock held, in case a subscriber takes it too
+            let subscribers = self.subscribers.lock().clone();
+            for subscriber in subscribers.iter() {
+                subscriber(&summary);
+            }
         }
+        summary
     }
 
     pub fn batched_updates_str(&self) -> string_id::UpdateSummary {
-        let delta = self.compute();
-        let mut delta_str = Vec::with_capacity(delta.len());
+        let UpdateSummary {
+            clusters,
+            removed_clusters,
+            bibliography,
+            warnings,
+            notes,
+        } = self.batched_updates();
         let interner = self.interner.read();
-        for (cid, neu) in delta {
-            if let Some(resolved) = interner.resolve(cid.raw()) {
-                delta_str.push((SmartString::from(resolved), neu));
-            }
-        }
+        let clusters = clusters
+            .into_iter()
+            .filter_map(|(cid, neu)| {
+                let resolved = interner.resolve(cid.raw())?;
+                Some((SmartString::from(resolved), neu))
+            })
+            .collect();
+        let removed_clusters = removed_clusters
+            .into_iter()
+            .filter_map(|cid| interner.resolve(cid.raw()).map(SmartString::from))
+            .collect();
+        let notes = notes
+            .into_iter()
+            .filter_map(|note| {
+                let id = interner.resolve(note.id.raw())?;
+                Some(string_id::NoteUpdate {
+                    id: SmartString::from(id),
+                    marker: note.marker,
+                    note_text: note.note_text,
+                })
+            })
+            .collect();
         string_id::UpdateSummary {
-            clusters: delta_str,
-            bibliography: self.save_and_diff_bibliography(),
+            clusters,
+            removed_clusters,
+            bibliography,
+            warnings,
+            notes,
+        }
+    }
+
+    /// Calls `subscriber` with every summary computed from now on, i.e. every time
+    /// [Processor::batched_updates] or [Processor::end_batch] (or their `_str` versions) runs
+    /// outside a batch. The subscriber gets exactly what the caller gets, so a UI can render from
+    /// it without keeping track of who asked. It is not called when nothing has changed (see
+    /// [UpdateSummary::is_empty]), e.g. for the empty summaries handed out while a batch is open.
+    pub fn subscribe(&mut self, subscriber: Subscriber) {
+        self.subscribers.lock().push(Arc::new(subscriber));
+    }
+
+    /// How much work the processor has done since it was created or since the last
+    /// [Processor::reset_metrics]. Work done in parallel on snapshots is counted too.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.get()
+    }
+
+    /// Zeroes the counters in [Processor::metrics].
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    /// Starts a batch of edits, e.g. one per keystroke while a user is editing a cite. Until the
+    /// matching [Processor::end_batch], [Processor::batched_updates] returns an empty summary
+    /// instead of recomputing anything. Batches can be nested; only the outermost `end_batch`
+    /// computes.
+    pub fn begin_batch(&mut self) {
+        self.batch_depth += 1;
+    }
+
+    /// Ends a batch started with [Processor::begin_batch]. When this closes the outermost batch,
+    /// returns one summary covering every edit made during it. Clusters that were changed and then
+    /// changed back are not included.
+    pub fn end_batch(&mut self) -> UpdateSummary {
+        self.batch_depth = self.batch_depth.saturating_sub(1);
+        self.batched_updates()
+    }
+
+    /// [Processor::end_batch], with string cluster ids.
+    pub fn end_batch_str(&mut self) -> string_id::UpdateSummary {
+        self.batch_depth = self.batch_depth.saturating_sub(1);
+        self.batched_updates_str()
+    }
+
+    /// Drops the saved output of every cluster that is no longer in the document, and returns
+    /// their ids in the order they were created.
+    fn forget_removed_clusters(&self) -> Vec<ClusterId> {
+        let in_document: FnvHashSet<ClusterId> = self
+            .clusters_cites_sorted()
+            .iter()
+            .map(|cluster| ClusterId::new(cluster.id))
+            .collect();
+        let mut last_clusters = self.last_clusters.lock();
+        let mut last_markers = self.last_markers.lock();
+        let mut removed: Vec<ClusterId> = last_clusters
+            .keys()
+            .filter(|id| !in_document.contains(id))
+            .cloned()
+            .collect();
+        removed.sort_by_key(|id| id.raw());
+        for id in &removed {
+            last_clusters.remove(id);
+            last_markers.remove(id);
+        }
+        removed
+    }
+
+    /// Pairs up the note text of every cluster in `delta` with its marker, plus any clusters
+    /// whose text is the same but whose note number has changed.
+    fn note_updates(&self, delta: &[(ClusterId, Arc<SmartString>)]) -> Vec<NoteUpdate> {
+        if !self.note_markers || self.get_style().class != StyleClass::Note {
+            return Vec::new();
         }
+        let changed: FnvHashMap<ClusterId, &Arc<SmartString>> =
+            delta.iter().map(|(id, text)| (*id, text)).collect();
+        let last_clusters = self.last_clusters.lock();
+        let mut last_markers = self.last_markers.lock();
+        let mut notes = Vec::new();
+        for cluster in self.clusters_cites_sorted().iter() {
+            let id = ClusterId::new(cluster.id);
+            let number = match cluster.number {
+                ClusterNumber::Note(intra) => intra.note_number(),
+                ClusterNumber::OutsideFlow | ClusterNumber::InText(_) => continue,
+            };
+            let marker = Arc::new(self.note_marker(number));
+            let renumbered = last_markers.get(&id) != Some(&marker);
+            let note_text = match changed.get(&id) {
+                Some(&text) => text.clone(),
+                None if renumbered => match last_clusters.get(&id) {
+                    Some(text) => text.clone(),
+                    None => continue,
+                },
+                None => continue,
+            };
+            last_markers.insert(id, marker.clone());
+            notes.push(NoteUpdate {
+                id,
+                marker,
+                note_text,
+            });
+        }
+        notes
+    }
+
+    fn note_marker(&self, note_number: u32) -> SmartString {
+        let fmt = &self.formatter;
+        let superscript = Formatting {
+            vertical_alignment: Some(VerticalAlignment::Superscript),
+            ..Default::default()
+        };
+        let build = fmt.text_node(note_number.to_string(), Some(superscript));
+        fmt.output(build, false).into()
+    }
+
+    fn update_warnings(&self) -> Vec<UpdateWarning> {
+        let mut warnings = Vec::new();
+        let missing = self.missing_references();
+        if !missing.is_empty() {
+            warnings.push(UpdateWarning::MissingReferences { ids: missing });
+        }
+        let terms = IrDatabase::undefined_terms(self);
+        if !terms.is_empty() {
+            warnings.push(UpdateWarning::UndefinedTerms {
+                terms: terms.to_vec(),
+            });
+        }
+        let fallback = IrDatabase::fallback_terms(self);
+        if !fallback.is_empty() {
+            warnings.push(UpdateWarning::FallbackTerms {
+                terms: fallback.to_vec(),
+            });
+        }
+        if self.style_requirements().year_suffix_without_date {
+            warnings.push(UpdateWarning::YearSuffixWithoutDate);
+        }
+        warnings
+    }
+
+    /// Reference ids that are cited somewhere in the document, but are not in the library, in the
+    /// order they are first cited. Cites to these render as `???` or similar; use this to prompt
+    /// the user to import them.
+    pub fn missing_references(&self) -> Vec<Atom> {
+        self.missing_keys().iter().cloned().collect()
+    }
+
+    /// Terms the style renders that the default locale, merged with the style's own `<locale>`
+    /// blocks, does not define. They render as nothing, which is easy to miss.
+    pub fn undefined_terms(&self) -> Vec<UndefinedTerm> {
+        IrDatabase::undefined_terms(self).to_vec()
+    }
+
+    /// Terms the style renders that the default locale leaves out, and which are taken from the
+    /// en-US locale instead, as the spec says. The output is fine, but it has English in it, so
+    /// this is for telling the locale's maintainers what is missing.
+    pub fn fallback_terms(&self) -> Vec<FallbackTerm> {
+        IrDatabase::fallback_terms(self).to_vec()
+    }
+
+    /// Looks up a term in the default locale, merged with the style's own `<locale>` blocks, just
+    /// as the style would render it. For a host's own UI strings, e.g. a placeholder reading "no
+    /// date" in the style's language. Returns None if the locale doesn't define the term.
+    pub fn get_term(&self, selector: TextTermSelector, plural: bool) -> Option<String> {
+        let locale = self.default_locale();
+        locale.get_text_term(selector, plural).map(String::from)
+    }
+
+    /// Reference ids in the order they are first cited in the document, regardless of how the
+    /// bibliography is sorted. For exporters that number references by first citation, e.g. to
+    /// write out `\bibitem`s. Missing references and uncited ones from
+    /// [Processor::include_uncited] are not included.
+    pub fn cited_keys_in_citation_order(&self) -> Vec<Atom> {
+        self.cited_keys().iter().cloned().collect()
     }
 
     pub fn drain(&mut self) {
@@ -348,8 +834,8 @@ impl Processor {
     /// let mut processor = Processor::new(options).unwrap();
     /// let a = processor.new_cluster("cluster-A");
     /// let b = processor.new_cluster("cluster-B");
-    /// processor.insert_cites(a, &[Cite::basic("nonexistent-reference")]);
-    /// processor.insert_cites(b, &[Cite::basic("nonexistent-reference")]);
+    /// processor.insert_cites(a, &[Cite::basic("nonexistent-reference")]).unwrap();
+    /// processor.insert_cites(b, &[Cite::basic("nonexistent-reference")]).unwrap();
     /// processor.set_cluster_order(&[
     ///     ClusterPosition { id: a, note: None },
     ///     ClusterPosition { id: b, note: None },
@@ -359,7 +845,21 @@ impl Processor {
         self.intern_cluster_id(string)
     }
 
+    /// Looks up the [ClusterId] for a string id used with the `_str` methods, without interning a
+    /// new one. Returns None if the processor has never seen that string. Ids are never
+    /// un-interned, so this still works after the cluster is removed.
+    pub fn cluster_id_of(&self, string: &str) -> Option<ClusterId> {
+        self.interner.read().get(string).map(ClusterId::new)
+    }
+
+    /// The reverse of [Processor::cluster_id_of]: the string a [ClusterId] was interned from.
+    pub fn cluster_str_of(&self, cluster_id: ClusterId) -> Option<SmartString> {
+        let interner = self.interner.read();
+        interner.resolve(cluster_id.raw()).map(SmartString::from)
+    }
+
     /// Returns a random cluster id, with an extra guarantee that it isn't already in use.
+    #[cfg(feature = "random-cluster-id")]
     pub fn random_cluster_id_str(&self) -> SmartString {
         let interner = self.interner.read();
         loop {
@@ -371,23 +871,61 @@ impl Processor {
     }
 
     /// Returns a random cluster id, with an extra guarantee that it isn't already in use.
+    #[cfg(feature = "random-cluster-id")]
     pub fn random_cluster_id(&self) -> ClusterId {
         let rand_id = self.random_cluster_id_str();
         ClusterId::new(self.interner.write().get_or_intern(rand_id))
     }
 
+    /// Applies [InitOptions::unknown_type_fallback] and [InitOptions::normalize_initials].
+    fn prepare_reference(&self, mut refr: Reference) -> Reference {
+        if let (Some(fallback), Some(_)) = (self.unknown_type_fallback, &refr.original_type) {
+            refr.csl_type = fallback;
+        }
+        if self.normalize_initials {
+            for name in refr.name.values_mut().flatten() {
+                if let Name::Person(pn) = name {
+                    pn.normalize_initials();
+                }
+            }
+        }
+        refr
+    }
+
     pub fn reset_references(&mut self, refs: Vec<Reference>) {
         let keys: IndexSet<Atom> = refs.iter().map(|r| r.id.clone()).collect();
         for r in refs {
+            let r = self.prepare_reference(r);
             self.set_reference_input_with_durability(r.id.clone(), Arc::new(r), Durability::MEDIUM);
         }
         self.set_all_keys_with_durability(Arc::new(keys), Durability::MEDIUM);
     }
 
+    /// Replaces the reference library with a CSL-JSON array read from `reader`, like
+    /// [Processor::reset_references]. Each reference is inserted as soon as it is parsed, so a large
+    /// library is never held in memory all at once. `progress` is called after each one with the
+    /// number read so far.
+    ///
+    /// If reading fails partway, the set of references in the library is left as it was, but those
+    /// already read will have replaced any existing references with the same ids.
+    pub fn set_references_from_reader<R: std::io::Read>(
+        &mut self,
+        reader: R,
+        progress: impl FnMut(usize),
+    ) -> Result<(), serde_json::Error> {
+        let mut de = serde_json::Deserializer::from_reader(std::io::BufReader::new(reader));
+        let sink = ReferenceSink { db: self, progress };
+        let keys = serde::Deserializer::deserialize_seq(&mut de, sink)?;
+        de.end()?;
+        self.set_all_keys_with_durability(Arc::new(keys), Durability::MEDIUM);
+        Ok(())
+    }
+
     pub fn extend_references(&mut self, refs: Vec<Reference>) {
         let keys = self.all_keys();
         let mut keys = IndexSet::clone(&keys);
         for r in refs {
+            let r = self.prepare_reference(r);
             keys.insert(r.id.clone());
             self.set_reference_input_with_durability(r.id.clone(), Arc::new(r), Durability::MEDIUM);
         }
@@ -398,6 +936,7 @@ impl Processor {
         let keys = self.all_keys();
         let mut keys = IndexSet::clone(&keys);
         keys.insert(refr.id.clone());
+        let refr = self.prepare_reference(refr);
         self.set_reference_input_with_durability(
             refr.id.clone(),
             Arc::new(refr),
@@ -413,6 +952,26 @@ impl Processor {
         self.set_all_keys_with_durability(Arc::new(keys), Durability::MEDIUM);
     }
 
+    /// Continues citation numbering from a previous part of the document, e.g. a previous chapter
+    /// processed by another `Processor`. With an offset of 12, the first reference is numbered 13.
+    pub fn set_citation_number_offset(&mut self, offset: u32) {
+        self.set_citation_number_offset_with_durability(offset, Durability::MEDIUM);
+    }
+
+    /// Changes the [InitOptions::locale_override] after the fact, e.g. when the document's
+    /// language changes. `None` goes back to the style's `default-locale`. Any locales the new
+    /// one needs must be fetched again; see [Processor::get_langs_in_use].
+    pub fn set_locale_override(&mut self, lang: Option<Lang>) {
+        self.set_default_lang_override_with_durability(lang, Durability::HIGH);
+    }
+
+    /// Sets how multilingual variants of reference fields (from a CSL-JSON `multi` block) are
+    /// rendered. By default, only the original values are used. Names have no variants, and always
+    /// render as written.
+    pub fn set_lang_prefs_for_cites(&mut self, prefs: LangPrefs) {
+        self.set_lang_prefs_with_durability(Arc::new(prefs), Durability::MEDIUM);
+    }
+
     pub fn include_uncited(&mut self, uncited: IncludeUncited) {
         let db_uncited = match uncited {
             IncludeUncited::All => Uncited::All,
@@ -424,13 +983,23 @@ impl Processor {
         self.set_all_uncited_with_durability(Arc::new(db_uncited), Durability::MEDIUM);
     }
 
-    pub fn init_clusters(&mut self, clusters: Vec<Cluster>) {
+    /// Replaces every cluster in the document. Nothing is changed if a cluster id is given twice,
+    /// or a cite has an empty reference id.
+    pub fn init_clusters(&mut self, clusters: Vec<Cluster>) -> Result<(), ClusterError> {
+        let mut seen = FnvHashSet::default();
+        for cluster in &clusters {
+            if !seen.insert(cluster.id) {
+                return Err(ClusterError::DuplicateClusterId(cluster.id));
+            }
+            check_cites(cluster.id, &cluster.cites)?;
+        }
         let mut cluster_ids = Vec::new();
         for cluster in clusters {
             let Cluster {
                 id: cluster_id,
                 cites,
                 mode,
+                suppress_trailing_punctuation,
             } = cluster;
             let mut ids = Vec::with_capacity(cites.len());
             for (index, cite) in cites.into_iter().enumerate() {
@@ -445,38 +1014,55 @@ impl Processor {
             self.set_cluster_cites(raw, Arc::new(ids));
             self.set_cluster_note_number(raw, None);
             self.set_cluster_mode(raw, mode);
+            self.set_cluster_suppress_trailing_punctuation(raw, suppress_trailing_punctuation);
             cluster_ids.push(raw);
         }
         self.set_cluster_ids(Arc::new(cluster_ids));
+        Ok(())
     }
 
-    pub fn init_clusters_str(&mut self, clusters: Vec<string_id::Cluster>) {
-        let mut cluster_ids = Vec::new();
-        let interner_arc = self.interner.clone();
-        let mut interner = interner_arc.write();
-        for cluster in clusters {
-            let string_id::Cluster {
-                id: cluster_id,
-                cites,
-                mode,
-            } = cluster;
-            let cluster_id = ClusterId::new(interner.get_or_intern(cluster_id));
-            let mut ids = Vec::with_capacity(cites.len());
-            for (index, cite) in cites.into_iter().enumerate() {
-                let cite_id = self.cite(CiteData::RealCite {
-                    cluster: cluster_id.raw(),
-                    index: index as u32,
-                    cite: Arc::new(cite),
-                });
-                ids.push(cite_id);
+    pub fn init_clusters_str(
+        &mut self,
+        clusters: Vec<string_id::Cluster>,
+    ) -> Result<(), string_id::ClusterError> {
+        let clusters = clusters
+            .into_iter()
+            .map(|cluster| self.intern_cluster(cluster))
+            .collect();
+        self.init_clusters(clusters).map_err(|e| {
+            let reader = self.interner.read();
+            e.to_external(&reader)
+        })
+    }
+
+    /// Checks the clusters already in the document, and returns every problem found. The methods
+    /// that add clusters reject most of these problems up front, so an empty list is the normal
+    /// result. The exception is [ClusterError::DuplicateCiteInCluster], which is only reported
+    /// here, as citing the same thing twice in a cluster may be deliberate.
+    ///
+    /// Cites are only compared within their own cluster. Citing the same reference in several
+    /// clusters is normal, and is not reported.
+    pub fn validate_document(&self) -> Vec<ClusterError> {
+        let mut errors = Vec::new();
+        let mut seen_clusters = FnvHashSet::default();
+        for &raw in self.cluster_ids().iter() {
+            let cluster = ClusterId::new(raw);
+            if !seen_clusters.insert(raw) {
+                errors.push(ClusterError::DuplicateClusterId(cluster));
+                continue;
+            }
+            let mut seen_cites = FnvHashSet::default();
+            for (index, &cite_id) in self.cluster_cites(raw).iter().enumerate() {
+                let index = index as u32;
+                let cite = cite_id.lookup(self);
+                if cite.ref_id.is_empty() {
+                    errors.push(ClusterError::EmptyRefId { cluster, index });
+                } else if !seen_cites.insert((cite.ref_id.clone(), cite.locators.clone())) {
+                    errors.push(ClusterError::DuplicateCiteInCluster { cluster, index });
+                }
             }
-            let raw = cluster_id.raw();
-            self.set_cluster_cites(raw, Arc::new(ids));
-            self.set_cluster_note_number(raw, None);
-            self.set_cluster_mode(raw, mode);
-            cluster_ids.push(raw);
         }
-        self.set_cluster_ids(Arc::new(cluster_ids));
+        errors
     }
 
     // cluster_ids is maintained manually
@@ -487,6 +1073,7 @@ impl Processor {
         self.set_cluster_cites(raw, Arc::new(Vec::new()));
         self.set_cluster_note_number(raw, None);
         self.set_cluster_mode(raw, None);
+        self.set_cluster_suppress_trailing_punctuation(raw, false);
         let cluster_ids = self.cluster_ids();
         let cluster_ids: Vec<_> = (*cluster_ids)
             .iter()
@@ -510,6 +1097,7 @@ impl Processor {
             self.set_cluster_ids(Arc::new(new_cluster_ids));
             self.set_cluster_note_number(raw, None);
             self.set_cluster_mode(raw, None);
+            self.set_cluster_suppress_trailing_punctuation(raw, false);
         }
 
         let mut ids = Vec::new();
@@ -524,39 +1112,228 @@ impl Processor {
         self.set_cluster_cites(raw, Arc::new(ids));
     }
 
-    pub fn insert_cluster(&mut self, cluster: Cluster) {
+    /// Inserts or replaces the cluster with a matching `id`. Nothing is changed if a cite has an
+    /// empty reference id.
+    pub fn insert_cluster(&mut self, cluster: Cluster) -> Result<(), ClusterError> {
         let Cluster {
             id: cluster_id,
             cites,
             mode,
+            suppress_trailing_punctuation,
         } = cluster;
+        check_cites(cluster_id, &cites)?;
         self.insert_cites_only(cluster_id, cites);
         self.set_cluster_mode(cluster_id.raw(), mode);
+        self.set_cluster_suppress_trailing_punctuation(
+            cluster_id.raw(),
+            suppress_trailing_punctuation,
+        );
+        Ok(())
     }
 
     fn intern_cluster(&mut self, cluster: string_id::Cluster) -> Cluster {
-        let string_id::Cluster { id, cites, mode } = cluster;
+        let string_id::Cluster {
+            id,
+            cites,
+            mode,
+            suppress_trailing_punctuation,
+        } = cluster;
         let interned = self.intern_cluster_id(id);
         Cluster {
             id: interned,
             cites,
             mode,
+            suppress_trailing_punctuation,
         }
     }
 
-    pub fn insert_cluster_str(&mut self, cluster: string_id::Cluster) {
+    pub fn insert_cluster_str(
+        &mut self,
+        cluster: string_id::Cluster,
+    ) -> Result<(), string_id::ClusterError> {
         let cluster = self.intern_cluster(cluster);
-        self.insert_cluster(cluster)
+        self.insert_cluster(cluster).map_err(|e| {
+            let reader = self.interner.read();
+            e.to_external(&reader)
+        })
     }
 
-    pub fn insert_cites(&mut self, cluster_id: ClusterId, cites: &[Cite<Markup>]) {
+    pub fn insert_cites(
+        &mut self,
+        cluster_id: ClusterId,
+        cites: &[Cite<Markup>],
+    ) -> Result<(), ClusterError> {
+        check_cites(cluster_id, cites)?;
         let cites = cites.to_owned();
         self.insert_cites_only(cluster_id, cites);
+        Ok(())
     }
 
-    pub fn insert_cites_str(&mut self, cluster_id: &str, cites: &[Cite<Markup>]) {
+    pub fn insert_cites_str(
+        &mut self,
+        cluster_id: &str,
+        cites: &[Cite<Markup>],
+    ) -> Result<(), string_id::ClusterError> {
         let interned = self.intern_cluster_id(cluster_id);
-        self.insert_cites(interned, cites);
+        self.insert_cites(interned, cites).map_err(|e| {
+            let reader = self.interner.read();
+            e.to_external(&reader)
+        })
+    }
+
+    /// Rebinds one cite to a different reference, e.g. to fix a wrong pick in a citation dialog.
+    /// The cite keeps its locator, affixes and mode, and the other cites in the cluster are left
+    /// alone. `index` is the cite's position in the cluster as it was inserted. The cluster's new
+    /// text comes out in the next [Processor::batched_updates].
+    pub fn swap_cite_reference(
+        &mut self,
+        cluster_id: ClusterId,
+        index: u32,
+        new_ref_id: Atom,
+    ) -> Result<(), ClusterError> {
+        let mut cite = (*self.existing_cite_id(cluster_id, index)?.lookup(self)).clone();
+        cite.ref_id = new_ref_id;
+        self.replace_cite(cluster_id, index, cite)
+    }
+
+    /// What cite `index` of a cluster renders as, or would render as, when it is author-only. A
+    /// host can use this to leave out an author-only cite that has no printed form, or one that
+    /// fell back to a title with [AuthorOnlySubstitute::Any], rather than show it.
+    pub fn author_only_outcome(
+        &self,
+        cluster_id: ClusterId,
+        index: u32,
+    ) -> Result<AuthorOnlyOutcome, ClusterError> {
+        let cite_id = self.existing_cite_id(cluster_id, index)?;
+        Ok(IrDatabase::author_only_outcome(self, cite_id))
+    }
+
+    fn existing_cite_id(&self, cluster_id: ClusterId, index: u32) -> Result<CiteId, ClusterError> {
+        let raw = cluster_id.raw();
+        let no_such_cite = ClusterError::NoSuchCite {
+            cluster: cluster_id,
+            index,
+        };
+        if !self.cluster_ids().contains(&raw) {
+            return Err(no_such_cite);
+        }
+        self.cluster_cites(raw)
+            .get(index as usize)
+            .copied()
+            .ok_or(no_such_cite)
+    }
+
+    pub fn swap_cite_reference_str(
+        &mut self,
+        cluster_id: &str,
+        index: u32,
+        new_ref_id: Atom,
+    ) -> Result<(), string_id::ClusterError> {
+        let interned = match self.cluster_id_of(cluster_id) {
+            Some(interned) => interned,
+            None => {
+                let cluster = cluster_id.into();
+                return Err(string_id::ClusterError::NoSuchCite { cluster, index });
+            }
+        };
+        self.swap_cite_reference(interned, index, new_ref_id)
+            .map_err(|e| {
+                let reader = self.interner.read();
+                e.to_external(&reader)
+            })
+    }
+
+    /// Replaces the cite at `index` in an existing cluster, leaving the other cites alone.
+    pub fn replace_cite(
+        &mut self,
+        cluster_id: ClusterId,
+        index: u32,
+        cite: Cite<Markup>,
+    ) -> Result<(), ClusterError> {
+        let old_id = self.existing_cite_id(cluster_id, index)?;
+        if cite.ref_id.is_empty() {
+            return Err(ClusterError::EmptyRefId {
+                cluster: cluster_id,
+                index,
+            });
+        }
+        let raw = cluster_id.raw();
+        let new_id = self.cite(CiteData::RealCite {
+            cluster: raw,
+            index,
+            cite: Arc::new(cite),
+        });
+        if new_id != old_id {
+            let mut new_ids = (*self.cluster_cites(raw)).clone();
+            new_ids[index as usize] = new_id;
+            self.set_cluster_cites(raw, Arc::new(new_ids));
+        }
+        Ok(())
+    }
+
+    /// Replaces the whole cite list of an existing cluster, without resubmitting its mode or
+    /// moving it in the document. A cite that is unchanged and still at the same index keeps its
+    /// [CiteId], and with it everything already computed for it. Unlike
+    /// [Processor::insert_cites], this does not create the cluster if it is missing.
+    pub fn replace_cluster_cites(
+        &mut self,
+        cluster_id: ClusterId,
+        cites: Vec<Cite<Markup>>,
+    ) -> Result<(), ClusterError> {
+        let raw = cluster_id.raw();
+        if !self.cluster_ids().contains(&raw) {
+            return Err(ClusterError::NoSuchCluster(cluster_id));
+        }
+        check_cites(cluster_id, &cites)?;
+        let new_ids: Vec<CiteId> = cites
+            .into_iter()
+            .enumerate()
+            .map(|(index, cite)| {
+                self.cite(CiteData::RealCite {
+                    cluster: raw,
+                    index: index as u32,
+                    cite: Arc::new(cite),
+                })
+            })
+            .collect();
+        if *self.cluster_cites(raw) != new_ids {
+            self.set_cluster_cites(raw, Arc::new(new_ids));
+        }
+        Ok(())
+    }
+
+    pub fn replace_cite_str(
+        &mut self,
+        cluster_id: &str,
+        index: u32,
+        cite: Cite<Markup>,
+    ) -> Result<(), string_id::ClusterError> {
+        let interned = match self.cluster_id_of(cluster_id) {
+            Some(interned) => interned,
+            None => {
+                let cluster = cluster_id.into();
+                return Err(string_id::ClusterError::NoSuchCite { cluster, index });
+            }
+        };
+        self.replace_cite(interned, index, cite).map_err(|e| {
+            let reader = self.interner.read();
+            e.to_external(&reader)
+        })
+    }
+
+    pub fn replace_cluster_cites_str(
+        &mut self,
+        cluster_id: &str,
+        cites: Vec<Cite<Markup>>,
+    ) -> Result<(), string_id::ClusterError> {
+        let interned = match self.cluster_id_of(cluster_id) {
+            Some(interned) => interned,
+            None => return Err(string_id::ClusterError::NoSuchCluster(cluster_id.into())),
+        };
+        self.replace_cluster_cites(interned, cites).map_err(|e| {
+            let reader = self.interner.read();
+            e.to_external(&reader)
+        })
     }
 
     // Getters, because the query groups have too much exposed to publish.
@@ -570,6 +1347,19 @@ impl Processor {
         }
     }
 
+    /// The formatted cluster as a tree of inline elements, just before it would be written out as
+    /// HTML, RTF or plain text. Serialize it to see exactly where formatting and quotes ended up,
+    /// or walk it to write another output format. Returns None if the cluster has not been
+    /// assigned a position in the document.
+    ///
+    /// Each cite is wrapped in an [InlineElement::Cite] carrying its index in the cluster as it
+    /// was inserted, so a host can map a click on the output back to the cite to edit.
+    pub fn built_cluster_ast(&self, cluster_id: ClusterId) -> Option<Vec<InlineElement>> {
+        self.cluster_note_number(cluster_id.raw())?;
+        let tree = citeproc_proc::db::built_cluster_tree(self, cluster_id.raw());
+        Some(tree)
+    }
+
     pub fn get_cluster_note_number(&self, cluster_id: ClusterId) -> Option<ClusterNumber> {
         self.cluster_note_number(cluster_id.raw())
     }
@@ -581,15 +1371,73 @@ impl Processor {
     }
 
     pub fn get_bib_item(&self, ref_id: Atom) -> Arc<MarkupOutput> {
-        self.bib_item(ref_id)
+        let value = self.bib_item(ref_id.clone());
+        self.decorate_bib_entry(&ref_id, value, &self.sorted_refs().1)
+    }
+
+    /// Replaces the [BibEntryTemplate] set in [InitOptions::bib_entry_template]. Every entry that
+    /// changes as a result is included in the next [UpdateSummary].
+    pub fn set_bib_entry_template(&mut self, template: Option<BibEntryTemplate>) {
+        self.bib_entry_template = template;
+    }
+
+    /// Empty entries are left empty, so they are still reported as errors.
+    fn decorate_bib_entry(
+        &self,
+        ref_id: &Atom,
+        value: Arc<MarkupOutput>,
+        bib_numbers: &FnvHashMap<Atom, BibNumber>,
+    ) -> Arc<MarkupOutput> {
+        match &self.bib_entry_template {
+            Some(template) if !value.is_empty() => {
+                let number = bib_numbers
+                    .get(ref_id)
+                    .map(|n| n.get() + self.citation_number_offset());
+                Arc::new(template.decorate(&self.formatter, ref_id, number, &value))
+            }
+            _ => value,
+        }
+    }
+
+    fn decorated_bibliography_map(&self) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
+        let map = self.get_bibliography_map();
+        if self.bib_entry_template.is_none() {
+            return map;
+        }
+        let sorted_refs = self.sorted_refs();
+        let decorated = map
+            .iter()
+            .map(|(k, v)| {
+                let value = self.decorate_bib_entry(k, v.clone(), &sorted_refs.1);
+                (k.clone(), value)
+            })
+            .collect();
+        Arc::new(decorated)
+    }
+
+    /// Reports which document metadata the current style depends on, e.g. whether clusters need
+    /// note numbers at all.
+    pub fn style_requirements(&self) -> StyleRequirements {
+        citeproc_proc::style_requirements(&self.style())
+    }
+
+    /// Whether the current style could render, test or sort by a variable at all, for any reference.
+    /// If not, editing that field of a reference cannot change the output.
+    pub fn style_uses_variable(&self, var: AnyVariable) -> bool {
+        self.style_variables().contains(&var)
+    }
+
+    /// Which fields of a reference the current style renders, in a cite and in its bibliography
+    /// entry. Returns None if the reference is not in the library.
+    pub fn used_variables(&self, ref_id: Atom) -> Option<UsedVariables> {
+        citeproc_proc::used_variables(self, ref_id)
     }
 
     pub fn get_bibliography_meta(&self) -> Option<BibliographyMeta> {
         let style = self.get_style();
         style.bibliography.as_ref().map(|bib| {
             BibliographyMeta {
-                // TODO
-                max_offset: 0,
+                max_offset: self.bib_max_offset(),
                 entry_spacing: bib.entry_spacing,
                 line_spacing: bib.line_spaces,
                 hanging_indent: bib.hanging_indent,
@@ -608,22 +1456,36 @@ impl Processor {
             return None;
         }
         let mut last_bibliography = self.last_bibliography.lock();
-        let new = self.get_bibliography_map();
+        let new = self.decorated_bibliography_map();
+        let content = self.get_bibliography_content_map();
         let old = std::mem::replace(&mut *last_bibliography, SavedBib::new());
         let mut update = BibliographyUpdate::new();
         for (k, v) in new.iter() {
             let old_v = old.bib_entries.get(k);
             if Some(v) != old_v {
                 update.updated_entries.insert(k.clone(), v.clone());
+                if let Some(c) = content.get(k) {
+                    update.updated_content.insert(k.clone(), c.clone());
+                }
             }
         }
+        update.removed_entries = old
+            .bib_entries
+            .keys()
+            .filter(|k| !new.contains_key(*k))
+            .cloned()
+            .collect();
+        update.removed_entries.sort();
         last_bibliography.bib_entries = new;
         let sorted_refs = self.sorted_refs();
         if sorted_refs.0 != old.sorted_refs.0 {
             update.entry_ids = Some(sorted_refs.0.clone());
         }
         last_bibliography.sorted_refs = sorted_refs;
-        if update.updated_entries.is_empty() && update.entry_ids.is_none() {
+        if update.updated_entries.is_empty()
+            && update.entry_ids.is_none()
+            && update.removed_entries.is_empty()
+        {
             None
         } else {
             Some(update)
@@ -659,8 +1521,37 @@ impl Processor {
         mapping
     }
 
+    /// The clusters that have been assigned a position, in document order, with their output.
+    /// Unlike [Processor::all_clusters], nothing is collected up front; each cluster is fetched
+    /// when the iterator reaches it.
+    pub fn iter_clusters(&self) -> impl Iterator<Item = (ClusterId, Arc<MarkupOutput>)> + '_ {
+        let clusters = self.clusters_sorted();
+        (0..clusters.len()).map(move |ix| {
+            let raw = clusters[ix].id;
+            (ClusterId::new(raw), self.built_cluster(raw))
+        })
+    }
+
+    /// The cites in a cluster, in the order they were given. Yields nothing if there is no such
+    /// cluster.
+    pub fn iter_cites_of(
+        &self,
+        cluster_id: ClusterId,
+    ) -> impl Iterator<Item = Arc<Cite<Markup>>> + '_ {
+        let raw = cluster_id.raw();
+        let cites = if self.cluster_ids().contains(&raw) {
+            self.cluster_cites(raw)
+        } else {
+            Arc::new(Vec::new())
+        };
+        (0..cites.len()).map(move |ix| cites[ix].lookup(self))
+    }
+
+    /// Returns an empty Vec for a style with no `<bibliography>`, the same as when nothing has been
+    /// cited. See [Processor::get_bibliography_if_any] to tell the two apart.
     pub fn get_bibliography(&self) -> Vec<BibEntry> {
-        let bib_map = self.get_bibliography_map();
+        let bib_map = self.decorated_bibliography_map();
+        let paragraph_props = self.paragraph_props();
         self.sorted_refs()
             .0
             .iter()
@@ -674,10 +1565,40 @@ impl Processor {
                 } else {
                     v.clone()
                 },
+                content: Some(k)
+                    .filter(|_| !v.is_empty())
+                    .and_then(|k| self.bib_item_content(k.clone())),
+                paragraph_props,
             })
             .collect()
     }
 
+    /// The plain-text values each bibliography entry was sorted by, in bibliography order, for
+    /// hosts that group the bibliography under headings, e.g. by the first letter of the author or
+    /// by year. See [SortKeyValues].
+    pub fn bib_sort_keys(&self) -> Vec<(Atom, SortKeyValues)> {
+        citeproc_proc::bib_sort_keys(self)
+    }
+
+    /// Returns None if the style has no `<bibliography>` element, e.g. a note style that only
+    /// formats citations, so a host can hide its bibliography UI instead of showing it empty.
+    pub fn get_bibliography_if_any(&self) -> Option<Vec<BibEntry>> {
+        self.get_style().bibliography.as_ref()?;
+        Some(self.get_bibliography())
+    }
+
+    /// Only RTF output gets [ParagraphProps]; HTML has CSS for this, and plain text has no
+    /// paragraphs to speak of.
+    fn paragraph_props(&self) -> Option<ParagraphProps> {
+        match self.formatter {
+            Markup::Rtf(_) => {}
+            _ => return None,
+        }
+        let style = self.get_style();
+        let bib = style.bibliography.as_ref()?;
+        Some(ParagraphProps::new(bib, self.bib_max_offset()))
+    }
+
     pub fn get_reference(&self, ref_id: Atom) -> Option<Arc<Reference>> {
         self.reference(ref_id)
     }
@@ -686,9 +1607,28 @@ impl Processor {
         self.style()
     }
 
+    /// The parsed style tree as JSON, for tools (linters, style editors) that want to analyse a
+    /// style exactly as the processor read it. The output is the same every time for the same
+    /// style:
+    ///
+    /// - Structs are objects with their field names, and tuple structs are arrays.
+    /// - Enums with data, like elements and conditions, are `{ "type": ..., "value": ... }` with
+    ///   the variant name in kebab-case, e.g. `{ "type": "text", "value": { "source": ... } }`.
+    ///   A variant holding several values has them in an array.
+    /// - Enums without data are kebab-case strings, mostly the same as the CSL attribute values.
+    /// - Macros are an object sorted by name, and the conditions in a condition set are sorted.
+    /// - The style's `<locale>` overrides appear as locale XML, keyed by language, with `""` for
+    ///   the one without an `xml:lang`.
+    pub fn style_ast_json(&self) -> serde_json::Value {
+        serde_json::to_value(&*self.style()).expect("style AST serializes to JSON")
+    }
+
+    /// Stores locale XML for each `Lang`. The langs are normalized (see [`Lang::parse_bcp47`]) so
+    /// that they line up with the style's and references' languages.
     pub fn store_locales(&mut self, locales: Vec<(Lang, String)>) {
         let mut langs = (*self.locale_input_langs()).clone();
         for (lang, xml) in locales {
+            let lang = lang.normalized();
             langs.insert(lang.clone());
             self.set_locale_input_xml_with_durability(lang, Arc::new(xml), Durability::HIGH);
         }
@@ -707,6 +1647,14 @@ impl Processor {
         let langs = self.locale_input_langs();
         langs.contains(lang)
     }
+
+    /// The locale `lang` actually renders with, as locale XML: the fetched locale files for it
+    /// and its fallbacks, merged with the style's own `<locale>` overrides. Store it alongside a
+    /// document and pass it back through [Processor::store_locales] to reproduce the output
+    /// later, even if the upstream locale files have since changed.
+    pub fn export_effective_locale(&self, lang: Lang) -> String {
+        self.merged_locale(lang).to_xml()
+    }
 }
 
 /// Stores all the relevant #[salsa::input] entries from CiteDatabase.
@@ -790,6 +1738,28 @@ impl Processor {
         cites: &[Cite<Markup>],
         position: PreviewPosition<'a>,
         format: Option<SupportedFormat>,
+    ) -> Result<Arc<MarkupOutput>, ReorderingError> {
+        self.preview_citation_cluster_inner(cites, position, format, None)
+    }
+
+    /// [Processor::preview_citation_cluster], but with `delimiter` between cites in place of the
+    /// delimiter on the style's citation `<layout>`. The style itself is not changed.
+    pub fn preview_citation_cluster_with_delimiter<'a>(
+        &mut self,
+        cites: &[Cite<Markup>],
+        position: PreviewPosition<'a>,
+        format: Option<SupportedFormat>,
+        delimiter: &str,
+    ) -> Result<Arc<MarkupOutput>, ReorderingError> {
+        self.preview_citation_cluster_inner(cites, position, format, Some(delimiter))
+    }
+
+    fn preview_citation_cluster_inner<'a>(
+        &mut self,
+        cites: &[Cite<Markup>],
+        position: PreviewPosition<'a>,
+        format: Option<SupportedFormat>,
+        delimiter: Option<&str>,
     ) -> Result<Arc<MarkupOutput>, ReorderingError> {
         let (id, state) = match position {
             PreviewPosition::ReplaceCluster(cluster_id) => {
@@ -817,11 +1787,17 @@ impl Processor {
             }
             PreviewPosition::MarkWithZero(positions) => self.preview_marked_init(positions)?,
         };
-        self.insert_cites(id, cites);
+        // A preview is thrown away, so it doesn't need checking
+        self.insert_cites_only(id, cites.to_owned());
         let formatter = format
-            .map(|fmt| fmt.make_markup())
+            .map(|fmt| {
+                fmt.make_markup()
+                    .with_small_caps(self.formatter.small_caps())
+                    .with_links(self.formatter.links())
+            })
             .unwrap_or_else(|| self.formatter.clone());
-        let markup = citeproc_proc::db::built_cluster_preview(self, id.raw(), &formatter);
+        let markup =
+            citeproc_proc::db::built_cluster_preview(self, id.raw(), &formatter, delimiter);
         self.restore_cluster_state(state);
         Ok(markup)
     }
@@ -910,12 +1886,92 @@ impl Processor {
             })
     }
 
+    /// Mirrors citeproc-js's `processCitationCluster`, for integrations moving over from it.
+    /// Inserts or replaces `cluster`, and places it between the clusters in `pre` and `post`.
+    /// Those are `(id, note index)` pairs in document order, like citeproc-js's `citationsPre`
+    /// and `citationsPost`; a note index of 0 means an in-text cluster, as does a `note_index`
+    /// of 0 for `cluster` itself. Clusters in neither list are dropped from the document order.
+    ///
+    /// Returns `(index, output, id)` for every cluster whose output changed, where `index` is
+    /// its position in the document. These changes are not reported again by the next
+    /// [Processor::batched_updates].
+    pub fn process_citation_cluster(
+        &mut self,
+        cluster: string_id::Cluster,
+        note_index: u32,
+        pre: &[(SmartString, u32)],
+        post: &[(SmartString, u32)],
+    ) -> Result<Vec<(usize, Arc<SmartString>, SmartString)>, string_id::ProcessClusterError> {
+        let compat_position = |id: &SmartString, note_index: u32| string_id::ClusterPosition {
+            id: Some(id.clone()),
+            note: Some(note_index).filter(|&n| n > 0),
+        };
+        let mut positions: Vec<_> = pre.iter().map(|(id, n)| compat_position(id, *n)).collect();
+        positions.push(compat_position(&cluster.id, note_index));
+        positions.extend(post.iter().map(|(id, n)| compat_position(id, *n)));
+        // Before inserting, so that a bad order leaves the document as it was
+        check_note_order(positions.iter().map(|pos| pos.note))
+            .map_err(string_id::ReorderingError::from)?;
+        self.insert_cluster_str(cluster)?;
+        self.set_cluster_order_str(&positions)?;
+        Ok(self.compat_updates())
+    }
+
+    /// Mirrors citeproc-js's `appendCitationCluster`: inserts `cluster` and places it after every
+    /// other cluster in the document. See [Processor::process_citation_cluster] for the rest.
+    pub fn append_citation_cluster(
+        &mut self,
+        cluster: string_id::Cluster,
+        note_index: u32,
+    ) -> Result<Vec<(usize, Arc<SmartString>, SmartString)>, string_id::ProcessClusterError> {
+        let pre: Vec<_> = {
+            let interner = self.interner.read();
+            self.cluster_ids()
+                .iter()
+                .filter_map(|&raw| {
+                    let id = SmartString::from(interner.resolve(raw)?);
+                    let note_index = match self.cluster_note_number(raw) {
+                        Some(ClusterNumber::Note(intra)) => intra.note_number(),
+                        _ => 0,
+                    };
+                    Some((id, note_index))
+                })
+                .collect()
+        };
+        self.process_citation_cluster(cluster, note_index, &pre, &[])
+    }
+
+    fn compat_updates(&self) -> Vec<(usize, Arc<SmartString>, SmartString)> {
+        let summary = self.batched_updates();
+        let order: FnvHashMap<_, usize> = self
+            .cluster_ids()
+            .iter()
+            .enumerate()
+            .map(|(index, &raw)| (raw, index))
+            .collect();
+        let interner = self.interner.read();
+        let mut updates: Vec<_> = summary
+            .clusters
+            .into_iter()
+            .filter_map(|(id, output)| {
+                let index = *order.get(&id.raw())?;
+                let string = interner.resolve(id.raw())?;
+                Some((index, output, SmartString::from(string)))
+            })
+            .collect();
+        updates.sort_by_key(|(index, ..)| *index);
+        updates
+    }
+
     /// Variant of the above that allows logging the changes.
     pub fn set_cluster_order_inner<T: std::borrow::Borrow<ClusterPosition>>(
         &mut self,
         positions: impl ExactSizeIterator<Item = T>,
         mut mods: impl FnMut(ClusterId, Option<ClusterNumber>),
     ) -> Result<(), ReorderingError> {
+        // Checked up front, so that an error doesn't leave some of the note numbers changed
+        let positions: Vec<T> = positions.collect();
+        check_note_order(positions.iter().map(|piece| piece.borrow().note))?;
         let old_cluster_ids = self.cluster_ids();
         let mut cluster_ids = Vec::with_capacity(positions.len());
         let mut intext_number = 1u32;
@@ -925,9 +1981,6 @@ impl Processor {
             let piece = piece.borrow();
             if let Some(nn) = piece.note {
                 if let Some(ref mut note) = this_note {
-                    if nn < note.0 {
-                        return Err(ReorderingError::NonMonotonicNoteNumber(nn));
-                    }
                     if old_cluster_ids.contains(&piece.id.raw()) {
                         mods(piece.id, self.cluster_note_number(piece.id.raw()));
                     }
@@ -968,3 +2021,59 @@ impl Processor {
         Ok(())
     }
 }
+
+/// Inserts references from a CSL-JSON array as they are parsed, for
+/// [Processor::set_references_from_reader].
+struct ReferenceSink<'a, F> {
+    db: &'a mut Processor,
+    progress: F,
+}
+
+impl<'de, 'a, F: FnMut(usize)> serde::de::Visitor<'de> for ReferenceSink<'a, F> {
+    type Value = IndexSet<Atom>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an array of CSL-JSON references")
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut keys = IndexSet::new();
+        let mut count = 0;
+        while let Some(refr) = seq.next_element::<Reference>()? {
+            let refr = self.db.prepare_reference(refr);
+            let id = refr.id.clone();
+            keys.insert(id.clone());
+            self.db
+                .set_reference_input_with_durability(id, Arc::new(refr), Durability::MEDIUM);
+            count += 1;
+            (self.progress)(count);
+        }
+        Ok(keys)
+    }
+}
+
+/// Rejects note numbers that go backwards, e.g. `[1, 2, 3, 1]`. In-text clusters can go anywhere.
+fn check_note_order(notes: impl Iterator<Item = Option<u32>>) -> Result<(), ReorderingError> {
+    let mut last = 0;
+    for nn in notes.flatten() {
+        if nn < last {
+            return Err(ReorderingError::NonMonotonicNoteNumber(nn));
+        }
+        last = nn;
+    }
+    Ok(())
+}
+
+/// Rejects cites that don't refer to anything.
+fn check_cites(cluster_id: ClusterId, cites: &[Cite<Markup>]) -> Result<(), ClusterError> {
+    match cites.iter().position(|cite| cite.ref_id.is_empty()) {
+        Some(index) => Err(ClusterError::EmptyRefId {
+            cluster: cluster_id,
+            index: index as u32,
+        }),
+        None => Ok(()),
+    }
+}
@@ -57,13 +57,14 @@ fn insert_ascending_notes(db: &mut Processor, ref_ids: &[&str]) {
             id,
             cites: vec![Cite::basic(ref_ids[i - 1])],
             mode: None,
+            suppress_trailing_punctuation: false,
         });
         order.push(ClusterPosition {
             id,
             note: Some(i as u32),
         });
     }
-    db.init_clusters(clusters);
+    db.init_clusters(clusters).unwrap();
     db.set_cluster_order(&order).unwrap();
 }
 
@@ -72,12 +73,17 @@ mod position {
 
     use csl::Position;
 
+    const NOTE_STYLE: &str = r#"<style version="1.0" class="note">
+                                    <citation><layout></layout></citation>
+                                  </style>"#;
+
     fn test_ibid_1_2(
+        style: Option<&str>,
         ordering: impl Fn(ClusterId, ClusterId) -> Vec<ClusterPosition>,
         pos1: (Position, Option<u32>),
         pos2: (Position, Option<u32>),
     ) {
-        let mut db = test_db(None);
+        let mut db = test_db(style);
         let one = cid(&mut db, 1);
         let two = cid(&mut db, 2);
         db.init_clusters(vec![
@@ -85,13 +91,16 @@ mod position {
                 id: one,
                 cites: vec![Cite::basic("one")],
                 mode: None,
+                suppress_trailing_punctuation: false,
             },
             Cluster {
                 id: two,
                 cites: vec![Cite::basic("one")],
                 mode: None,
+                suppress_trailing_punctuation: false,
             },
-        ]);
+        ])
+        .unwrap();
         db.set_cluster_order(&ordering(one, two)).unwrap();
         let poss = db.cite_positions();
         // Get the single cites inside
@@ -105,6 +114,7 @@ mod position {
     #[test]
     fn cite_positions_note_ibid() {
         test_ibid_1_2(
+            Some(NOTE_STYLE),
             |one, two| {
                 vec![
                     ClusterPosition {
@@ -125,6 +135,7 @@ mod position {
     #[test]
     fn cite_positions_intext_ibid() {
         test_ibid_1_2(
+            None,
             |one, two| {
                 vec![
                     // both in-text
@@ -147,6 +158,7 @@ mod position {
     #[test]
     fn cite_positions_mixed_noibid() {
         test_ibid_1_2(
+            Some(NOTE_STYLE),
             |one, two| {
                 vec![
                     ClusterPosition {
@@ -167,6 +179,7 @@ mod position {
     #[test]
     fn cite_positions_mixed_notefirst() {
         test_ibid_1_2(
+            Some(NOTE_STYLE),
             |one, two| {
                 vec![
                     ClusterPosition {
@@ -187,8 +200,90 @@ mod position {
     }
 
     #[test]
-    fn cite_positions_near_note() {
+    fn cite_positions_intext_style_note_after_text() {
+        test_ibid_1_2(
+            None,
+            |one, two| {
+                vec![
+                    ClusterPosition {
+                        id: one,
+                        note: None,
+                    },
+                    ClusterPosition {
+                        id: two,
+                        note: Some(1),
+                    },
+                ]
+            },
+            (Position::First, None),
+            // An in-text style reads a footnote as part of the text around it.
+            (Position::Ibid, None),
+        );
+    }
+
+    #[test]
+    fn cite_positions_intext_style_note_first() {
+        test_ibid_1_2(
+            None,
+            |one, two| {
+                vec![
+                    ClusterPosition {
+                        id: one,
+                        note: Some(1),
+                    },
+                    ClusterPosition {
+                        id: two,
+                        note: None,
+                    },
+                ]
+            },
+            (Position::First, None),
+            (Position::Ibid, None),
+        );
+    }
+
+    #[test]
+    fn cite_positions_intext_style_mixed_document() {
         let mut db = test_db(None);
+        let ids: Vec<ClusterId> = (1..=5).map(|n| cid(&mut db, n)).collect();
+        let clusters = ids
+            .iter()
+            .zip(&["one", "one", "other", "one", "one"])
+            .map(|(&id, &ref_id)| Cluster {
+                id,
+                cites: vec![Cite::basic(ref_id)],
+                mode: None,
+                suppress_trailing_punctuation: false,
+            })
+            .collect();
+        db.init_clusters(clusters).unwrap();
+        let notes = [Some(1), None, Some(2), None, Some(3)];
+        let order: Vec<ClusterPosition> = ids
+            .iter()
+            .zip(&notes)
+            .map(|(&id, &note)| ClusterPosition { id, note })
+            .collect();
+        db.set_cluster_order(&order).unwrap();
+        let poss = db.cite_positions();
+        let positions: Vec<_> = ids
+            .iter()
+            .map(|id| poss[&db.cluster_cites(id.raw())[0]])
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (Position::First, None),
+                (Position::Ibid, None),
+                (Position::First, None),
+                (Position::Subsequent, None),
+                (Position::Ibid, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn cite_positions_near_note() {
+        let mut db = test_db(Some(NOTE_STYLE));
         insert_ascending_notes(&mut db, &["one", "other", "one"]);
         let one = cid(&mut db, 1);
         let two = cid(&mut db, 2);
@@ -201,6 +296,35 @@ mod position {
         assert_eq!(poss[&id2], (Position::First, None));
         assert_eq!(poss[&id3], (Position::NearNote, Some(1)));
     }
+
+    #[test]
+    fn cite_positions_same_note_ibid() {
+        let mut db = test_db(Some(NOTE_STYLE));
+        let ids: Vec<ClusterId> = (1..=3).map(|n| cid(&mut db, n)).collect();
+        let clusters = ids
+            .iter()
+            .zip(&["other", "one", "one"])
+            .map(|(&id, &ref_id)| Cluster {
+                id,
+                cites: vec![Cite::basic(ref_id)],
+                mode: None,
+                suppress_trailing_punctuation: false,
+            })
+            .collect();
+        db.init_clusters(clusters).unwrap();
+        let order: Vec<ClusterPosition> = ids
+            .iter()
+            .map(|&id| ClusterPosition { id, note: Some(1) })
+            .collect();
+        db.set_cluster_order(&order).unwrap();
+        let poss = db.cite_positions();
+        let id2 = db.cluster_cites(ids[1].raw())[0];
+        let id3 = db.cluster_cites(ids[2].raw())[0];
+        assert_eq!(poss[&id2], (Position::First, None));
+        // The immediately preceding cluster in the same note cites the same reference, even though
+        // the note as a whole does not.
+        assert_eq!(poss[&id3], (Position::IbidNear, Some(1)));
+    }
 }
 
 mod preview {
@@ -333,123 +457,2768 @@ mod preview {
         assert_cluster!(db.get_cluster(two), Some("Book two"));
         assert_cluster!(db.get_cluster(marker), None);
     }
+
+    #[test]
+    fn preview_cluster_delimiter() {
+        let mut db = mk_db();
+        let one = cid(&mut db, 1);
+        let cites = vec![Cite::basic("one"), Cite::basic("three")];
+        let position = || PreviewPosition::ReplaceCluster(one);
+        let preview = db.preview_citation_cluster_with_delimiter(&cites, position(), None, " | ");
+        assert_cluster!(preview.ok(), Some("Book one | Book three"));
+        let preview = db.preview_citation_cluster(&cites, position(), None);
+        assert_cluster!(preview.ok(), Some("Book one; Book three"));
+        assert_cluster!(db.get_cluster(one), Some("Book one"));
+    }
 }
 
-mod terms {
+mod small_caps {
     use super::*;
 
-    fn en_au() -> Lang {
-        Lang::Iso(IsoLang::English, Some(IsoCountry::AU))
+    const STYLE: &'static str = r##"
+    <style class="note" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="title" font-variant="small-caps" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn built(format: SupportedFormat, small_caps: SmallCapsFallback) -> Option<String> {
+        let mut db = Processor::new(InitOptions {
+            style: STYLE,
+            format,
+            small_caps,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let c = cid(&mut db, 1);
+        db.get_cluster(c).map(|s| s.to_string())
     }
 
-    fn terms(xml: &str) -> String {
-        format!(
-            r#"<?xml version="1.0" encoding="utf-8"?>
-        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US">
-        <terms>{}</terms></locale>"#,
-            xml
-        )
+    #[test]
+    fn plain() {
+        let plain = SupportedFormat::Plain;
+        let book = Some("Book one".to_string());
+        assert_eq!(built(plain, SmallCapsFallback::Native), book);
+        assert_eq!(built(plain, SmallCapsFallback::Ignore), book);
+        assert_eq!(
+            built(plain, SmallCapsFallback::Uppercase),
+            Some("BOOK ONE".to_string())
+        );
     }
 
-    use citeproc_db::PredefinedLocales;
-    fn predefined_xml(pairs: &[(Lang, &str)]) -> PredefinedLocales {
-        let mut map = HashMap::new();
-        for (lang, ts) in pairs {
-            map.insert(lang.clone(), terms(ts));
-        }
-        PredefinedLocales(map)
+    #[test]
+    fn rtf() {
+        let rtf = SupportedFormat::Rtf;
+        assert_eq!(
+            built(rtf, SmallCapsFallback::Native),
+            Some(r"{\scaps Book one}".to_string())
+        );
+        assert_eq!(
+            built(rtf, SmallCapsFallback::Ignore),
+            Some("{Book one}".to_string())
+        );
+        assert_eq!(
+            built(rtf, SmallCapsFallback::Uppercase),
+            Some("{BOOK ONE}".to_string())
+        );
     }
+}
 
-    fn term_and(form: TermFormExtended) -> SimpleTermSelector {
-        SimpleTermSelector::Misc(MiscTerm::And, form)
+mod cluster_ast {
+    use super::*;
+
+    #[test]
+    fn before_serialization() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation><layout><text variable="title" font-style="italic" /></layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one"]);
+        let id = cid(&mut db, 1);
+        assert_eq!(db.built_cluster_ast(id), None);
+        insert_ascending_notes(&mut db, &["one"]);
+        let ast = db.built_cluster_ast(id).unwrap();
+        let json = serde_json::to_string(&ast).unwrap();
+        // Plain text output drops the italics, but the tree still has them
+        assert_eq!(db.get_cluster(id).unwrap().as_str(), "Book one");
+        assert!(json.contains(r#"{"font_style":"Italic"}"#));
+        assert!(json.contains(r#"{"Text":"Book one"}"#));
     }
 
-    fn test_simple_term(term: SimpleTermSelector, langs: &[(Lang, &str)], expect: Option<&str>) {
-        let db = Processor::safe_default(Arc::new(predefined_xml(langs)));
-        // use en-AU so it has to do fallback to en-US
-        let locale = db.merged_locale(en_au());
+    #[test]
+    fn cites_tagged_with_their_index() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation>
+                    <sort><key variable="title" sort="descending" /></sort>
+                    <layout delimiter="; "><text variable="title" /></layout>
+                </citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        let id = cid(&mut db, 1);
+        let see_one = Cite {
+            prefix: Some(MarkupInput::Text("see".into())),
+            ..Cite::basic("one")
+        };
+        db.insert_cluster(Cluster {
+            id,
+            cites: vec![see_one, Cite::basic("two")],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        })
+        .unwrap();
+        db.set_cluster_order(&[ClusterPosition { id, note: None }])
+            .unwrap();
+        assert_cluster!(db.get_cluster(id), Some("Book two; see Book one"));
+
+        let cites: Vec<(u32, String)> = db
+            .built_cluster_ast(id)
+            .unwrap()
+            .into_iter()
+            .filter_map(|inline| match inline {
+                InlineElement::Cite { index, content } => {
+                    Some((index, Markup::plain().output(content, false).to_string()))
+                }
+                _ => None,
+            })
+            .collect();
         assert_eq!(
-            locale.get_text_term(TextTermSelector::Simple(term), false),
-            expect
-        )
+            cites,
+            vec![(1, "Book two".into()), (0, "see Book one".into())]
+        );
     }
+}
+
+mod cluster_ids {
+    use super::*;
 
     #[test]
-    fn term_override() {
-        test_simple_term(
-            term_and(TermFormExtended::Long),
-            &[
-                (Lang::en_us(), r#"<term name="and">USA</term>"#),
-                (en_au(), r#"<term name="and">Australia</term>"#),
-            ],
-            Some("Australia"),
-        )
+    fn string_round_trip() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        db.insert_cluster_str(string_id::Cluster {
+            id: "cluster-A".into(),
+            cites: vec![Cite::basic("one")],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        })
+        .unwrap();
+        let id = db.cluster_id_of("cluster-A").unwrap();
+        assert_eq!(db.cluster_str_of(id).as_deref(), Some("cluster-A"));
+        assert_eq!(db.cluster_id_of("cluster-B"), None);
+        let b = db.new_cluster("cluster-B");
+        assert_eq!(db.cluster_id_of("cluster-B"), Some(b));
     }
 
     #[test]
-    fn term_form_refine() {
-        test_simple_term(
-            term_and(TermFormExtended::Long),
-            &[
-                (Lang::en_us(), r#"<term name="and">USA</term>"#),
-                (en_au(), r#"<term name="and" form="short">Australia</term>"#),
-            ],
-            Some("USA"),
+    fn iterators() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation><layout delimiter="; "><text variable="title" /></layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["two", "one"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        db.set_cluster_order(&[
+            ClusterPosition {
+                id: two,
+                note: Some(1),
+            },
+            ClusterPosition {
+                id: one,
+                note: Some(2),
+            },
+        ])
+        .unwrap();
+        let clusters: Vec<(ClusterId, String)> = db
+            .iter_clusters()
+            .map(|(id, built)| (id, built.to_string()))
+            .collect();
+        assert_eq!(
+            clusters,
+            vec![(two, "Book one".to_string()), (one, "Book two".to_string())]
         );
-        test_simple_term(
-            term_and(TermFormExtended::Short),
-            &[
-                (Lang::en_us(), r#"<term name="and">USA</term>"#),
-                (en_au(), r#"<term name="and" form="short">Australia</term>"#),
-            ],
-            Some("Australia"),
+        let ref_ids: Vec<Atom> = db.iter_cites_of(one).map(|c| c.ref_id.clone()).collect();
+        assert_eq!(ref_ids, vec![Atom::from("two")]);
+        let unknown = db.new_cluster("unknown");
+        assert_eq!(db.iter_cites_of(unknown).count(), 0);
+    }
+}
+
+mod cluster_errors {
+    use super::*;
+
+    fn cluster(id: ClusterId, ref_ids: &[&str]) -> Cluster {
+        Cluster::new(id, ref_ids.iter().map(|&r| Cite::basic(r)).collect())
+    }
+
+    #[test]
+    fn duplicate_cluster_id() {
+        let mut db = test_db(None);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        db.init_clusters(vec![cluster(one, &["a"])]).unwrap();
+        let result = db.init_clusters(vec![
+            cluster(two, &["a"]),
+            cluster(one, &["b"]),
+            cluster(two, &["c"]),
+        ]);
+        assert_eq!(result, Err(ClusterError::DuplicateClusterId(two)));
+        // Nothing was changed
+        assert_eq!(*db.cluster_ids(), vec![one.raw()]);
+        assert!(db.validate_document().is_empty());
+    }
+
+    #[test]
+    fn duplicate_cite() {
+        let mut db = test_db(None);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let at_five: Cite<Markup> = serde_json::from_str(r#"{"id":"a","locator":"5"}"#).unwrap();
+        let mut with_locator = cluster(two, &["a"]);
+        with_locator.cites.push(at_five);
+        // Inserting is allowed, but the document reports it
+        db.init_clusters(vec![cluster(one, &["a", "b", "a"]), with_locator])
+            .unwrap();
+        assert_eq!(
+            db.validate_document(),
+            vec![ClusterError::DuplicateCiteInCluster {
+                cluster: one,
+                index: 2
+            }]
         );
     }
 
     #[test]
-    fn term_form_fallback() {
-        test_simple_term(
-            term_and(TermFormExtended::Short),
-            &[
-                (Lang::en_us(), r#"<term name="and">USA</term>"#),
-                (en_au(), r#"<term name="and">Australia</term>"#),
-            ],
-            Some("Australia"),
+    fn empty_ref_id() {
+        let mut db = test_db(None);
+        let one = cid(&mut db, 1);
+        let empty = Err(ClusterError::EmptyRefId {
+            cluster: one,
+            index: 1,
+        });
+        assert_eq!(db.insert_cluster(cluster(one, &["a", ""])), empty);
+        assert_eq!(
+            db.insert_cites(one, &[Cite::basic("a"), Cite::basic("")]),
+            empty
         );
-        test_simple_term(
-            // short falls back to long and skips the "symbol" in a later locale
-            term_and(TermFormExtended::Short),
-            &[
-                (Lang::en_us(), r#"<term name="and">USA</term>"#),
-                (
-                    en_au(),
-                    r#"<term name="and" form="symbol">Australia</term>"#,
-                ),
-            ],
-            Some("USA"),
+        assert_eq!(db.init_clusters(vec![cluster(one, &["a", ""])]), empty);
+        assert!(db.cluster_ids().is_empty());
+    }
+
+    #[test]
+    fn string_ids() {
+        let mut db = test_db(None);
+        let result = db.insert_cluster_str(string_id::Cluster {
+            id: "cluster-A".into(),
+            cites: vec![Cite::basic("")],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        });
+        assert!(matches!(
+            result,
+            Err(string_id::ClusterError::EmptyRefId { cluster, index: 0 }) if cluster == "cluster-A"
+        ));
+    }
+}
+
+mod citation_order {
+    use super::*;
+
+    #[test]
+    fn first_occurrence_only_in_library() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["a", "b", "c"]);
+        insert_ascending_notes(&mut db, &["c", "missing", "a", "c"]);
+        db.include_uncited(IncludeUncited::All);
+        assert_eq!(
+            db.cited_keys_in_citation_order(),
+            vec![Atom::from("c"), Atom::from("a")]
         );
-        test_simple_term(
-            term_and(TermFormExtended::VerbShort),
-            &[
-                (Lang::en_us(), r#"<term name="and" form="long">USA</term>"#),
-                (
-                    en_au(),
-                    r#"<term name="and" form="symbol">Australia</term>"#,
-                ),
-            ],
-            Some("USA"),
+    }
+}
+
+mod missing_references {
+    use super::*;
+
+    #[test]
+    fn lists_cited_ids_not_in_library() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["two", "one", "three", "two"]);
+        let missing = vec![Atom::from("two"), Atom::from("three")];
+        assert_eq!(db.missing_references(), missing);
+        let summary = db.batched_updates();
+        assert_eq!(
+            summary.warnings,
+            vec![UpdateWarning::MissingReferences { ids: missing }]
         );
+
+        insert_basic_refs(&mut db, &["two", "three"]);
+        assert!(db.missing_references().is_empty());
+        assert!(db.batched_updates().warnings.is_empty());
+    }
+
+    fn rendered(policy: MissingReferencePolicy) -> Option<String> {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="note">
+                <citation><layout><text variable="title" /></layout></citation>
+            </style>"#,
+            format: SupportedFormat::Plain,
+            missing_reference_policy: policy,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_ascending_notes(&mut db, &["smith2008"]);
+        let c = cid(&mut db, 1);
+        db.get_cluster(c).map(|s| s.to_string())
     }
 
     #[test]
-    fn term_locale_fallback() {
-        test_simple_term(
-            term_and(TermFormExtended::Long),
-            &[
-                (Lang::en_us(), r#"<term name="and">USA</term>"#),
-                (en_au(), r#""#),
-            ],
-            Some("USA"),
-        )
+    fn policy() {
+        use MissingReferencePolicy::*;
+        assert_eq!(rendered(QuestionMarks), Some("???".to_string()));
+        assert_eq!(rendered(BracketedKey), Some("[smith2008?]".to_string()));
+        assert_eq!(rendered(Empty), Some("".to_string()));
+        assert_eq!(
+            rendered(Template("(missing: {id})".into())),
+            Some("(missing: smith2008)".to_string())
+        );
+    }
+}
+
+#[test]
+fn citation_number_offset() {
+    let mut db = test_db(Some(
+        r#"<style version="1.0" class="in-text">
+            <citation collapse="citation-number">
+                <sort><key variable="citation-number" /></sort>
+                <layout prefix="[" suffix="]" delimiter=",">
+                    <text variable="citation-number" />
+                </layout>
+            </citation>
+            <bibliography><layout><text variable="citation-number" /></layout></bibliography>
+        </style>"#,
+    ));
+    insert_basic_refs(&mut db, &["one", "two", "three"]);
+    let one = cid(&mut db, 1);
+    let two = cid(&mut db, 2);
+    db.init_clusters(vec![
+        Cluster {
+            id: one,
+            cites: vec![Cite::basic("one")],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        },
+        Cluster {
+            id: two,
+            cites: vec![Cite::basic("two"), Cite::basic("three"), Cite::basic("one")],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        },
+    ])
+    .unwrap();
+    db.set_cluster_order(&[
+        ClusterPosition {
+            id: one,
+            note: None,
+        },
+        ClusterPosition {
+            id: two,
+            note: None,
+        },
+    ])
+    .unwrap();
+    db.set_citation_number_offset(12);
+    assert_cluster!(db.get_cluster(one), Some("[13]"));
+    assert_cluster!(db.get_cluster(two), Some("[13–15]"));
+}
+
+#[test]
+fn locale_quotes_in_every_format() {
+    for &format in &[
+        SupportedFormat::Html,
+        SupportedFormat::Rtf,
+        SupportedFormat::Plain,
+    ] {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="note">
+                <locale>
+                    <terms>
+                        <term name="open-quote">[[</term>
+                        <term name="close-quote">]]</term>
+                    </terms>
+                </locale>
+                <citation><layout><text variable="title" quotes="true" /></layout></citation>
+            </style>"#,
+            format,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let c = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(c), Some("[[Book one]]"));
+    }
+}
+
+mod unknown_types {
+    use super::*;
+
+    fn rendered(fallback: Option<CslType>) -> Option<String> {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="note">
+                <citation><layout>
+                    <choose>
+                        <if type="book"><text value="book" /></if>
+                        <else><text value="other" /></else>
+                    </choose>
+                </layout></citation>
+            </style>"#,
+            format: SupportedFormat::Plain,
+            unknown_type_fallback: fallback,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let refr: Reference =
+            serde_json::from_str(r#"{ "id": "one", "type": "some-new-type" }"#).unwrap();
+        db.insert_reference(refr);
+        insert_ascending_notes(&mut db, &["one"]);
+        assert_eq!(
+            db.get_reference("one".into())
+                .and_then(|r| r.original_type.clone()),
+            Some("some-new-type".to_string())
+        );
+        let c = cid(&mut db, 1);
+        db.get_cluster(c).map(|s| s.to_string())
+    }
+
+    #[test]
+    fn fallback() {
+        assert_eq!(rendered(None), Some("other".to_string()));
+        assert_eq!(rendered(Some(CslType::Book)), Some("book".to_string()));
+    }
+}
+
+mod verbatim_affixes {
+    use super::*;
+
+    fn built(verbatim_affixes: bool) -> Arc<SmartString> {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="note">
+                <citation><layout><text variable="title" /></layout></citation>
+            </style>"#,
+            format: SupportedFormat::Html,
+            verbatim_affixes,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one"]);
+        let link = "<a href=\"#one\">see</a>";
+        let id = cid(&mut db, 1);
+        db.insert_cluster(Cluster {
+            id,
+            cites: vec![Cite {
+                prefix: Some(MarkupInput::Verbatim {
+                    verbatim: link.into(),
+                }),
+                suffix: Some(MarkupInput::Text(link.into())),
+                ..Cite::basic("one")
+            }],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        })
+        .unwrap();
+        db.set_cluster_order(&[ClusterPosition { id, note: Some(1) }])
+            .unwrap();
+        db.get_cluster(id).unwrap()
+    }
+
+    #[test]
+    fn passed_through_unescaped() {
+        let built = built(true);
+        assert!(built.starts_with("<a href=\"#one\">see</a> Book one"));
+        // Ordinary affixes are still escaped
+        assert_eq!(built.matches("<a href").count(), 1);
+    }
+
+    #[test]
+    fn escaped_unless_enabled() {
+        let built = built(false);
+        assert!(built.starts_with("&lt;a href"));
+        assert_eq!(built.matches("<a href").count(), 0);
+    }
+}
+
+mod cite_affixes {
+    use super::*;
+
+    fn built(
+        class: &str,
+        prefix: &str,
+        suffix: &str,
+        raw_cite_affixes: bool,
+        cite_capitalization: CiteCapitalization,
+    ) -> String {
+        let style = format!(
+            r#"<style version="1.0" class="{}">
+                <citation><layout>
+                    <group delimiter=" "><text term="ibid" /><text variable="title" /></group>
+                </layout></citation>
+            </style>"#,
+            class
+        );
+        let mut db = Processor::new(InitOptions {
+            style: &style,
+            format: SupportedFormat::Plain,
+            raw_cite_affixes,
+            cite_capitalization,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one"]);
+        let id = cid(&mut db, 1);
+        db.insert_cluster(Cluster {
+            id,
+            cites: vec![Cite {
+                prefix: Some(MarkupInput::Text(prefix.into())),
+                suffix: Some(MarkupInput::Text(suffix.into())),
+                ..Cite::basic("one")
+            }],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        })
+        .unwrap();
+        let note = if class == "note" { Some(1) } else { None };
+        db.set_cluster_order(&[ClusterPosition { id, note }])
+            .unwrap();
+        db.get_cluster(id).unwrap().to_string()
+    }
+
+    const SENTENCE: &str = "see the discussion above.";
+
+    #[test]
+    fn spaced() {
+        use CiteCapitalization::*;
+        assert_eq!(
+            built("in-text", SENTENCE, "at 5", false, NoteStyles),
+            "see the discussion above. ibid. Book one at 5"
+        );
+        assert_eq!(
+            built("in-text", SENTENCE, "at 5", true, NoteStyles),
+            "see the discussion above.ibid. Book oneat 5"
+        );
+    }
+
+    #[test]
+    fn capitalized() {
+        use CiteCapitalization::*;
+        assert_eq!(
+            built("note", SENTENCE, "at 5", false, NoteStyles),
+            "see the discussion above. Ibid. Book one at 5"
+        );
+        assert_eq!(
+            built("note", SENTENCE, "at 5", false, Never),
+            "see the discussion above. ibid. Book one at 5"
+        );
+        assert_eq!(
+            built("in-text", SENTENCE, "at 5", false, Always),
+            "see the discussion above. Ibid. Book one at 5"
+        );
+        // A one-word prefix is an abbreviation, not the end of a sentence
+        assert_eq!(
+            built("note", "cf.", "at 5", false, NoteStyles),
+            "cf. ibid. Book one at 5"
+        );
+    }
+
+    #[test]
+    fn raw_but_capitalized() {
+        assert_eq!(
+            built(
+                "note",
+                "see the discussion above. ",
+                ", at 5",
+                true,
+                CiteCapitalization::NoteStyles
+            ),
+            "see the discussion above. Ibid. Book one, at 5"
+        );
+    }
+}
+
+mod trailing_punctuation {
+    use super::*;
+
+    fn built(layout: &str, suppress_trailing_punctuation: bool) -> String {
+        let style = format!(
+            r#"<style class="note" version="1.0.1">
+                <citation><layout suffix=".">{}</layout></citation>
+            </style>"#,
+            layout
+        );
+        let mut db = test_db(Some(&style));
+        insert_basic_refs(&mut db, &["one"]);
+        let id = cid(&mut db, 1);
+        db.insert_cluster(Cluster {
+            id,
+            cites: vec![Cite::basic("one")],
+            mode: None,
+            suppress_trailing_punctuation,
+        })
+        .unwrap();
+        db.set_cluster_order(&[ClusterPosition { id, note: Some(1) }])
+            .unwrap();
+        db.get_cluster(id).unwrap().to_string()
+    }
+
+    #[test]
+    fn full_stop() {
+        let layout = r#"<text variable="title" />"#;
+        assert_eq!(built(layout, false), "Book one.");
+        assert_eq!(built(layout, true), "Book one");
+    }
+
+    #[test]
+    fn inside_quotes() {
+        let layout = r#"<text variable="title" quotes="true" />"#;
+        assert_eq!(built(layout, false), "\u{201c}Book one.\u{201d}");
+        assert_eq!(built(layout, true), "\u{201c}Book one\u{201d}");
+    }
+
+    #[test]
+    fn not_before_a_closing_bracket() {
+        let layout = r#"<text variable="title" /><text value="(ed.)" prefix=" " />"#;
+        assert_eq!(built(layout, false), "Book one (ed.).");
+        assert_eq!(built(layout, true), "Book one (ed.)");
+    }
+}
+
+mod serialization {
+    use super::*;
+
+    #[test]
+    fn supported_format_names_and_mime_types() {
+        for format in SupportedFormat::all() {
+            assert_eq!(format.name().parse(), Ok(*format));
+        }
+        let parse = |s: &str| s.parse::<SupportedFormat>();
+        assert_eq!(parse("text/html"), Ok(SupportedFormat::Html));
+        assert_eq!(parse("text/html; charset=utf-8"), Ok(SupportedFormat::Html));
+        assert_eq!(parse("application/rtf"), Ok(SupportedFormat::Rtf));
+        assert_eq!(parse("text/RTF"), Ok(SupportedFormat::Rtf));
+        assert_eq!(parse("text/plain"), Ok(SupportedFormat::Plain));
+        assert_eq!(parse("image/png"), Err(()));
+        assert_eq!(SupportedFormat::TestHtml.mime_type(), "text/html");
+    }
+
+    #[test]
+    fn maps_sorted_by_key() {
+        let mut update = BibliographyUpdate::new();
+        for id in &["c", "a", "b"] {
+            let out = Arc::new(id.to_uppercase());
+            update.updated_entries.insert(Atom::from(*id), out);
+        }
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"updatedEntries":{"a":"A","b":"B","c":"C"},"entryIds":null}"#
+        );
+
+        let mut render = string_id::FullRender::default();
+        for id in &["2", "10", "1"] {
+            let out = Arc::new(SmartString::from(*id));
+            render.all_clusters.insert(SmartString::from(*id), out);
+        }
+        assert_eq!(
+            serde_json::to_string(&render).unwrap(),
+            r#"{"allClusters":{"1":"1","10":"10","2":"2"},"bibEntries":[]}"#
+        );
+    }
+
+    /// The wasm crate's TypeScript definitions describe these shapes by hand, so a change here
+    /// needs a matching change there.
+    #[test]
+    fn shapes_match_typescript() {
+        let mut summary = string_id::UpdateSummary::<Markup>::default();
+        assert_eq!(
+            serde_json::to_string(&summary).unwrap(),
+            r#"{"clusters":[],"bibliography":null}"#
+        );
+        summary.bibliography = Some(BibliographyUpdate::new());
+        assert_eq!(
+            serde_json::to_string(&summary).unwrap(),
+            r#"{"clusters":[],"bibliography":{"updatedEntries":{},"entryIds":null}}"#
+        );
+
+        let cluster: string_id::Cluster = serde_json::from_str(
+            r#"{"id":"a","cites":[{"id":"r","locator":"5"}],"mode":"AuthorOnly","suppressTrailingPunctuation":true}"#,
+        )
+        .unwrap();
+        assert_eq!(cluster.id.as_str(), "a");
+        assert_eq!(cluster.mode, Some(citeproc_io::ClusterMode::AuthorOnly));
+        assert!(cluster.suppress_trailing_punctuation);
+
+        let position: string_id::ClusterPosition = serde_json::from_str(r#"{"note":1}"#).unwrap();
+        assert_eq!(position.id, None);
+        assert_eq!(
+            serde_json::to_string(&position).unwrap(),
+            r#"{"id":null,"note":1}"#
+        );
+    }
+
+    #[test]
+    fn clusters_round_trip() {
+        let json = r#"[
+            {"id":"a","cites":[{"id":"r1","prefix":"see ","locator":"5","label":"chapter"}]},
+            {"id":"b","cites":[{"id":"r2","mode":"SuppressAuthor"}],"mode":"AuthorOnly"},
+            {"id":"c","cites":[{"id":"r3","suffix":{"verbatim":"<i>"},"locators":[
+                {"locator":"1","label":"page"},{"locator":2,"label":"sub-verbo"}
+            ]}],"mode":"Composite","infix":", who said","suppressFirst":1}
+        ]"#;
+        let clusters: Vec<string_id::Cluster> = serde_json::from_str(json).unwrap();
+        let serialized = serde_json::to_string(&clusters).unwrap();
+        let round_tripped: Vec<string_id::Cluster> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(clusters, round_tripped);
+        assert_eq!(
+            serde_json::to_string(&clusters[0]).unwrap(),
+            r#"{"id":"a","cites":[{"id":"r1","prefix":"see ","locator":"5","label":"chapter"}],"suppressTrailingPunctuation":false}"#
+        );
+    }
+
+    #[test]
+    fn locator_label_in_string() {
+        let json = r#"[
+            {"id":"r1","locator":"pp. 12-14"},
+            {"id":"r1","locator":"§§ 4-5"},
+            {"id":"r1","locator":"12-14","label":"page"},
+            {"id":"r1","locator":"vol. 2","label":"book"},
+            {"id":"r1","locator":"Preface"}
+        ]"#;
+        let cites: Vec<Cite<Markup>> = serde_json::from_str(json).unwrap();
+        let locators: Vec<_> = cites
+            .iter()
+            .map(|cite| {
+                let locator = cite.locators.as_ref().unwrap().single().unwrap();
+                (locator.type_of(), locator.value().clone().into_string())
+            })
+            .collect();
+        assert_eq!(
+            locators,
+            vec![
+                (LocatorType::Page, "12-14".to_owned()),
+                (LocatorType::Section, "4-5".to_owned()),
+                (LocatorType::Page, "12-14".to_owned()),
+                (LocatorType::Book, "vol. 2".to_owned()),
+                (LocatorType::Page, "Preface".to_owned()),
+            ]
+        );
+        assert_eq!(cites[0], cites[2]);
+    }
+}
+
+mod batch {
+    use super::*;
+
+    fn setup() -> (Processor, ClusterId, ClusterId) {
+        let mut db = test_db(Some(
+            r#"<style class="note" version="1.0.1">
+                <citation><layout><text variable="title" /></layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two", "three"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let _ = db.batched_updates();
+        let (one, two) = (cid(&mut db, 1), cid(&mut db, 2));
+        (db, one, two)
+    }
+
+    #[test]
+    fn coalesces_edits() {
+        let (mut db, one, two) = setup();
+        db.begin_batch();
+        db.insert_cites(one, &[Cite::basic("two")]).unwrap();
+        assert!(db.batched_updates().clusters.is_empty());
+        db.insert_cites(one, &[Cite::basic("three")]).unwrap();
+        db.insert_cites(two, &[Cite::basic("one")]).unwrap();
+        db.insert_cites(two, &[Cite::basic("two")]).unwrap();
+        assert!(db.batched_updates().clusters.is_empty());
+        let summary = db.end_batch();
+        assert_eq!(
+            summary.clusters,
+            vec![(one, Arc::new(SmartString::from("Book three")))]
+        );
+        assert!(db.batched_updates().clusters.is_empty());
+    }
+
+    #[test]
+    fn nested() {
+        let (mut db, one, _) = setup();
+        db.begin_batch();
+        db.begin_batch();
+        db.insert_cites(one, &[Cite::basic("three")]).unwrap();
+        assert!(db.end_batch().clusters.is_empty());
+        assert_eq!(db.end_batch().clusters.len(), 1);
+    }
+
+    #[test]
+    fn subscribers_see_each_summary() {
+        let (mut db, one, _) = setup();
+        let seen = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen2 = seen.clone();
+        db.subscribe(Box::new(move |summary: &UpdateSummary| {
+            seen2.lock().push(summary.clusters.clone());
+        }));
+        db.begin_batch();
+        db.insert_cites(one, &[Cite::basic("two")]).unwrap();
+        let _ = db.batched_updates();
+        db.insert_cites(one, &[Cite::basic("three")]).unwrap();
+        assert!(seen.lock().is_empty());
+        let _ = db.end_batch_str();
+        let _ = db.batched_updates();
+        // Not called again for the second, empty summary
+        assert_eq!(
+            *seen.lock(),
+            vec![vec![(one, Arc::new(SmartString::from("Book three")))]]
+        );
+    }
+}
+
+mod swap_cite_reference {
+    use super::*;
+
+    fn setup() -> (Processor, ClusterId) {
+        let mut db = test_db(Some(
+            r#"<style class="note" version="1.0.1">
+                <citation><layout delimiter="; "><text variable="title" /></layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two", "three"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &[Cite::basic("one"), Cite::basic("two")])
+            .unwrap();
+        let _ = db.batched_updates();
+        (db, one)
+    }
+
+    #[test]
+    fn rebinds_one_cite() {
+        let (mut db, one) = setup();
+        let before = db.cluster_cites(one.raw());
+        db.swap_cite_reference(one, 1, Atom::from("three")).unwrap();
+        let after = db.cluster_cites(one.raw());
+        assert_eq!(before[0], after[0]);
+        assert_eq!(after[1].lookup(&db).ref_id, Atom::from("three"));
+        assert_eq!(
+            db.batched_updates().clusters,
+            vec![(one, Arc::new(SmartString::from("Book one; Book three")))]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let (mut db, one) = setup();
+        assert_eq!(
+            db.swap_cite_reference(one, 2, Atom::from("three")),
+            Err(ClusterError::NoSuchCite {
+                cluster: one,
+                index: 2
+            })
+        );
+        assert_eq!(
+            db.swap_cite_reference(one, 0, Atom::from("")),
+            Err(ClusterError::EmptyRefId {
+                cluster: one,
+                index: 0
+            })
+        );
+        let unused = cid(&mut db, 9);
+        assert_eq!(
+            db.swap_cite_reference(unused, 0, Atom::from("three")),
+            Err(ClusterError::NoSuchCite {
+                cluster: unused,
+                index: 0
+            })
+        );
+        assert!(matches!(
+            db.swap_cite_reference_str("nope", 0, Atom::from("three")),
+            Err(string_id::ClusterError::NoSuchCite { cluster, index: 0 }) if cluster == "nope"
+        ));
+        assert!(db.batched_updates().clusters.is_empty());
+    }
+}
+
+mod replace_cluster_cites {
+    use super::*;
+
+    fn setup() -> (Processor, ClusterId) {
+        let mut db = test_db(Some(
+            r#"<style class="note" version="1.0.1">
+                <citation><layout delimiter="; "><text variable="title" /></layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two", "three"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &[Cite::basic("one"), Cite::basic("two")])
+            .unwrap();
+        let _ = db.batched_updates();
+        (db, one)
+    }
+
+    #[test]
+    fn keeps_unchanged_cite_ids() {
+        let (mut db, one) = setup();
+        let before = db.cluster_cites(one.raw());
+        db.replace_cluster_cites(
+            one,
+            vec![Cite::basic("one"), Cite::basic("two"), Cite::basic("three")],
+        )
+        .unwrap();
+        let after = db.cluster_cites(one.raw());
+        assert_eq!(before[..], after[..2]);
+        assert_eq!(
+            db.batched_updates().clusters,
+            vec![(
+                one,
+                Arc::new(SmartString::from("Book one; Book two; Book three"))
+            )]
+        );
+
+        let cite: Cite<Markup> = serde_json::from_str(r#"{"id":"two","locator":"5"}"#).unwrap();
+        db.replace_cite(one, 1, cite).unwrap();
+        let patched = db.cluster_cites(one.raw());
+        assert_eq!((patched[0], patched[2]), (after[0], after[2]));
+        assert_ne!(patched[1], after[1]);
+    }
+
+    #[test]
+    fn unchanged_is_not_an_update() {
+        let (mut db, one) = setup();
+        db.replace_cluster_cites(one, vec![Cite::basic("one"), Cite::basic("two")])
+            .unwrap();
+        db.replace_cite(one, 0, Cite::basic("one")).unwrap();
+        assert!(db.batched_updates().clusters.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let (mut db, one) = setup();
+        let unused = cid(&mut db, 9);
+        assert_eq!(
+            db.replace_cluster_cites(unused, vec![Cite::basic("one")]),
+            Err(ClusterError::NoSuchCluster(unused))
+        );
+        assert_eq!(
+            db.replace_cluster_cites(one, vec![Cite::basic("one"), Cite::basic("")]),
+            Err(ClusterError::EmptyRefId {
+                cluster: one,
+                index: 1
+            })
+        );
+        assert_eq!(
+            db.replace_cite(one, 2, Cite::basic("three")),
+            Err(ClusterError::NoSuchCite {
+                cluster: one,
+                index: 2
+            })
+        );
+        assert!(matches!(
+            db.replace_cluster_cites_str("nope", vec![]),
+            Err(string_id::ClusterError::NoSuchCluster(cluster)) if cluster == "nope"
+        ));
+        assert!(db.batched_updates().clusters.is_empty());
+    }
+}
+
+mod no_bibliography {
+    use super::*;
+
+    #[test]
+    fn distinguished_from_empty() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        assert!(db.get_bibliography().is_empty());
+        assert_eq!(db.get_bibliography_if_any(), None);
+        assert!(db.get_bibliography_meta().is_none());
+
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography><layout><text variable="title" /></layout></bibliography>
+            </style>"#,
+        ));
+        assert_eq!(db.get_bibliography_if_any(), Some(vec![]));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        assert_eq!(db.get_bibliography_if_any().map(|bib| bib.len()), Some(1));
+    }
+}
+
+mod paragraph_props {
+    use super::*;
+
+    fn rtf_db(bib_attrs: &str) -> Processor {
+        let style = format!(
+            r#"<style class="in-text" version="1.0.1">
+                <citation><layout><text variable="citation-number" /></layout></citation>
+                <bibliography {}>
+                    <layout>
+                        <text variable="citation-number" prefix="[" suffix="]" display="left-margin" />
+                        <text variable="title" display="right-inline" />
+                    </layout>
+                </bibliography>
+            </style>"#,
+            bib_attrs
+        );
+        let mut db = Processor::new(InitOptions {
+            style: &style,
+            format: SupportedFormat::Rtf,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let ids: Vec<String> = (1..=10).map(|i| i.to_string()).collect();
+        let ids: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+        insert_basic_refs(&mut db, &ids);
+        insert_ascending_notes(&mut db, &ids);
+        db
+    }
+
+    #[test]
+    fn second_field_align() {
+        let db = rtf_db(r#"second-field-align="flush""#);
+        assert_eq!(db.get_bibliography_meta().unwrap().max_offset, 4);
+        let bib = db.get_bibliography();
+        assert!(bib[0].value.starts_with("{[1]}\\tab "));
+        assert_eq!(
+            bib[9].paragraph_props,
+            Some(ParagraphProps {
+                left_indent: 600,
+                first_line_indent: -600,
+                tab_stop: Some(600),
+                line_spacing: 1,
+                entry_spacing: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn hanging_indent() {
+        let db = rtf_db(r#"hanging-indent="true""#);
+        let props = db.get_bibliography()[0].paragraph_props.unwrap();
+        assert_eq!((props.left_indent, props.first_line_indent), (720, -720));
+        assert_eq!(props.tab_stop, None);
+    }
+}
+
+mod bib_entry_content {
+    use super::*;
+
+    #[test]
+    fn without_layout_affixes() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography>
+                    <layout prefix="* " suffix="."><text variable="title" /></layout>
+                </bibliography>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "why?"]);
+        insert_ascending_notes(&mut db, &["one", "why?"]);
+        let bib = db.get_bibliography();
+        let pieces: Vec<(&str, Option<&str>)> = bib
+            .iter()
+            .map(|e| (e.value.as_str(), e.content.as_deref().map(|c| c.as_str())))
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                ("* Book one.", Some("Book one")),
+                // The layout suffix doesn't double up on the entry's own punctuation
+                ("* Book why?", Some("Book why?")),
+            ]
+        );
+    }
+
+    #[test]
+    fn none_without_layout_affixes() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography><layout><text variable="title" /></layout></bibliography>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        assert_eq!(db.get_bibliography()[0].content, None);
+    }
+
+    #[test]
+    fn subsequent_author_substitute() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography subsequent-author-substitute="---">
+                    <layout suffix=".">
+                        <group delimiter=", ">
+                            <names variable="author" />
+                            <text variable="title" />
+                        </group>
+                    </layout>
+                </bibliography>
+            </style>"#,
+        ));
+        for json in &[
+            r#"{ "id": "one", "title": "One", "author": [{ "family": "Smith" }] }"#,
+            r#"{ "id": "two", "title": "Two", "author": [{ "family": "Smith" }] }"#,
+        ] {
+            db.insert_reference(serde_json::from_str(json).unwrap());
+        }
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let bib = db.get_bibliography();
+        assert_eq!(bib[1].value.as_str(), "---, Two.");
+        assert_eq!(
+            bib[1].content.as_deref().map(|c| c.as_str()),
+            Some("---, Two")
+        );
+
+        let update = db.batched_updates().bibliography.unwrap();
+        assert_eq!(
+            update.updated_content[&Atom::from("two")].as_str(),
+            "---, Two"
+        );
+    }
+}
+
+mod bib_entry_template {
+    use super::*;
+
+    fn html_db(bib_entry_template: Option<BibEntryTemplate>) -> Processor {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="in-text">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography><layout><text variable="title" /></layout></bibliography>
+            </style>"#,
+            format: SupportedFormat::Html,
+            bib_entry_template,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one", "a&b"]);
+        insert_ascending_notes(&mut db, &["one", "a&b"]);
+        db
+    }
+
+    fn template() -> BibEntryTemplate {
+        BibEntryTemplate {
+            prefix: r#"<span id="ref-{id}">{citation-number}. "#.into(),
+            suffix: " {citation} </span>".into(),
+        }
+    }
+
+    fn values(bib: &[BibEntry]) -> Vec<&str> {
+        bib.iter().map(|entry| entry.value.as_str()).collect()
+    }
+
+    #[test]
+    fn fills_in_placeholders() {
+        let db = html_db(Some(template()));
+        assert_eq!(
+            values(&db.get_bibliography()),
+            vec![
+                r#"<span id="ref-one">1. Book one {citation} </span>"#,
+                r#"<span id="ref-a&amp;b">2. Book a&amp;b {citation} </span>"#,
+            ]
+        );
+        assert_eq!(
+            db.get_bib_item(Atom::from("one")).as_str(),
+            r#"<span id="ref-one">1. Book one {citation} </span>"#
+        );
+    }
+
+    #[test]
+    fn changing_template_updates_entries() {
+        let mut db = html_db(None);
+        db.batched_updates();
+        db.set_bib_entry_template(Some(template()));
+        let update = db.batched_updates().bibliography.unwrap();
+        assert_eq!(update.updated_entries.len(), 2);
+        assert_eq!(
+            update.updated_entries[&Atom::from("one")].as_str(),
+            r#"<span id="ref-one">1. Book one {citation} </span>"#
+        );
+    }
+}
+
+mod numeric_style {
+    use super::*;
+
+    /// IEEE-ish, with an after-collapse-delimiter so it can be told apart from the layout's
+    fn html_db(second_field_align: &str) -> Processor {
+        let style = format!(
+            r#"<style version="1.0" class="in-text">
+                <citation collapse="citation-number" after-collapse-delimiter="; ">
+                    <sort><key variable="citation-number" /></sort>
+                    <layout delimiter=", ">
+                        <text variable="citation-number" prefix="[" suffix="]" />
+                    </layout>
+                </citation>
+                <bibliography second-field-align="{}">
+                    <layout>
+                        <text variable="citation-number" prefix="[" suffix="]" />
+                        <text variable="title" />
+                    </layout>
+                </bibliography>
+            </style>"#,
+            second_field_align
+        );
+        let mut db = Processor::new(InitOptions {
+            style: &style,
+            format: SupportedFormat::Html,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one", "two", "three", "four", "five"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let cites = |ids: &[&str]| ids.iter().map(|&id| Cite::basic(id)).collect();
+        db.init_clusters(vec![
+            Cluster {
+                id: one,
+                cites: cites(&["one", "two", "three", "four"]),
+                mode: None,
+                suppress_trailing_punctuation: false,
+            },
+            Cluster {
+                id: two,
+                cites: cites(&["five", "three", "two", "one"]),
+                mode: None,
+                suppress_trailing_punctuation: false,
+            },
+        ])
+        .unwrap();
+        db.set_cluster_order(&[
+            ClusterPosition {
+                id: one,
+                note: None,
+            },
+            ClusterPosition {
+                id: two,
+                note: None,
+            },
+        ])
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn collapsed_ranges() {
+        let mut db = html_db("flush");
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        assert_cluster!(db.get_cluster(one), Some("[1]\u{2013}[4]"));
+        assert_cluster!(db.get_cluster(two), Some("[1]\u{2013}[3]; [5]"));
+    }
+
+    #[test]
+    fn bibliography_in_citation_order() {
+        for align in &["flush", "margin"] {
+            let db = html_db(align);
+            let bib = db.get_bibliography();
+            let ids: Vec<&str> = bib.iter().map(|entry| &*entry.id).collect();
+            assert_eq!(ids, vec!["one", "two", "three", "four", "five"]);
+            assert_eq!(
+                bib[4].value.as_str(),
+                r#"<div class="csl-left-margin">[5]</div><div class="csl-right-inline">Book five</div>"#,
+                "second-field-align={}",
+                align
+            );
+            assert_eq!(db.get_bibliography_meta().unwrap().max_offset, 3);
+        }
+    }
+}
+
+mod note_markers {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="note" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn html_db(note_markers: bool) -> Processor {
+        let mut db = Processor::new(InitOptions {
+            style: STYLE,
+            format: SupportedFormat::Html,
+            note_markers,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        db
+    }
+
+    fn markers(notes: &[NoteUpdate]) -> Vec<(&str, &str)> {
+        notes
+            .iter()
+            .map(|n| (n.marker.as_str(), n.note_text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn marker_and_note_text() {
+        let db = html_db(true);
+        let summary = db.batched_updates();
+        assert_eq!(
+            markers(&summary.notes),
+            vec![("<sup>1</sup>", "Book one"), ("<sup>2</sup>", "Book two")]
+        );
+    }
+
+    #[test]
+    fn renumbered_without_changing_text() {
+        let mut db = html_db(true);
+        db.batched_updates();
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        db.set_cluster_order(&[
+            ClusterPosition {
+                id: one,
+                note: Some(1),
+            },
+            ClusterPosition {
+                id: two,
+                note: Some(3),
+            },
+        ])
+        .unwrap();
+        let summary = db.batched_updates();
+        assert!(summary.clusters.is_empty());
+        assert_eq!(markers(&summary.notes), vec![("<sup>3</sup>", "Book two")]);
+    }
+
+    #[test]
+    fn off_by_default() {
+        let db = html_db(false);
+        assert!(db.batched_updates().notes.is_empty());
+    }
+}
+
+mod links {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="note" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="URL" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn html_db(links: LinkOptions) -> Processor {
+        let mut db = Processor::new(InitOptions {
+            style: STYLE,
+            format: SupportedFormat::Html,
+            links,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut refr = Reference::empty(Atom::from("one"), CslType::Webpage);
+        refr.ordinary
+            .insert(Variable::URL, "https://example.com".to_string());
+        db.insert_reference(refr);
+        insert_ascending_notes(&mut db, &["one"]);
+        db
+    }
+
+    fn anchor_tag(db: &Processor) -> String {
+        let output = db.get_cluster(db.cluster_id_of("1").unwrap()).unwrap();
+        let end = output.find('>').unwrap();
+        output[..=end].to_string()
+    }
+
+    #[test]
+    fn plain_by_default() {
+        let db = html_db(LinkOptions::default());
+        assert_eq!(anchor_tag(&db), r#"<a href="https://example.com">"#);
+    }
+
+    #[test]
+    fn configured_attributes() {
+        let db = html_db(LinkOptions {
+            target: Some("_blank".into()),
+            rel: Some("noopener noreferrer".into()),
+            class: Some("csl-link".into()),
+        });
+        assert_eq!(
+            anchor_tag(&db),
+            r#"<a href="https://example.com" target="_blank" rel="noopener noreferrer" class="csl-link">"#
+        );
+    }
+}
+
+mod style_requirements {
+    use super::*;
+
+    #[test]
+    fn in_text_numeric() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation>
+                    <layout><text variable="citation-number" /></layout>
+                </citation>
+                <bibliography><layout><text variable="title" /></layout></bibliography>
+            </style>"#,
+        ));
+        assert_eq!(
+            db.style_requirements(),
+            StyleRequirements {
+                requires_note_numbers: false,
+                uses_citation_number: true,
+                uses_year_suffix: false,
+                year_suffix_without_date: false,
+                has_bibliography: true,
+            }
+        );
+    }
+
+    #[test]
+    fn via_macros_and_conditions() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <macro name="backref">
+                    <choose>
+                        <if position="subsequent">
+                            <text variable="first-reference-note-number" />
+                        </if>
+                        <else-if variable="year-suffix">
+                            <text value="x" />
+                        </else-if>
+                    </choose>
+                </macro>
+                <citation>
+                    <layout><text macro="backref" /></layout>
+                </citation>
+            </style>"#,
+        ));
+        assert_eq!(
+            db.style_requirements(),
+            StyleRequirements {
+                requires_note_numbers: true,
+                uses_citation_number: false,
+                uses_year_suffix: true,
+                year_suffix_without_date: false,
+                has_bibliography: false,
+            }
+        );
+    }
+
+    #[test]
+    fn note_class() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation disambiguate-add-year-suffix="true">
+                    <layout><text variable="title" /></layout>
+                </citation>
+            </style>"#,
+        ));
+        let reqs = db.style_requirements();
+        assert!(reqs.requires_note_numbers);
+        assert!(reqs.uses_year_suffix);
+        assert!(reqs.year_suffix_without_date);
+    }
+
+    #[test]
+    fn year_suffix_without_date() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <macro name="year"><date variable="issued" form="numeric" /></macro>
+                <citation disambiguate-add-year-suffix="true">
+                    <layout><text variable="title" /></layout>
+                </citation>
+                <bibliography><layout><text macro="year" /></layout></bibliography>
+            </style>"#,
+        ));
+        assert!(db.style_requirements().year_suffix_without_date);
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        assert_eq!(
+            db.batched_updates().warnings,
+            vec![UpdateWarning::YearSuffixWithoutDate]
+        );
+
+        for layout in &[
+            r#"<text macro="year" />"#,
+            r#"<choose><if type="book"><text variable="year-suffix" /></if></choose>"#,
+        ] {
+            db.set_style_text(&format!(
+                r#"<style version="1.0" class="in-text">
+                    <macro name="year"><date variable="issued" form="numeric" /></macro>
+                    <citation disambiguate-add-year-suffix="true">
+                        <layout>{}</layout>
+                    </citation>
+                </style>"#,
+                layout
+            ))
+            .unwrap();
+            assert!(!db.style_requirements().year_suffix_without_date);
+        }
+    }
+
+    #[test]
+    fn style_uses_variable() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation>
+                    <sort><key variable="issued" /></sort>
+                    <layout>
+                        <names variable="author">
+                            <substitute><text variable="title" form="short" /></substitute>
+                        </names>
+                        <choose>
+                            <if variable="URL"><text value="online" /></if>
+                            <else><number variable="page-first" /></else>
+                        </choose>
+                    </layout>
+                </citation>
+            </style>"#,
+        ));
+        let used = |var| db.style_uses_variable(var);
+        assert!(used(AnyVariable::Name(NameVariable::Author)));
+        assert!(used(AnyVariable::Ordinary(Variable::Title)));
+        assert!(used(AnyVariable::Ordinary(Variable::TitleShort)));
+        assert!(used(AnyVariable::Ordinary(Variable::URL)));
+        assert!(used(AnyVariable::Number(NumberVariable::Page)));
+        assert!(used(AnyVariable::Date(DateVariable::Issued)));
+        assert!(!used(AnyVariable::Ordinary(Variable::ArchiveLocation)));
+        assert!(!used(AnyVariable::Name(NameVariable::Editor)));
+    }
+}
+
+mod used_variables {
+    use super::*;
+    use citeproc_io::NumberLike;
+
+    fn names(set: &fnv::FnvHashSet<AnyVariable>) -> Vec<&str> {
+        let mut names: Vec<&str> = set.iter().map(AnyVariable::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn taken_branches_and_substitute() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <macro name="contributors">
+                    <names variable="author">
+                        <substitute>
+                            <names variable="editor" />
+                            <text variable="title" form="short" />
+                        </substitute>
+                    </names>
+                </macro>
+                <citation>
+                    <layout>
+                        <text macro="contributors" />
+                        <choose>
+                            <if type="article-journal">
+                                <text variable="volume" />
+                            </if>
+                            <else>
+                                <text variable="publisher" />
+                            </else>
+                        </choose>
+                    </layout>
+                </citation>
+                <bibliography>
+                    <layout>
+                        <text macro="contributors" />
+                        <text variable="title" />
+                        <text variable="page" />
+                        <date variable="issued" form="numeric" />
+                    </layout>
+                </bibliography>
+            </style>"#,
+        ));
+        let mut refr = Reference::empty("one".into(), CslType::Book);
+        refr.ordinary.insert(Variable::Title, "Title".into());
+        refr.ordinary.insert(Variable::TitleShort, "Short".into());
+        refr.ordinary
+            .insert(Variable::Publisher, "Publisher".into());
+        refr.number
+            .insert(NumberVariable::Volume, NumberLike::Num(5));
+        db.insert_reference(refr);
+        let used = db.used_variables("one".into()).unwrap();
+        assert_eq!(names(&used.citation), vec!["publisher", "title-short"]);
+        assert_eq!(names(&used.bibliography), vec!["title", "title-short"]);
+        assert_eq!(db.used_variables("missing".into()), None);
+    }
+}
+
+mod undefined_terms {
+    use super::*;
+
+    #[test]
+    fn reported_in_every_branch() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation>
+                    <layout>
+                        <choose>
+                            <if type="book"><text term="ibid" /></if>
+                            <else><text term="anthropology" /></else>
+                        </choose>
+                    </layout>
+                </citation>
+            </style>"#,
+        ));
+        let expected = vec![UndefinedTerm {
+            name: "anthropology".into(),
+            form: "long".into(),
+            plural: false,
+        }];
+        assert_eq!(db.undefined_terms(), expected);
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        assert_eq!(
+            db.batched_updates().warnings,
+            vec![UpdateWarning::UndefinedTerms { terms: expected }]
+        );
+    }
+
+    #[test]
+    fn defined_in_style_locale() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <locale><terms><term name="anthropology">anthro.</term></terms></locale>
+                <citation><layout><text term="anthropology" /></layout></citation>
+            </style>"#,
+        ));
+        assert!(db.undefined_terms().is_empty());
+    }
+
+    #[test]
+    fn fallback_to_en_us() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text" default-locale="fr-FR">
+                <locale xml:lang="fr"><terms><term name="ibid">ibid. fr</term></terms></locale>
+                <citation>
+                    <layout delimiter=" / ">
+                        <text term="ibid" />
+                        <text term="and" />
+                    </layout>
+                </citation>
+            </style>"#,
+        ));
+        let expected = vec![FallbackTerm {
+            locale: "fr-FR".into(),
+            name: "and".into(),
+            form: "long".into(),
+            plural: false,
+        }];
+        assert_eq!(db.fallback_terms(), expected);
+        assert!(db.undefined_terms().is_empty());
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let summary = db.batched_updates();
+        assert_eq!(
+            summary.warnings,
+            vec![UpdateWarning::FallbackTerms { terms: expected }]
+        );
+        let id = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(id), Some("ibid. fr / and"));
+
+        let en_us = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation><layout><text term="and" /></layout></citation>
+            </style>"#,
+        ));
+        assert!(en_us.fallback_terms().is_empty());
+    }
+}
+
+#[cfg(feature = "unstable-api")]
+mod unstable_api {
+    use super::*;
+    use crate::unstable::DisambToken;
+
+    #[test]
+    fn inspect_ambiguous_cluster() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <citation>
+                    <layout><text variable="title" /></layout>
+                </citation>
+            </style>"#,
+        ));
+        for id in &["one", "two"] {
+            let mut refr = Reference::empty(Atom::from(*id), CslType::Book);
+            refr.ordinary.insert(Variable::Title, "Same".into());
+            db.insert_reference(refr);
+        }
+        let id = cid(&mut db, 1);
+        db.insert_cites(id, &[Cite::basic("one")]).unwrap();
+        let inspected = db.inspect_cluster(id);
+        assert_eq!(inspected.len(), 1);
+        assert_eq!(inspected[0].ref_id, Atom::from("one"));
+        assert_eq!(
+            inspected[0].tokens,
+            vec![DisambToken::Output("Same".into())]
+        );
+        assert_eq!(
+            inspected[0].matching_refs,
+            vec![Atom::from("one"), Atom::from("two")]
+        );
+        assert!(!inspected[0].ir.is_empty());
+        assert!(db.reference_dfa_dot("one".into()).is_some());
+        assert!(db.reference_dfa_dot("missing".into()).is_none());
+    }
+}
+
+mod terms {
+    use super::*;
+
+    fn en_au() -> Lang {
+        Lang::Iso(IsoLang::English, Some(IsoCountry::AU))
+    }
+
+    fn terms(xml: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US">
+        <terms>{}</terms></locale>"#,
+            xml
+        )
+    }
+
+    use citeproc_db::PredefinedLocales;
+    fn predefined_xml(pairs: &[(Lang, &str)]) -> PredefinedLocales {
+        let mut map = HashMap::new();
+        for (lang, ts) in pairs {
+            map.insert(lang.clone(), terms(ts));
+        }
+        PredefinedLocales(map)
+    }
+
+    fn term_and(form: TermFormExtended) -> SimpleTermSelector {
+        SimpleTermSelector::Misc(MiscTerm::And, form)
+    }
+
+    fn test_simple_term(term: SimpleTermSelector, langs: &[(Lang, &str)], expect: Option<&str>) {
+        let db = Processor::safe_default(Arc::new(predefined_xml(langs)));
+        // use en-AU so it has to do fallback to en-US
+        let locale = db.merged_locale(en_au());
+        assert_eq!(
+            locale.get_text_term(TextTermSelector::Simple(term), false),
+            expect
+        )
+    }
+
+    #[test]
+    fn term_override() {
+        test_simple_term(
+            term_and(TermFormExtended::Long),
+            &[
+                (Lang::en_us(), r#"<term name="and">USA</term>"#),
+                (en_au(), r#"<term name="and">Australia</term>"#),
+            ],
+            Some("Australia"),
+        )
+    }
+
+    #[test]
+    fn term_form_refine() {
+        test_simple_term(
+            term_and(TermFormExtended::Long),
+            &[
+                (Lang::en_us(), r#"<term name="and">USA</term>"#),
+                (en_au(), r#"<term name="and" form="short">Australia</term>"#),
+            ],
+            Some("USA"),
+        );
+        test_simple_term(
+            term_and(TermFormExtended::Short),
+            &[
+                (Lang::en_us(), r#"<term name="and">USA</term>"#),
+                (en_au(), r#"<term name="and" form="short">Australia</term>"#),
+            ],
+            Some("Australia"),
+        );
+    }
+
+    #[test]
+    fn term_form_fallback() {
+        test_simple_term(
+            term_and(TermFormExtended::Short),
+            &[
+                (Lang::en_us(), r#"<term name="and">USA</term>"#),
+                (en_au(), r#"<term name="and">Australia</term>"#),
+            ],
+            Some("Australia"),
+        );
+        test_simple_term(
+            // short falls back to long and skips the "symbol" in a later locale
+            term_and(TermFormExtended::Short),
+            &[
+                (Lang::en_us(), r#"<term name="and">USA</term>"#),
+                (
+                    en_au(),
+                    r#"<term name="and" form="symbol">Australia</term>"#,
+                ),
+            ],
+            Some("USA"),
+        );
+        test_simple_term(
+            term_and(TermFormExtended::VerbShort),
+            &[
+                (Lang::en_us(), r#"<term name="and" form="long">USA</term>"#),
+                (
+                    en_au(),
+                    r#"<term name="and" form="symbol">Australia</term>"#,
+                ),
+            ],
+            Some("USA"),
+        );
+    }
+
+    #[test]
+    fn get_term() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <locale><terms><term name="and">und</term></terms></locale>
+                <citation><layout></layout></citation>
+            </style>"#,
+        ));
+        let simple = |term, form| TextTermSelector::Simple(SimpleTermSelector::Misc(term, form));
+        assert_eq!(
+            db.get_term(simple(MiscTerm::And, TermFormExtended::Long), false)
+                .as_deref(),
+            Some("und")
+        );
+        assert_eq!(
+            db.get_term(simple(MiscTerm::Accessed, TermFormExtended::Long), false)
+                .as_deref(),
+            Some("accessed")
+        );
+        let pages = TextTermSelector::Gendered(GenderedTermSelector::Locator(
+            LocatorType::Page,
+            TermForm::Short,
+        ));
+        assert_eq!(db.get_term(pages, true).as_deref(), Some("pp."));
+    }
+
+    #[test]
+    fn term_locale_fallback() {
+        test_simple_term(
+            term_and(TermFormExtended::Long),
+            &[
+                (Lang::en_us(), r#"<term name="and">USA</term>"#),
+                (en_au(), r#""#),
+            ],
+            Some("USA"),
+        )
+    }
+}
+
+mod clone_with_style {
+    use super::*;
+
+    const TITLE_STYLE: &str = r#"<style version="1.0" class="note">
+        <citation><layout><text variable="title" /></layout></citation>
+    </style>"#;
+    const NUMBER_STYLE: &str = r#"<style version="1.0" class="in-text">
+        <citation><layout><text variable="citation-number" prefix="[" suffix="]" /></layout></citation>
+    </style>"#;
+
+    #[test]
+    fn renders_same_document_in_both_styles() {
+        let mut db = test_db(Some(TITLE_STYLE));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let other = db.clone_with_style(NUMBER_STYLE).unwrap();
+        let id = db.cluster_id_of("2").unwrap();
+        assert_eq!(db.get_cluster(id).unwrap().as_str(), "Book two");
+        assert_eq!(other.get_cluster(id).unwrap().as_str(), "[2]");
+    }
+
+    #[test]
+    fn independent_after_cloning() {
+        let mut db = test_db(Some(TITLE_STYLE));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let other = db.clone_with_style(NUMBER_STYLE).unwrap();
+        let mut refr = Reference::empty("one".into(), CslType::Book);
+        refr.ordinary.insert(Variable::Title, "Renamed".into());
+        db.insert_reference(refr);
+        let id = db.cluster_id_of("1").unwrap();
+        assert_eq!(db.get_cluster(id).unwrap().as_str(), "Renamed");
+        assert_eq!(
+            other.reference(Atom::from("one")).unwrap().ordinary[&Variable::Title],
+            "Book one"
+        );
+    }
+
+    #[test]
+    fn same_parse_options() {
+        let db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="note">
+                <info><id>id</id><title /></info>
+                <citation><layout><text variable="title" /></layout></citation>
+            </style>"#,
+            ..Default::default()
+        })
+        .unwrap();
+        // Outside test mode, the <info> block is required.
+        assert!(db.clone_with_style(NUMBER_STYLE).is_err());
+    }
+
+    #[test]
+    fn preview_reports_only_its_own_edits() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation><layout>
+                    <choose>
+                        <if position="ibid"><text value="ibid" /></if>
+                        <else><text variable="title" /></else>
+                    </choose>
+                </layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one", "one"]);
+        db.batched_updates();
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+
+        let mut preview = db.clone_for_preview();
+        assert!(preview.batched_updates().clusters.is_empty());
+        preview.remove_cluster(one);
+        let summary = preview.batched_updates();
+        let clusters: Vec<(ClusterId, &str)> = summary
+            .clusters
+            .iter()
+            .map(|(id, built)| (*id, built.as_str()))
+            .collect();
+        assert_eq!(clusters, vec![(two, "Book one")]);
+
+        assert_cluster!(db.get_cluster(two), Some("ibid"));
+        assert!(db.batched_updates().clusters.is_empty());
+
+        // Cluster ids interned in the preview stay there
+        let three = preview.new_cluster("3");
+        assert_eq!(preview.cluster_id_of("3"), Some(three));
+        assert_eq!(db.cluster_id_of("3"), None);
+        assert_eq!(preview.cluster_id_of("2"), Some(two));
+    }
+}
+
+mod references_from_reader {
+    use super::*;
+
+    #[test]
+    fn replaces_library() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["old"]);
+        let json = r#"[
+            { "id": "one", "type": "book", "title": "One" },
+            { "id": "two", "type": "article", "title": "Two" }
+        ]"#;
+        let mut progress = Vec::new();
+        db.set_references_from_reader(json.as_bytes(), |n| progress.push(n))
+            .unwrap();
+        assert_eq!(progress, vec![1, 2]);
+        assert_eq!(
+            db.all_keys().iter().cloned().collect::<Vec<_>>(),
+            vec![Atom::from("one"), Atom::from("two")]
+        );
+        assert_eq!(
+            db.get_reference("two".into()).unwrap().ordinary[&Variable::Title],
+            "Two"
+        );
+    }
+
+    #[test]
+    fn invalid_json_keeps_library() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["old"]);
+        let json = r#"[{ "id": "one", "type": "book" }, { "id": "#;
+        assert!(db
+            .set_references_from_reader(json.as_bytes(), |_| {})
+            .is_err());
+        assert_eq!(
+            db.all_keys().iter().cloned().collect::<Vec<_>>(),
+            vec![Atom::from("old")]
+        );
+    }
+}
+
+mod in_text_ibid {
+    use super::*;
+
+    fn rendered(in_text_no_ibid: bool) -> Vec<String> {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="in-text">
+                <citation><layout prefix="(" suffix=")">
+                    <choose>
+                        <if position="ibid"><text value="ibid." /></if>
+                        <else-if position="subsequent"><text value="again" /></else-if>
+                        <else><text variable="title" /></else>
+                    </choose>
+                </layout></citation>
+            </style>"#,
+            format: SupportedFormat::Plain,
+            in_text_no_ibid,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_basic_refs(&mut db, &["one"]);
+        let ids: Vec<_> = (1..=2).map(|n| cid(&mut db, n)).collect();
+        for &id in &ids {
+            db.insert_cluster(Cluster {
+                id,
+                cites: vec![Cite::basic("one")],
+                mode: None,
+                suppress_trailing_punctuation: false,
+            })
+            .unwrap();
+        }
+        let order: Vec<_> = ids
+            .iter()
+            .map(|&id| ClusterPosition { id, note: None })
+            .collect();
+        db.set_cluster_order(&order).unwrap();
+        ids.iter()
+            .map(|&id| db.get_cluster(id).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn consecutive_in_text_cites() {
+        assert_eq!(rendered(false), vec!["(Book one)", "(ibid.)"]);
+        assert_eq!(rendered(true), vec!["(Book one)", "(again)"]);
+    }
+}
+
+mod metrics {
+    use super::*;
+
+    #[test]
+    fn counts_only_recomputed_work() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation><layout><text variable="title" /></layout></citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let (one, two) = (cid(&mut db, 1), cid(&mut db, 2));
+        db.get_cluster(one);
+        db.get_cluster(two);
+        let metrics = db.metrics();
+        assert_eq!(metrics.built_clusters, 2);
+        assert!(metrics.ir_gen_passes >= 2);
+        assert!(metrics.locale_parses >= 1);
+
+        // Nothing changed, so nothing is even checked
+        db.reset_metrics();
+        db.get_cluster(one);
+        db.get_cluster(two);
+        assert_eq!(db.metrics(), Metrics::default());
+
+        let mut refr = Reference::empty(Atom::from("two"), CslType::Book);
+        refr.ordinary.insert(Variable::Title, "Edited".to_string());
+        db.insert_reference(refr);
+        assert_cluster!(db.get_cluster(two), Some("Edited"));
+        let metrics = db.metrics();
+        assert_eq!(metrics.built_clusters, 1);
+        assert_eq!(metrics.locale_parses, 0);
+        assert!(metrics.queries_reused > 0);
+    }
+}
+
+mod name_limit {
+    use super::*;
+
+    fn rendered(name_attrs: &str, name_limit: Option<NameLimit>) -> String {
+        let style = format!(
+            r#"<style version="1.0" class="note">
+                <citation><layout><names variable="author"><name {} /></names></layout></citation>
+            </style>"#,
+            name_attrs
+        );
+        let mut db = Processor::new(InitOptions {
+            style: &style,
+            format: SupportedFormat::Plain,
+            test_mode: true,
+            name_limit,
+            ..Default::default()
+        })
+        .unwrap();
+        let refr: Reference = serde_json::from_str(
+            r#"{ "id": "one", "type": "article", "author": [
+                {"family": "A"}, {"family": "B"}, {"family": "C"},
+                {"family": "D"}, {"family": "E"}, {"family": "F"}
+            ] }"#,
+        )
+        .unwrap();
+        db.insert_reference(refr);
+        insert_ascending_notes(&mut db, &["one"]);
+        let id = cid(&mut db, 1);
+        db.get_cluster(id).unwrap().to_string()
+    }
+
+    fn limit(max_names: u32, truncation: NameTruncation) -> Option<NameLimit> {
+        Some(NameLimit {
+            max_names,
+            truncation,
+        })
+    }
+
+    #[test]
+    fn caps_names() {
+        assert_eq!(rendered("", None), "A, B, C, D, E, F");
+        assert_eq!(
+            rendered("", limit(3, NameTruncation::EtAl)),
+            "A, B, C, et al."
+        );
+        assert_eq!(
+            rendered("", limit(3, NameTruncation::Ellipsis)),
+            "A, B, … F"
+        );
+        // The style already shows fewer
+        assert_eq!(
+            rendered(
+                r#"et-al-min="3" et-al-use-first="1""#,
+                limit(3, NameTruncation::EtAl)
+            ),
+            "A et al."
+        );
+        assert_eq!(
+            rendered(
+                r#"et-al-min="3" et-al-use-first="5""#,
+                limit(3, NameTruncation::EtAl)
+            ),
+            "A, B, C, et al."
+        );
+    }
+
+    #[test]
+    fn caps_count() {
+        assert_eq!(rendered(r#"form="count""#, None), "6");
+        assert_eq!(
+            rendered(r#"form="count""#, limit(3, NameTruncation::EtAl)),
+            "3"
+        );
+    }
+
+    #[cfg(feature = "unstable-api")]
+    #[test]
+    fn reports_omitted_names() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation><layout><names variable="author"><name /></names></layout></citation>
+            </style>"#,
+        ));
+        db.set_name_limit(limit(2, NameTruncation::EtAl));
+        let refr: Reference = serde_json::from_str(
+            r#"{ "id": "one", "type": "article", "author": [
+                {"family": "A"}, {"family": "B"}, {"family": "C"}
+            ] }"#,
+        )
+        .unwrap();
+        db.insert_reference(refr);
+        insert_ascending_notes(&mut db, &["one"]);
+        let id = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(id), Some("A, B, et al."));
+        let inspected = db.inspect_cluster(id);
+        assert_eq!(inspected[0].omitted_names, vec![(NameVariable::Author, 1)]);
+    }
+}
+
+mod citeproc_js_compat {
+    use super::*;
+
+    fn cluster(id: &str, ref_id: &str) -> string_id::Cluster {
+        string_id::Cluster {
+            id: id.into(),
+            cites: vec![Cite::basic(ref_id)],
+            mode: None,
+            suppress_trailing_punctuation: false,
+        }
+    }
+
+    fn output(
+        updates: Vec<(usize, Arc<SmartString>, SmartString)>,
+    ) -> Vec<(usize, String, String)> {
+        updates
+            .into_iter()
+            .map(|(index, output, id)| (index, output.to_string(), id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn process_and_append() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <citation>
+                    <layout>
+                        <choose>
+                            <if position="ibid"><text value="Ibid" /></if>
+                            <else><text variable="title" /></else>
+                        </choose>
+                    </layout>
+                </citation>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two"]);
+
+        let updates = db.append_citation_cluster(cluster("a", "one"), 1).unwrap();
+        assert_eq!(output(updates), vec![(0, "Book one".into(), "a".into())]);
+
+        let updates = db.append_citation_cluster(cluster("b", "one"), 2).unwrap();
+        assert_eq!(output(updates), vec![(1, "Ibid".into(), "b".into())]);
+
+        // Inserting a different reference between them breaks the ibid.
+        let updates = db
+            .process_citation_cluster(
+                cluster("c", "two"),
+                2,
+                &[("a".into(), 1)],
+                &[("b".into(), 3)],
+            )
+            .unwrap();
+        assert_eq!(
+            output(updates),
+            vec![
+                (1, "Book two".into(), "c".into()),
+                (2, "Book one".into(), "b".into())
+            ]
+        );
+
+        // A bad order is rejected before anything changes, whether the cluster is new or not.
+        let err = db.process_citation_cluster(cluster("d", "two"), 1, &[("a".into(), 2)], &[]);
+        assert!(matches!(
+            err,
+            Err(string_id::ProcessClusterError::Reordering(_))
+        ));
+        let err = db.process_citation_cluster(cluster("c", "one"), 1, &[("a".into(), 2)], &[]);
+        assert!(matches!(
+            err,
+            Err(string_id::ProcessClusterError::Reordering(_))
+        ));
+        let a = db.cluster_id_of("a").unwrap();
+        assert_eq!(
+            db.get_cluster_note_number(a),
+            Some(ClusterNumber::Note(IntraNote::Multi(1, 0)))
+        );
+        assert_cluster!(db.get_cluster_str("d"), None);
+        assert_cluster!(db.get_cluster_str("c"), Some("Book two"));
+        assert!(db.batched_updates_str().clusters.is_empty());
+    }
+}
+
+mod normalize_initials {
+    use super::*;
+
+    fn rendered(name_attrs: &str, normalize_initials: bool) -> Vec<String> {
+        let style = format!(
+            r#"<style version="1.0" class="note">
+                <citation><layout><names variable="author"><name {} /></names></layout></citation>
+            </style>"#,
+            name_attrs
+        );
+        let mut db = Processor::new(InitOptions {
+            style: &style,
+            format: SupportedFormat::Plain,
+            test_mode: true,
+            normalize_initials,
+            ..Default::default()
+        })
+        .unwrap();
+        let refs: Vec<Reference> = serde_json::from_str(
+            r#"[
+                { "id": "one", "type": "book", "author": [{"family": "Tolkien", "given": "J.R.R."}] },
+                { "id": "two", "type": "book", "author": [{"family": "Tolkien", "given": "J. R. R."}] },
+                { "id": "three", "type": "book", "author": [{"family": "Tolkien", "given": "JRR"}] }
+            ]"#,
+        )
+        .unwrap();
+        db.reset_references(refs);
+        insert_ascending_notes(&mut db, &["one", "two", "three"]);
+        (1..=3)
+            .map(|n| {
+                let id = cid(&mut db, n);
+                db.get_cluster(id).unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn off_by_default() {
+        assert_eq!(
+            rendered("", false),
+            vec!["J.R.R. Tolkien", "J. R. R. Tolkien", "JRR Tolkien"]
+        );
+    }
+
+    #[test]
+    fn same_name_however_typed() {
+        assert_eq!(rendered("", true), vec!["J. R. R. Tolkien"; 3]);
+        assert_eq!(
+            rendered(r#"initialize-with=".""#, true),
+            vec!["J.R.R. Tolkien"; 3]
+        );
+    }
+}
+
+mod bib_sort_keys {
+    use super::*;
+
+    #[test]
+    fn plain_values_in_bibliography_order() {
+        let mut db = test_db(Some(
+            r#"<style version="1.0" class="in-text">
+                <macro name="title"><text variable="title" /></macro>
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography>
+                    <sort>
+                        <key variable="author" />
+                        <key variable="issued" />
+                        <key macro="title" />
+                    </sort>
+                    <layout><text variable="title" /></layout>
+                </bibliography>
+            </style>"#,
+        ));
+        let refs: Vec<Reference> = serde_json::from_str(
+            r#"[
+                { "id": "smith", "type": "book", "title": "Second",
+                  "author": [{"family": "Smith", "given": "John"}],
+                  "issued": {"date-parts": [[2000]]} },
+                { "id": "adams", "type": "book", "title": "First",
+                  "author": [{"family": "Adams", "given": "Jane"}],
+                  "issued": {"date-parts": [[1999, 5]]} },
+                { "id": "anon", "type": "book", "title": "Third" }
+            ]"#,
+        )
+        .unwrap();
+        db.reset_references(refs);
+        insert_ascending_notes(&mut db, &["smith", "adams", "anon"]);
+        let keys = db.bib_sort_keys();
+        let values = |k: &SortKeyValues| -> Vec<Option<String>> {
+            k.keys
+                .iter()
+                .map(|v| v.as_ref().map(|v| v.to_string()))
+                .collect()
+        };
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].0, Atom::from("adams"));
+        assert_eq!(
+            values(&keys[0].1),
+            vec![
+                Some("Adams Jane".into()),
+                Some("1999-05".into()),
+                Some("First".into())
+            ]
+        );
+        assert_eq!(keys[0].1.primary(), Some("Adams Jane"));
+        assert_eq!(keys[1].0, Atom::from("smith"));
+        assert_eq!(
+            values(&keys[1].1),
+            vec![
+                Some("Smith John".into()),
+                Some("2000".into()),
+                Some("Second".into())
+            ]
+        );
+        assert_eq!(keys[2].0, Atom::from("anon"));
+        assert_eq!(values(&keys[2].1), vec![None, None, Some("Third".into())]);
+    }
+}
+
+mod bib_tie_break {
+    use super::*;
+
+    // Every entry has the same (empty) sort key.
+    const STYLE: &str = r#"<style version="1.0" class="in-text">
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography>
+            <sort><key variable="issued" /></sort>
+            <layout><text variable="title" /></layout>
+        </bibliography>
+    </style>"#;
+
+    fn bib_order(insertion_order: &[&str]) -> Vec<String> {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, insertion_order);
+        db.include_uncited(IncludeUncited::All);
+        insert_ascending_notes(&mut db, &["c", "a"]);
+        db.get_bibliography()
+            .iter()
+            .map(|entry| entry.id.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn cited_order_then_id() {
+        let expected = vec!["c", "a", "b", "d", "e"];
+        assert_eq!(bib_order(&["a", "b", "c", "d", "e"]), expected);
+        assert_eq!(bib_order(&["e", "d", "c", "b", "a"]), expected);
+        assert_eq!(bib_order(&["d", "a", "e", "c", "b"]), expected);
+        assert_eq!(bib_order(&["b", "e", "a", "d", "c"]), expected);
+    }
+}
+
+mod style_ast_json {
+    use super::*;
+
+    #[test]
+    fn macros_layout_and_locale() {
+        let db = test_db(Some(
+            r#"<style version="1.0" class="note">
+                <locale>
+                    <terms><term name="editor" form="short">ed.</term></terms>
+                </locale>
+                <macro name="title"><text variable="title" form="short" /></macro>
+                <macro name="author"><names variable="author" /></macro>
+                <macro name="choose">
+                    <choose>
+                        <if type="book" variable="title" position="ibid" match="any">
+                            <text value="x" />
+                        </if>
+                    </choose>
+                </macro>
+                <citation><layout delimiter="; "><text macro="title" /></layout></citation>
+            </style>"#,
+        ));
+        let json = db.style_ast_json();
+        assert_eq!(json["class"], "note");
+        let text = &json["macros"]["title"][0];
+        assert_eq!(text["type"], "text");
+        assert_eq!(
+            text["value"]["source"],
+            serde_json::json!({
+                "type": "variable",
+                "value": [{ "type": "ordinary", "value": "title" }, "short"],
+            })
+        );
+        let layout = &json["citation"]["layout"];
+        assert_eq!(layout["delimiter"], "; ");
+        assert_eq!(
+            layout["elements"][0]["value"]["source"],
+            serde_json::json!({ "type": "macro", "value": "title" })
+        );
+        let macros: Vec<_> = json["macros"].as_object().unwrap().keys().collect();
+        assert_eq!(macros, vec!["author", "choose", "title"]);
+
+        // The conditions are a set, but they always come out in the same order
+        let choose = &json["macros"]["choose"][0];
+        assert_eq!(choose["type"], "choose");
+        let cond_set = &choose["value"][0][0][1][0];
+        assert_eq!(cond_set["match_type"], "any");
+        assert_eq!(
+            cond_set["conds"],
+            serde_json::json!([
+                { "type": "variable", "value": { "type": "ordinary", "value": "title" } },
+                { "type": "position", "value": "ibid" },
+                { "type": "type", "value": "book" },
+            ])
+        );
+        let locale = json["locale_overrides"][""].as_str().unwrap();
+        assert!(locale.contains(r#"<term name="editor" form="short">ed.</term>"#));
+    }
+}
+
+mod locale_override {
+    use super::*;
+
+    const STYLE: &str = r#"<style version="1.0" class="note" default-locale="de-DE">
+        <locale xml:lang="de"><terms><term name="and">und (style)</term></terms></locale>
+        <locale xml:lang="en"><terms><term name="and">and (style)</term></terms></locale>
+        <citation>
+            <layout delimiter=" / ">
+                <text term="and" />
+                <text variable="title" text-case="title" />
+            </layout>
+        </citation>
+    </style>"#;
+
+    fn insert_lowercase_ref(db: &mut Processor) {
+        let mut refr = Reference::empty(Atom::from("one"), CslType::Book);
+        refr.ordinary
+            .insert(Variable::Title, "the title".to_string());
+        db.insert_reference(refr);
+        insert_ascending_notes(db, &["one"]);
+    }
+
+    #[test]
+    fn style_locale_overrides_follow_the_override() {
+        let mut db = Processor::new(InitOptions {
+            style: STYLE,
+            format: SupportedFormat::Plain,
+            locale_override: Some(Lang::en_us()),
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        insert_lowercase_ref(&mut db);
+        let id = cid(&mut db, 1);
+        // English, so title case applies too.
+        assert_cluster!(db.get_cluster(id), Some("and (style) / The Title"));
+
+        db.set_locale_override(None);
+        assert_cluster!(db.get_cluster(id), Some("und (style) / the title"));
+    }
+
+    #[test]
+    fn set_after_init() {
+        let mut db = test_db(Some(STYLE));
+        insert_lowercase_ref(&mut db);
+        let id = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(id), Some("und (style) / the title"));
+        db.set_locale_override(Some(Lang::en_us()));
+        assert_cluster!(db.get_cluster(id), Some("and (style) / The Title"));
+    }
+}
+
+mod removals {
+    use super::*;
+
+    fn setup() -> Processor {
+        let mut db = test_db(Some(
+            r#"<style class="note" version="1.0.1">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography><layout><text variable="title" /></layout></bibliography>
+            </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let _ = db.batched_updates();
+        db
+    }
+
+    #[test]
+    fn removed_cluster_and_entry() {
+        let mut db = setup();
+        let two = cid(&mut db, 2);
+        db.remove_cluster(two);
+        let summary = db.batched_updates();
+        assert!(summary.clusters.is_empty());
+        assert_eq!(summary.removed_clusters, vec![two]);
+        let bib = summary.bibliography.unwrap();
+        assert_eq!(bib.removed_entries, vec![Atom::from("two")]);
+        assert_eq!(bib.entry_ids, Some(vec![Atom::from("one")]));
+
+        // Only reported once.
+        let summary = db.batched_updates();
+        assert!(summary.removed_clusters.is_empty());
+        assert!(summary.bibliography.is_none());
+    }
+
+    #[test]
+    fn left_out_of_cluster_order() {
+        let mut db = setup();
+        let one = cid(&mut db, 1);
+        let order = [ClusterPosition {
+            id: one,
+            note: Some(1),
+        }];
+        db.set_cluster_order(&order).unwrap();
+        let summary = db.batched_updates_str();
+        assert_eq!(summary.removed_clusters, vec![SmartString::from("2")]);
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["removedClusters"], serde_json::json!(["2"]));
+        assert_eq!(
+            json["bibliography"]["removedEntries"],
+            serde_json::json!(["two"])
+        );
+    }
+}
+
+mod author_only_substitute {
+    use super::*;
+
+    fn db(author_only_substitute: AuthorOnlySubstitute) -> Processor {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="in-text">
+                <citation><layout>
+                    <group delimiter=", ">
+                        <names variable="author">
+                            <name />
+                            <substitute>
+                                <names variable="editor" />
+                                <text variable="title" />
+                            </substitute>
+                        </names>
+                        <text value="cite" />
+                    </group>
+                </layout></citation>
+            </style>"#,
+            format: SupportedFormat::Plain,
+            author_only_substitute,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        for json in &[
+            r#"{ "id": "author", "title": "T", "author": [{ "family": "Smith" }] }"#,
+            r#"{ "id": "editor", "title": "T", "editor": [{ "family": "Jones" }] }"#,
+            r#"{ "id": "title", "title": "Title Only" }"#,
+        ] {
+            db.insert_reference(serde_json::from_str(json).unwrap());
+        }
+        let ids = ["author", "editor", "title"];
+        let mut clusters = Vec::new();
+        let mut order = Vec::new();
+        for (i, ref_id) in ids.iter().enumerate() {
+            let id = cid(&mut db, i as u32 + 1);
+            clusters.push(Cluster {
+                id,
+                cites: vec![Cite::basic(*ref_id)],
+                mode: Some(citeproc_io::ClusterMode::AuthorOnly),
+                suppress_trailing_punctuation: false,
+            });
+            order.push(ClusterPosition { id, note: None });
+        }
+        db.init_clusters(clusters).unwrap();
+        db.set_cluster_order(&order).unwrap();
+        db
+    }
+
+    fn rendered(db: &mut Processor) -> Vec<String> {
+        (1..=3)
+            .map(|n| {
+                let id = cid(db, n);
+                db.get_cluster(id).unwrap().to_string()
+            })
+            .collect()
+    }
+
+    fn outcomes(db: &mut Processor) -> Vec<AuthorOnlyOutcome> {
+        (1..=3)
+            .map(|n| {
+                let id = cid(db, n);
+                db.author_only_outcome(id, 0).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn any() {
+        let mut db = db(AuthorOnlySubstitute::Any);
+        assert_eq!(rendered(&mut db), vec!["Smith", "Jones", "Title Only"]);
+        assert_eq!(
+            outcomes(&mut db),
+            vec![
+                AuthorOnlyOutcome::Names,
+                AuthorOnlyOutcome::Names,
+                AuthorOnlyOutcome::Substitute
+            ]
+        );
+    }
+
+    #[test]
+    fn names_only() {
+        let mut db = db(AuthorOnlySubstitute::NamesOnly);
+        assert_eq!(
+            rendered(&mut db),
+            vec!["Smith", "Jones", "[NO_PRINTED_FORM]"]
+        );
+        assert_eq!(
+            outcomes(&mut db),
+            vec![
+                AuthorOnlyOutcome::Names,
+                AuthorOnlyOutcome::Names,
+                AuthorOnlyOutcome::NoPrintedForm
+            ]
+        );
+        let id = cid(&mut db, 1);
+        assert!(db.author_only_outcome(id, 1).is_err());
+    }
+}
+
+mod text_case_locales {
+    use super::*;
+
+    fn rendered(text_case_locales: TextCaseLocales) -> Vec<String> {
+        let mut db = Processor::new(InitOptions {
+            style: r#"<style version="1.0" class="note" default-locale="de-DE">
+                <citation><layout><text variable="title" text-case="title" /></layout></citation>
+            </style>"#,
+            format: SupportedFormat::Plain,
+            text_case_locales,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        for json in &[
+            r#"{ "id": "none", "title": "a title without a language" }"#,
+            r#"{ "id": "en", "title": "a title in english", "language": "en-GB" }"#,
+            r#"{ "id": "fr", "title": "un titre en français", "language": "fr" }"#,
+        ] {
+            db.insert_reference(serde_json::from_str(json).unwrap());
+        }
+        insert_ascending_notes(&mut db, &["none", "en", "fr"]);
+        (1..=3)
+            .map(|n| {
+                let id = cid(&mut db, n);
+                db.get_cluster(id).unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn strict() {
+        assert_eq!(
+            rendered(TextCaseLocales::Strict),
+            vec![
+                "a title without a language",
+                "A Title in English",
+                "un titre en français"
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient() {
+        assert_eq!(
+            rendered(TextCaseLocales::Lenient),
+            vec![
+                "A Title Without a Language",
+                "A Title in English",
+                "un titre en français"
+            ]
+        );
     }
 }
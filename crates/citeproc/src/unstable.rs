@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! A view of the processor's internals for tooling, e.g. style editors that want to show why a
+//! cite was disambiguated the way it was. Enabled by the `unstable-api` feature.
+//!
+//! [ImplementationDetails](crate::prelude::ImplementationDetails) and
+//! [IrDatabase](crate::prelude::IrDatabase) are only exported because [Processor] has to
+//! implement them, and they change whenever the internals do. Everything in this module is
+//! supported instead: it is covered by this crate's semver, so it only breaks when the crate's
+//! version says it may. The types are plain data with no ties to the database, and are
+//! `#[non_exhaustive]` so more information can be added later.
+//!
+//! The *contents* are a different matter. The IR outline is a debugging aid whose text will
+//! change, and the tokens a cite produces can change whenever rendering does. Display them;
+//! don't parse them.
+
+use crate::prelude::*;
+use citeproc_proc::disamb::EdgeData;
+
+/// One token of the stream a cite is matched on during disambiguation. A cite is ambiguous when
+/// the stream it renders to could also have been produced by another reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DisambToken {
+    /// Some rendered text, in the processor's output format
+    Output(String),
+    Locator,
+    LocatorLabel,
+    YearSuffix,
+    CitationNumber,
+    CitationNumberLabel,
+    FirstReferenceNoteNumber,
+    FirstReferenceNoteNumberLabel,
+    /// The `accessed` date, which is never used to tell references apart
+    Accessed,
+}
+
+impl DisambToken {
+    fn from_edge(edge: EdgeData) -> Option<Self> {
+        Some(match edge {
+            EdgeData::Output(text) => DisambToken::Output(text.to_string()),
+            EdgeData::Locator => DisambToken::Locator,
+            EdgeData::LocatorLabel => DisambToken::LocatorLabel,
+            EdgeData::YearSuffix | EdgeData::YearSuffixExplicit | EdgeData::YearSuffixPlain => {
+                DisambToken::YearSuffix
+            }
+            EdgeData::CitationNumber => DisambToken::CitationNumber,
+            EdgeData::CitationNumberLabel => DisambToken::CitationNumberLabel,
+            EdgeData::Frnn => DisambToken::FirstReferenceNoteNumber,
+            EdgeData::FrnnLabel => DisambToken::FirstReferenceNoteNumberLabel,
+            EdgeData::Accessed => DisambToken::Accessed,
+            EdgeData::NotUsed => return None,
+        })
+    }
+}
+
+/// How one cite in a cluster was rendered, after disambiguation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CiteInspection {
+    pub ref_id: Atom,
+    /// An indented outline of the cite's intermediate representation, one node per line.
+    pub ir: String,
+    pub tokens: Vec<DisambToken>,
+    /// Every reference that could have produced `tokens`, sorted. If the cite is unambiguous, this
+    /// is just `ref_id`.
+    pub matching_refs: Vec<Atom>,
+    /// Name variables that were cut short by et-al or [InitOptions::name_limit], with how many of
+    /// their names were left out, e.g. to show "and 1,993 more authors".
+    pub omitted_names: Vec<(csl::NameVariable, usize)>,
+}
+
+impl Processor {
+    /// Inspects each cite in a cluster, in the order they were inserted. Returns an empty Vec for
+    /// a cluster with no cites.
+    pub fn inspect_cluster(&self, cluster_id: ClusterId) -> Vec<CiteInspection> {
+        let fmt = self.get_formatter();
+        let all_dfas = self.all_ref_dfas();
+        self.cluster_cites(cluster_id.raw())
+            .iter()
+            .map(|&cite_id| {
+                let cite = cite_id.lookup(self);
+                let gen = self.ir_fully_disambiguated(cite_id);
+                let edges = gen.to_edge_stream(&fmt);
+                let mut matching_refs: Vec<Atom> = all_dfas
+                    .keys()
+                    .filter(|&ref_id| self.edge_stream_matches_ref(edges.clone(), ref_id.clone()))
+                    .cloned()
+                    .collect();
+                matching_refs.sort();
+                CiteInspection {
+                    ref_id: cite.ref_id.clone(),
+                    ir: gen.debug_tree(),
+                    tokens: edges
+                        .into_iter()
+                        .filter_map(DisambToken::from_edge)
+                        .collect(),
+                    matching_refs,
+                    omitted_names: gen.omitted_names(),
+                }
+            })
+            .collect()
+    }
+
+    /// The automaton a reference's cites are matched against during disambiguation, in Graphviz
+    /// dot format. Returns None if there is no such reference.
+    pub fn reference_dfa_dot(&self, ref_id: Atom) -> Option<String> {
+        self.ref_dfa(ref_id).map(|dfa| dfa.debug_graph(self))
+    }
+}
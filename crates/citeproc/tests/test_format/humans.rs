@@ -37,6 +37,7 @@ impl CompatCitationItem {
             id: index.to_string().into(),
             cites: v,
             mode,
+            suppress_trailing_punctuation: false,
         }
     }
 }
@@ -267,11 +268,14 @@ impl JsExecutor<'_> {
                 &[PrePost(cluster.cluster_id.clone(), *note_index)],
             );
             self.to_renumbering(&mut renum, post);
-            self.proc.insert_cluster_str(ClusterStr {
-                id: cluster_id.clone(),
-                mode: mode.clone(),
-                cites: citation_items.to_vec(),
-            });
+            self.proc
+                .insert_cluster_str(ClusterStr {
+                    id: cluster_id.clone(),
+                    mode: mode.clone(),
+                    cites: citation_items.to_vec(),
+                    suppress_trailing_punctuation: false,
+                })
+                .unwrap();
             self.proc.set_cluster_order(&renum).unwrap();
             for &ClusterPosition { id, .. } in &renum {
                 if let Some(actual_note) = self.proc.get_cluster_note_number(id) {
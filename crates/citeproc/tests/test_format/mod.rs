@@ -133,6 +133,7 @@ impl TestCase {
                     id: processor.new_cluster(&str_cluster.id),
                     cites: str_cluster.cites,
                     mode: str_cluster.mode,
+                    suppress_trailing_punctuation: str_cluster.suppress_trailing_punctuation,
                 })
                 .collect()
         });
@@ -184,11 +185,12 @@ impl TestCase {
                     id: self.processor.random_cluster_id(),
                     cites,
                     mode: None,
+                    suppress_trailing_punctuation: false,
                 });
                 &clusters_auto
             };
 
-            self.processor.init_clusters(clusters.clone());
+            self.processor.init_clusters(clusters.clone()).unwrap();
             let positions: Vec<_> = clusters
                 .iter()
                 .enumerate()
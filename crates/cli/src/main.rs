@@ -210,7 +210,8 @@ fn main() {
                     id: 0,
                     note_number: 1,
                     cites: vec![citeproc::input::Cite::basic(key)],
-                }]);
+                }])
+                .unwrap();
                 db.inssert_references(refs);
 
                 let inlines = db.get_cluster(0).to_vec();
@@ -266,7 +267,7 @@ fn do_pandoc() {
             if let Some(library_path) = pandoc_meta_str(&doc, "bibliography") {
                 db.reset_references(expect_refs(library_path));
             }
-            db.init_clusters(pandoc::get_clusters(&mut doc));
+            db.init_clusters(pandoc::get_clusters(&mut doc)).unwrap();
             db.compute();
             pandoc::write_clusters(&mut doc, &db);
             serde_json::to_writer(output, &doc).expect("could not write pandoc json");
@@ -28,7 +28,9 @@ pub(crate) trait GetAttributeExtensions: GetAttribute {
         match node.attribute(attr.clone()) {
             Some(a) => match Self::get_attr(a, &info.features) {
                 Ok(val) => Ok(Some(val)),
-                Err(e) => Err(InvalidCsl::attr_val(node, attr, &e.value)),
+                Err(e) => {
+                    Err(InvalidCsl::attr_val(node, attr, &e.value).with_feature_hint(e.feature))
+                }
             },
             None => Ok(None),
         }
@@ -87,9 +89,13 @@ pub trait EnumGetAttribute: FromStr + EnumProperty {}
 impl<T: EnumGetAttribute> GetAttribute for T {
     fn get_attr(s: &str, features: &Features) -> Result<Self, UnknownAttributeValue> {
         match T::from_str(s) {
-            Ok(a) => features
-                .filter_arg(a)
-                .ok_or_else(|| UnknownAttributeValue::new(s)),
+            Ok(a) => {
+                let feature = a.get_str("feature");
+                features.filter_arg(a).ok_or_else(|| match feature {
+                    Some(feature) => UnknownAttributeValue::needs_feature(s, feature),
+                    None => UnknownAttributeValue::new(s),
+                })
+            }
             Err(_) => Err(UnknownAttributeValue::new(s)),
         }
     }
@@ -139,7 +145,7 @@ pub(crate) fn attribute_required<T: GetAttribute>(
     match node.attribute(attr) {
         Some(a) => match T::get_attr(a, &info.features) {
             Ok(val) => Ok(val),
-            Err(e) => Err(InvalidCsl::attr_val(node, attr, &e.value)),
+            Err(e) => Err(InvalidCsl::attr_val(node, attr, &e.value).with_feature_hint(e.feature)),
         },
         None => Err(InvalidCsl::missing(node, attr)),
     }
@@ -162,7 +168,8 @@ pub(crate) fn attribute_var_type<T: GetAttribute>(
                 &e.value,
                 need,
                 AnyVariable::get_attr(a, &info.features).ok(),
-            )),
+            )
+            .with_feature_hint(e.feature)),
         },
         None => Err(InvalidCsl::new(
             node,
@@ -209,7 +216,8 @@ pub(crate) fn attribute_array_var<T: GetAttribute>(
                     &e.value,
                     need,
                     AnyVariable::get_attr(&e.value, &info.features).ok(),
-                )),
+                )
+                .with_feature_hint(e.feature)),
             }
         }
         None => Ok(vec![]),
@@ -230,7 +238,9 @@ pub(crate) fn attribute_array<T: GetAttribute>(
                 .collect();
             match split {
                 Ok(val) => Ok(val),
-                Err(e) => Err(InvalidCsl::attr_val(node, attr, &e.value)),
+                Err(e) => {
+                    Err(InvalidCsl::attr_val(node, attr, &e.value).with_feature_hint(e.feature))
+                }
             }
         }
         None => Ok(vec![]),
@@ -14,12 +14,21 @@ pub(crate) type ExpName = roxmltree::ExpandedName<'static, 'static>;
 #[derive(Debug, PartialEq)]
 pub struct UnknownAttributeValue {
     pub value: String,
+    /// Set when the value is known, but only allowed with a feature that isn't enabled
+    pub feature: Option<&'static str>,
 }
 
 impl UnknownAttributeValue {
     pub fn new(s: &str) -> Self {
         UnknownAttributeValue {
             value: s.to_owned(),
+            feature: None,
+        }
+    }
+    pub fn needs_feature(s: &str, feature: &'static str) -> Self {
+        UnknownAttributeValue {
+            value: s.to_owned(),
+            feature: Some(feature),
         }
     }
 }
@@ -233,10 +242,24 @@ impl InvalidCsl {
         }
     }
 
+    /// If a rejected value was only rejected because a feature is disabled, says which one to
+    /// enable, either with `<feature>` in the style or via `ParseOptions::features`.
+    pub fn with_feature_hint(mut self, feature: Option<&str>) -> Self {
+        if let Some(feature) = feature {
+            self.hint = format!(
+                "requires <feature name=\"{}\"/> to be enabled",
+                feature.replace('_', "-")
+            );
+        }
+        self
+    }
+
     pub fn unknown_element(parent: &Node, child: &Node) -> Self {
         fn blacklist_lookup(parent_tag: &str, child_tag: &str) -> Option<&'static str> {
             match (parent_tag, child_tag) {
-                ("style", "intext") => Some("requires <feature name=\"intext\"/> to be enabled"),
+                ("style", "intext") => {
+                    Some("requires <feature name=\"custom-intext\"/> to be enabled")
+                }
                 _ => None,
             }
         }
@@ -742,6 +742,20 @@ impl From<Vec<CslError>> for ConditionError {
     }
 }
 
+/// Errors on the first of `gated` that appears on the node, hinting at the feature it needs.
+fn reject_gated_attributes(
+    node: &Node,
+    gated: &[&str],
+    feature: &str,
+) -> Result<(), ConditionError> {
+    if let Some(attr) = gated.iter().find(|&&attr| node.has_attribute(attr)) {
+        let message = format!("Unknown attribute `{}`", attr);
+        let err = InvalidCsl::new(node, message);
+        return Err(err.with_feature_hint(Some(feature)).into());
+    }
+    Ok(())
+}
+
 impl ConditionParser {
     fn from_node_custom(node: &Node, info: &ParseInfo) -> Result<Self, ConditionError> {
         let (has_year_only, has_month_or_season, has_day) = if info.features.condition_date_parts {
@@ -751,12 +765,23 @@ impl ConditionParser {
                 attribute_array_var(node, "has-day", NeedVarType::CondDate, info)?,
             )
         } else {
+            let gated = ["has-year-only", "has-month-or-season", "has-day"];
+            reject_gated_attributes(node, &gated, "condition_date_parts")?;
             Default::default()
         };
+        let (jurisdiction, subjurisdictions) = if info.features.jurisdictions {
+            (
+                attribute_option(node, "jurisdiction", info)?,
+                attribute_option_int(node, "subjurisdictions")?,
+            )
+        } else {
+            reject_gated_attributes(node, &["jurisdiction", "subjurisdictions"], "jurisdictions")?;
+            (None, None)
+        };
         let cond = ConditionParser {
             match_type: Match::from_node(node, info)?,
-            jurisdiction: attribute_option(node, "jurisdiction", info)?,
-            subjurisdictions: attribute_option_int(node, "subjurisdictions")?,
+            jurisdiction,
+            subjurisdictions,
             context: attribute_option(node, "context", info)?,
             disambiguate: bool::attribute_option(node, "disambiguate", info)?,
             variable: attribute_array_var(node, "variable", NeedVarType::Any, info)?,
@@ -1001,7 +1026,7 @@ fn disallow_default<T: Default + FromNode + AttrChecker>(
 }
 
 impl DatePart {
-    fn from_node_dp(node: &Node, full: bool, info: &ParseInfo) -> FromNodeResult<Self> {
+    fn from_node_dp(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
         let name: DatePartName = attribute_required(node, "name", info)?;
         let form = match name {
             DatePartName::Year => DatePartForm::Year(attribute_optional(node, "form", info)?),
@@ -1013,8 +1038,30 @@ impl DatePart {
         };
         Ok(DatePart {
             form,
-            // affixes not allowed in a locale date
-            affixes: disallow_default(node, !full, info)?,
+            affixes: Option::from_node(node, info)?,
+            formatting: Option::from_node(node, info)?,
+            text_case: Option::from_node(node, info)?,
+            range_delimiter: Option::from_node(node, info)?,
+        })
+    }
+}
+
+impl FromNode for DatePartOverride {
+    fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
+        let name: DatePartName = attribute_required(node, "name", info)?;
+        let form = match name {
+            DatePartName::Year => attribute_option(node, "form", info)?.map(DatePartForm::Year),
+            DatePartName::Month => {
+                attribute_option(node, "form", info)?.map(|form| DatePartForm::Month(form, false))
+            }
+            DatePartName::Day => attribute_option(node, "form", info)?.map(DatePartForm::Day),
+        };
+        // no affixes if you're calling a locale date
+        let _: Option<Affixes> = disallow_default(node, true, info)?;
+        Ok(DatePartOverride {
+            name,
+            form,
+            strip_periods: bool::attribute_option(node, "strip-periods", info)?,
             formatting: Option::from_node(node, info)?,
             text_case: Option::from_node(node, info)?,
             range_delimiter: Option::from_node(node, info)?,
@@ -1027,7 +1074,7 @@ impl FromNode for IndependentDate {
         let elements = node
             .children()
             .filter(|n| n.is_element() && n.has_tag_name("date-part"))
-            .map(|el| DatePart::from_node_dp(&el, true, info))
+            .map(|el| DatePart::from_node_dp(&el, info))
             .partition_results()?;
         Ok(IndependentDate {
             variable: attribute_var_type(node, "variable", NeedVarType::Date, info)?,
@@ -1046,8 +1093,7 @@ impl FromNode for LocalizedDate {
         let elements = node
             .children()
             .filter(|n| n.is_element() && n.has_tag_name("date-part"))
-            // no affixes if you're calling a locale date
-            .map(|el| DatePart::from_node_dp(&el, false, info))
+            .map(|el| DatePartOverride::from_node(&el, info))
             .partition_results()?;
         Ok(LocalizedDate {
             variable: attribute_var_type(node, "variable", NeedVarType::Date, info)?,
@@ -1300,7 +1346,7 @@ impl FromNode for Name {
 impl FromNode for NameEtAl {
     fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
         Ok(NameEtAl {
-            term: attribute_string(node, "term"),
+            term: attribute_optional(node, "term", info)?,
             formatting: Option::from_node(node, info)?,
         })
     }
@@ -5,7 +5,7 @@
 // Copyright © 2019 Corporation for Digital Scholarship
 
 use crate::error::{InvalidCsl, PartitionResults, StyleError};
-use crate::style::{DateForm, DatePart, Formatting, TextCase};
+use crate::style::{DateForm, DatePart, EtAlTerm, Formatting, TextCase};
 use crate::terms::*;
 use crate::variables::NumberVariable;
 use crate::{attr::*, CslError, Severity};
@@ -15,6 +15,7 @@ use roxmltree::{Document, Node};
 use std::str::FromStr;
 
 mod lang;
+mod xml;
 pub use self::lang::{IsoCountry, IsoLang, Lang, LocaleSource};
 
 pub const EN_US: &str = include_str!("locales-en-US.xml");
@@ -206,7 +207,7 @@ impl FromNode for LocaleDate {
         let elements = node
             .children()
             .filter(|n| n.is_element() && n.has_tag_name("date-part"))
-            .map(|el| DatePart::from_node_dp(&el, true, info))
+            .map(|el| DatePart::from_node_dp(&el, info))
             .partition_results()?;
         Ok(LocaleDate {
             form: attribute_required(node, "form", info)?,
@@ -363,16 +364,11 @@ impl Locale {
         &self,
         element: Option<&crate::NameEtAl>,
     ) -> Option<(String, Option<Formatting>)> {
-        let mut term = MiscTerm::EtAl;
-        let mut default = "et al";
-        let mut formatting = None;
-        if let Some(el) = element {
-            if el.term == "and others" {
-                term = MiscTerm::AndOthers;
-                default = "and others";
-            }
-            formatting = el.formatting;
-        }
+        let (term, default) = match element.map_or(EtAlTerm::EtAl, |el| el.term) {
+            EtAlTerm::EtAl => (MiscTerm::EtAl, "et al"),
+            EtAlTerm::AndOthers => (MiscTerm::AndOthers, "and others"),
+        };
+        let formatting = element.and_then(|el| el.formatting);
         let txt = self
             .get_text_term(
                 TextTermSelector::Simple(SimpleTermSelector::Misc(term, TermFormExtended::Long)),
@@ -418,7 +414,18 @@ impl Locale {
         }
         self.lang = with.lang.clone();
         extend(&mut self.simple_terms, &with.simple_terms);
-        extend(&mut self.gendered_terms, &with.gendered_terms);
+        // A style overriding e.g. `month-01` will often not repeat the locale's gender attribute,
+        // but the gender belongs to the noun, not that particular spelling of it. CSL has no way
+        // to write gender="neuter", so Neuter means unspecified.
+        for (sel, term) in with.gendered_terms.iter() {
+            let GenderedTerm(content, gender) = term;
+            let gender = match (self.gendered_terms.get(sel), gender) {
+                (Some(GenderedTerm(_, existing)), Gender::Neuter) => *existing,
+                _ => *gender,
+            };
+            self.gendered_terms
+                .insert(*sel, GenderedTerm(content.clone(), gender));
+        }
         extend(&mut self.role_terms, &with.role_terms);
         extend(&mut self.dates, &with.dates);
         // replace the whole ordinals configuration if any of them are specified
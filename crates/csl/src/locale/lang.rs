@@ -54,7 +54,10 @@ impl<'de> serde::Deserialize<'de> for Lang {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        FromStr::from_str(&s).map_err(serde::de::Error::custom)
+        Lang::parse_bcp47(&s)
+            .map(Ok)
+            .unwrap_or_else(|| FromStr::from_str(&s))
+            .map_err(serde::de::Error::custom)
     }
 }
 
@@ -116,6 +119,8 @@ impl Lang {
 use crate::attr::GetAttribute;
 use crate::error::UnknownAttributeValue;
 use crate::version::Features;
+/// Styles and locale files are parsed strictly, so a malformed `xml:lang` is reported rather than
+/// quietly corrected. [`Lang::parse_bcp47`] is only for tags supplied by the host.
 impl GetAttribute for Lang {
     fn get_attr(s: &str, _: &Features) -> Result<Self, UnknownAttributeValue> {
         Lang::from_str(s).map_err(|_| UnknownAttributeValue::new(s))
@@ -334,6 +339,79 @@ impl Lang {
     }
 }
 
+impl Lang {
+    /// Leniently parses a BCP 47 language tag, normalising it onto the `Lang` model. Use this for
+    /// tags that come from users rather than from CSL files.
+    ///
+    /// ```text
+    /// "en_US"       => en-US
+    /// "EN-us"       => en-US
+    /// "pt-BR"       => pt-BR
+    /// "zh-Hans"     => zh-CN
+    /// "zh-Hant"     => zh-TW
+    /// "sr-Latn-RS"  => sr-RS
+    /// "es-419"      => es
+    /// "de-DE-1996"  => de-DE
+    /// ```
+    ///
+    /// `Lang` has nowhere to put a script subtag, so it is dropped. The exception is Chinese
+    /// without a region, where the script picks the region whose locale file uses it. Numeric
+    /// regions, variants and extensions are dropped too.
+    ///
+    /// Returns `None` if the primary language subtag is not two or three letters, and the tag is
+    /// not an `i-` or `x-` tag either.
+    pub fn parse_bcp47(input: &str) -> Option<Self> {
+        let tag = input.trim().replace('_', "-");
+        let mut subtags = tag.split('-').filter(|s| !s.is_empty()).peekable();
+        let is_alpha = |s: &str| s.chars().all(|c| c.is_ascii_alphabetic());
+        let primary = subtags.next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "i" => {
+                let rest: Vec<&str> = subtags.collect();
+                if rest.is_empty() {
+                    return None;
+                }
+                return Some(Lang::Iana(rest.join("-").into()));
+            }
+            "x" => {
+                return subtags
+                    .next()
+                    .filter(|s| s.len() <= 8 && s.chars().all(char::is_alphanumeric))
+                    .map(|s| Lang::Unofficial(s.into()));
+            }
+            p if (2..=3).contains(&p.len()) && is_alpha(p) => {}
+            _ => return None,
+        }
+        // You can unwrap because codegen has a default case with no Err output
+        let lang = IsoLang::from_str(&primary).unwrap();
+        let script = subtags
+            .peek()
+            .filter(|s| s.len() == 4 && is_alpha(s))
+            .map(|s| s.to_ascii_lowercase());
+        if script.is_some() {
+            subtags.next();
+        }
+        let mut country = subtags
+            .next()
+            .filter(|s| s.len() == 2 && is_alpha(s))
+            .map(|s| IsoCountry::from_str(&s.to_ascii_uppercase()).unwrap());
+        if country.is_none() && lang == IsoLang::Chinese {
+            country = match script.as_deref() {
+                Some("hans") => Some(IsoCountry::CN),
+                Some("hant") => Some(IsoCountry::TW),
+                _ => None,
+            };
+        }
+        Some(Lang::Iso(lang, country))
+    }
+
+    /// Runs a `Lang` through [`Lang::parse_bcp47`], in case it was constructed by hand as e.g.
+    /// `Lang::Iso(IsoLang::Other("EN".into()), None)`.
+    pub fn normalized(&self) -> Self {
+        Lang::parse_bcp47(&self.to_string()).unwrap_or_else(|| self.clone())
+    }
+}
+
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while, take_while_m_n},
@@ -403,3 +481,78 @@ fn lang_from_str() {
     assert_eq!(Lang::from_str("i-Navajo"), Ok(iana));
     assert_eq!(Lang::from_str("x-Newspeak"), Ok(unofficial));
 }
+
+#[test]
+fn lang_round_trip() {
+    let langs = [
+        Lang::en_us(),
+        Lang::Iso(IsoLang::Deutsch, None),
+        Lang::Iso(IsoLang::Chinese, Some(IsoCountry::TW)),
+        Lang::Iso(IsoLang::English, Some(IsoCountry::Other("NZ".into()))),
+        Lang::Iso(IsoLang::Other("haw".into()), None),
+        Lang::Iana("Navajo".into()),
+        Lang::Unofficial("Newspeak".into()),
+    ];
+    for lang in langs.iter() {
+        assert_eq!(Lang::from_str(&lang.to_string()).as_ref(), Ok(lang));
+        assert_eq!(&lang.normalized(), lang);
+    }
+}
+
+#[test]
+fn lang_parse_bcp47() {
+    let parse = |s| Lang::parse_bcp47(s);
+    let iso = |l, c| Some(Lang::Iso(l, c));
+    assert_eq!(parse("en_US"), iso(IsoLang::English, Some(IsoCountry::US)));
+    assert_eq!(parse("EN-us"), iso(IsoLang::English, Some(IsoCountry::US)));
+    assert_eq!(
+        parse("pt-BR"),
+        iso(IsoLang::Portuguese, Some(IsoCountry::BR))
+    );
+    assert_eq!(
+        parse("zh-Hans"),
+        iso(IsoLang::Chinese, Some(IsoCountry::CN))
+    );
+    assert_eq!(
+        parse("zh-Hant"),
+        iso(IsoLang::Chinese, Some(IsoCountry::TW))
+    );
+    assert_eq!(
+        parse("zh-Hant-HK"),
+        iso(IsoLang::Chinese, Some(IsoCountry::Other("HK".into())))
+    );
+    assert_eq!(
+        parse("sr-Latn-RS"),
+        iso(
+            IsoLang::Other("sr".into()),
+            Some(IsoCountry::Other("RS".into()))
+        )
+    );
+    assert_eq!(parse("es-419"), iso(IsoLang::Spanish, None));
+    assert_eq!(
+        parse("de-DE-1996"),
+        iso(IsoLang::Deutsch, Some(IsoCountry::DE))
+    );
+    assert_eq!(
+        parse("x-Newspeak"),
+        Some(Lang::Unofficial("Newspeak".into()))
+    );
+    assert_eq!(parse("i-Navajo"), Some(Lang::Iana("Navajo".into())));
+    assert_eq!(parse("English"), None);
+    assert_eq!(parse(""), None);
+    assert_eq!(
+        Lang::Iso(IsoLang::Other("EN".into()), None).normalized(),
+        Lang::Iso(IsoLang::English, None)
+    );
+}
+
+#[test]
+fn lang_attribute_is_strict() {
+    let features = Features::default();
+    assert_eq!(
+        Lang::get_attr("pt-BR", &features),
+        Ok(Lang::Iso(IsoLang::Portuguese, Some(IsoCountry::BR)))
+    );
+    assert!(Lang::get_attr("en_US", &features).is_err());
+    assert!(Lang::get_attr("zh-Hans", &features).is_err());
+}
@@ -0,0 +1,265 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! Writes a [Locale] back out as locale XML, e.g. to keep a copy of exactly the terms and date
+//! formats a document was rendered with.
+
+use super::{Locale, LocaleDate};
+use crate::style::{DateForm, DatePart, DatePartForm, Formatting, TextCase};
+use crate::terms::*;
+use std::fmt::Write;
+
+impl Locale {
+    /// Serializes this locale as a `<locale>` document that [Locale::parse] reads back to an
+    /// equal `Locale`. Terms are written in a stable order, so the same locale always produces
+    /// the same XML.
+    pub fn to_xml(&self) -> String {
+        let mut s = String::new();
+        s.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        s.push_str("<locale xmlns=\"http://purl.org/net/xbiblio/csl\"");
+        attr(&mut s, "version", &self.version);
+        if let Some(lang) = &self.lang {
+            attr(&mut s, "xml:lang", &lang.to_string());
+        }
+        s.push_str(">\n");
+
+        let options = &self.options_node;
+        if options.limit_day_ordinals_to_day_1.is_some() || options.punctuation_in_quote.is_some() {
+            s.push_str("  <style-options");
+            if let Some(limit) = options.limit_day_ordinals_to_day_1 {
+                attr(&mut s, "limit-day-ordinals-to-day-1", bool_str(limit));
+            }
+            if let Some(piq) = options.punctuation_in_quote {
+                attr(&mut s, "punctuation-in-quote", bool_str(piq));
+            }
+            s.push_str("/>\n");
+        }
+
+        for form in &[DateForm::Text, DateForm::Numeric] {
+            if let Some(date) = self.dates.get(form) {
+                write_date(&mut s, date);
+            }
+        }
+
+        let mut terms = Vec::new();
+        for (sel, content) in &self.simple_terms {
+            let (name, form) = TextTermSelector::Simple(*sel).name_and_form();
+            terms.push(term(name, form, "", content));
+        }
+        for (sel, GenderedTerm(content, gender)) in &self.gendered_terms {
+            let (name, form) = TextTermSelector::Gendered(*sel).name_and_form();
+            let mut gender_attr = String::new();
+            if let Some(gender) = gender_str(*gender) {
+                attr(&mut gender_attr, "gender", gender);
+            }
+            terms.push(term(name, form, &gender_attr, content));
+        }
+        for (sel, content) in &self.role_terms {
+            let (name, form) = TextTermSelector::Role(*sel).name_and_form();
+            terms.push(term(name, form, "", content));
+        }
+        for (OrdinalTermSelector(ordinal, gender), content) in &self.ordinal_terms {
+            let mut extra = String::new();
+            if let Some(gender) = gender_str(*gender) {
+                attr(&mut extra, "gender-form", gender);
+            }
+            let name = match *ordinal {
+                OrdinalTerm::Ordinal => "ordinal".to_owned(),
+                OrdinalTerm::Mod100(n, ordinal_match) => {
+                    if ordinal_match != OrdinalMatch::default_for(n) {
+                        attr(&mut extra, "match", match_str(ordinal_match));
+                    }
+                    format!("ordinal-{:02}", n)
+                }
+                long => format!("long-ordinal-{:02}", long.to_number()),
+            };
+            let content = TermPlurality::Invariant(content.clone());
+            terms.push(term(&name, "long", &extra, &content));
+        }
+        terms.sort();
+        if !terms.is_empty() {
+            s.push_str("  <terms>\n");
+            for term in terms {
+                s.push_str("    ");
+                s.push_str(&term);
+                s.push('\n');
+            }
+            s.push_str("  </terms>\n");
+        }
+        s.push_str("</locale>\n");
+        s
+    }
+}
+
+fn term(name: &str, form: &str, extra_attrs: &str, content: &TermPlurality) -> String {
+    let mut s = String::from("<term");
+    attr(&mut s, "name", name);
+    if form != "long" {
+        attr(&mut s, "form", form);
+    }
+    s.push_str(extra_attrs);
+    s.push('>');
+    match content {
+        TermPlurality::Invariant(text) => escape(&mut s, text, false),
+        TermPlurality::Pluralized { single, multiple } => {
+            s.push_str("<single>");
+            escape(&mut s, single, false);
+            s.push_str("</single><multiple>");
+            escape(&mut s, multiple, false);
+            s.push_str("</multiple>");
+        }
+    }
+    s.push_str("</term>");
+    s
+}
+
+fn write_date(s: &mut String, date: &LocaleDate) {
+    s.push_str("  <date");
+    attr(s, "form", date.form.as_ref());
+    if let Some(delimiter) = &date.delimiter {
+        attr(s, "delimiter", delimiter);
+    }
+    if date.text_case != TextCase::None {
+        attr(s, "text-case", date.text_case.as_ref());
+    }
+    formatting(s, date.formatting.as_ref());
+    s.push_str(">\n");
+    for part in &date.date_parts {
+        write_date_part(s, part);
+    }
+    s.push_str("  </date>\n");
+}
+
+fn write_date_part(s: &mut String, part: &DatePart) {
+    s.push_str("    <date-part");
+    match part.form {
+        DatePartForm::Day(form) => {
+            attr(s, "name", "day");
+            attr(s, "form", form.as_ref());
+        }
+        DatePartForm::Month(form, strip_periods) => {
+            attr(s, "name", "month");
+            attr(s, "form", form.as_ref());
+            if strip_periods {
+                attr(s, "strip-periods", "true");
+            }
+        }
+        DatePartForm::Year(form) => {
+            attr(s, "name", "year");
+            attr(s, "form", form.as_ref());
+        }
+    }
+    if let Some(affixes) = &part.affixes {
+        if !affixes.prefix.is_empty() {
+            attr(s, "prefix", &affixes.prefix);
+        }
+        if !affixes.suffix.is_empty() {
+            attr(s, "suffix", &affixes.suffix);
+        }
+    }
+    if let Some(text_case) = part.text_case {
+        attr(s, "text-case", text_case.as_ref());
+    }
+    if let Some(range_delimiter) = &part.range_delimiter {
+        attr(s, "range-delimiter", &range_delimiter.0);
+    }
+    formatting(s, part.formatting.as_ref());
+    s.push_str("/>\n");
+}
+
+fn formatting(s: &mut String, formatting: Option<&Formatting>) {
+    let f = match formatting {
+        Some(f) => f,
+        None => return,
+    };
+    if let Some(x) = f.font_style {
+        attr(s, "font-style", x.as_ref());
+    }
+    if let Some(x) = f.font_variant {
+        attr(s, "font-variant", x.as_ref());
+    }
+    if let Some(x) = f.font_weight {
+        attr(s, "font-weight", x.as_ref());
+    }
+    if let Some(x) = f.vertical_alignment {
+        attr(s, "vertical-align", x.as_ref());
+    }
+    if let Some(x) = f.text_decoration {
+        attr(s, "text-decoration", x.as_ref());
+    }
+}
+
+/// Neuter is what you get when there is no gender attribute; CSL has no way to write it.
+fn gender_str(gender: Gender) -> Option<&'static str> {
+    match gender {
+        Gender::Masculine => Some("masculine"),
+        Gender::Feminine => Some("feminine"),
+        Gender::Neuter => None,
+    }
+}
+
+fn match_str(ordinal_match: OrdinalMatch) -> &'static str {
+    match ordinal_match {
+        OrdinalMatch::LastDigit => "last-digit",
+        OrdinalMatch::LastTwoDigits => "last-two-digits",
+        OrdinalMatch::WholeNumber => "whole-number",
+    }
+}
+
+fn bool_str(b: bool) -> &'static str {
+    if b {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+fn attr(s: &mut String, name: &str, value: &str) {
+    write!(s, " {}=\"", name).unwrap();
+    escape(s, value, true);
+    s.push('"');
+}
+
+fn escape(s: &mut String, text: &str, in_attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' if in_attribute => s.push_str("&quot;"),
+            c => s.push(c),
+        }
+    }
+}
+
+#[test]
+fn round_trip() {
+    let en_us = Locale::parse(super::EN_US).unwrap();
+    let xml = en_us.to_xml();
+    assert_eq!(Locale::parse(&xml).unwrap(), en_us);
+    assert_eq!(xml, en_us.to_xml());
+
+    let mut custom = Locale::parse(
+        r#"<locale xml:lang="fr-FR">
+            <style-options punctuation-in-quote="false" />
+            <date form="numeric" delimiter="/">
+                <date-part name="day" form="numeric-leading-zeros" font-weight="bold" />
+                <date-part name="month" form="short" strip-periods="true" range-delimiter="-" />
+                <date-part name="year" text-case="uppercase" />
+            </date>
+            <terms>
+                <term name="edition" gender="feminine">édition</term>
+                <term name="ordinal-01" gender-form="feminine" match="whole-number">re</term>
+                <term name="page" form="short"><single>p.</single><multiple>pp. &amp; &lt;</multiple></term>
+                <term name="editor" form="verb">édité par</term>
+            </terms>
+        </locale>"#,
+    )
+    .unwrap();
+    assert_eq!(Locale::parse(&custom.to_xml()).unwrap(), custom);
+    custom.merge(&en_us);
+    assert_eq!(Locale::parse(&custom.to_xml()).unwrap(), custom);
+}
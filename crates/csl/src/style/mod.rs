@@ -29,6 +29,9 @@ type StripPeriods = bool;
 type Quotes = bool;
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum TextSource {
     Macro(SmartString),
     Value(SmartString),
@@ -42,6 +45,7 @@ impl Default for TextSource {
 }
 
 #[derive(Default, Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct TextElement {
     pub source: TextSource,
     pub formatting: Option<Formatting>,
@@ -53,6 +57,7 @@ pub struct TextElement {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct LabelElement {
     pub variable: NumberVariable,
     pub form: TermForm,
@@ -64,6 +69,7 @@ pub struct LabelElement {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NumberElement {
     pub variable: NumberVariable,
     pub form: NumericForm,
@@ -74,6 +80,9 @@ pub struct NumberElement {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Element {
     /// <cs:text>
     Text(TextElement),
@@ -94,6 +103,7 @@ pub enum Element {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Group {
     pub formatting: Option<Formatting>,
     pub delimiter: Option<SmartString>,
@@ -105,6 +115,9 @@ pub struct Group {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum BodyDate {
     Indep(IndependentDate),
     Local(LocalizedDate),
@@ -121,6 +134,8 @@ impl BodyDate {
 
 /// e.g. for <text variable="title" form="short" />
 #[derive(AsRefStr, EnumString, EnumProperty, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum VariableForm {
     Long,
@@ -135,6 +150,8 @@ impl Default for VariableForm {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum NumericForm {
     Numeric,
@@ -151,6 +168,7 @@ impl Default for NumericForm {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Affixes {
     pub prefix: SmartString,
     pub suffix: SmartString,
@@ -256,6 +274,8 @@ pub enum DisplayMode {
 impl EnumGetAttribute for DisplayMode {}
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum TextCase {
     None,
@@ -355,6 +375,8 @@ impl Default for VerticalAlignment {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum Plural {
     Contextual,
@@ -382,7 +404,10 @@ impl IsIndependent for Cond {
     }
 }
 
-#[derive(Debug, Eq, Hash, Clone, PartialEq)]
+#[derive(Debug, Eq, Hash, Clone, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Cond {
     IsNumeric(AnyVariable),
     Variable(AnyVariable),
@@ -401,11 +426,23 @@ pub enum Cond {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CondSet {
     pub match_type: Match,
+    /// Serialized in [Cond]'s order, so that the same style always gives the same output.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_sorted_conds"))]
     pub conds: FnvHashSet<Cond>,
 }
 
+#[cfg(feature = "serde")]
+fn serialize_sorted_conds<S>(conds: &FnvHashSet<Cond>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let sorted: std::collections::BTreeSet<_> = conds.iter().collect();
+    serializer.collect_seq(sorted)
+}
+
 impl From<ConditionParser> for CondSet {
     #[rustfmt::skip]
     // Much neater to treat them all the same
@@ -457,8 +494,10 @@ pub(crate) struct ConditionParser {
     pub locator: Vec<LocatorType>,
     pub is_uncertain_date: Vec<DateVariable>,
 
-    // TODO: do not populate in plain CSL mode
+    /// https://citeproc-js.readthedocs.io/en/latest/csl-m/index.html#jurisdiction-extension
+    /// Space-separated, like `type`.
     pub jurisdiction: Option<SmartString>,
+    /// https://citeproc-js.readthedocs.io/en/latest/csl-m/index.html#subjurisdictions-extension
     pub subjurisdictions: Option<u32>,
 
     /// https://citeproc-js.readthedocs.io/en/latest/csl-m/index.html#has-year-only-extension
@@ -477,7 +516,11 @@ pub(crate) struct ConditionParser {
     pub is_plural: Vec<NameVariable>,
 }
 
-#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum Context {
     Citation,
@@ -505,6 +548,8 @@ impl ConditionParser {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum Match {
     Any,
@@ -523,19 +568,24 @@ impl Default for Match {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 // in CSL 1.0.1, conditions.len() == 1
 pub struct IfThen(pub Conditions, pub Vec<Element>);
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Conditions(pub Match, pub Vec<CondSet>);
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Else(pub Vec<Element>);
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Choose(pub IfThen, pub Vec<IfThen>, pub Else);
 
 #[derive(Debug, Default, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Names {
     // inheritable.
     pub delimiter: Option<SmartString>,
@@ -563,6 +613,8 @@ pub struct Names {
 /// cs:name. Similarly, names-delimiter corresponds to the delimiter attribute on cs:names.
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum NameAnd {
     Text,
@@ -573,12 +625,14 @@ impl EnumGetAttribute for NameAnd {}
 
 /// It is not entirely clear which attributes `<cs:with>` supports.
 #[derive(Debug, Eq, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NameWith {
     pub formatting: Option<Formatting>,
     pub affixes: Option<Affixes>,
 }
 
 #[derive(Debug, Eq, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Institution {
     pub and: Option<NameAnd>,
     pub delimiter: Option<SmartString>,
@@ -601,6 +655,7 @@ pub struct Institution {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct InstitutionPart {
     pub name: InstitutionPartName,
     pub formatting: Option<Formatting>,
@@ -612,6 +667,9 @@ pub struct InstitutionPart {
 type IfShort = bool;
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum InstitutionPartName {
     Long(IfShort),
@@ -627,6 +685,8 @@ impl Default for InstitutionPartName {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum InstitutionParts {
     Long,
@@ -644,6 +704,8 @@ impl Default for InstitutionParts {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum InstitutionUseFirst {
     /// Set with `use-first="1"`
@@ -656,6 +718,7 @@ pub enum InstitutionUseFirst {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Name {
     pub and: Option<NameAnd>,
     /// Between individual names for the same variable
@@ -795,6 +858,7 @@ impl Name {
     }
 }
 #[derive(Debug, Default, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NameLabelInput {
     pub form: Option<TermFormExtended>,
     pub formatting: Option<Formatting>,
@@ -838,6 +902,7 @@ impl NameLabelInput {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NameLabel {
     pub form: TermFormExtended,
     pub formatting: Option<Formatting>,
@@ -849,13 +914,32 @@ pub struct NameLabel {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NameEtAl {
-    // TODO: only accept "et-al" or "and others"
-    pub term: String,
+    pub term: EtAlTerm,
     pub formatting: Option<Formatting>,
 }
 
+/// The two terms an `<et-al>` element may select.
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum EtAlTerm {
+    #[strum(serialize = "et-al")]
+    EtAl,
+    #[strum(serialize = "and others")]
+    AndOthers,
+}
+impl EnumGetAttribute for EtAlTerm {}
+
+impl Default for EtAlTerm {
+    fn default() -> Self {
+        EtAlTerm::EtAl
+    }
+}
+
+#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum DemoteNonDroppingParticle {
     Never,
@@ -871,6 +955,8 @@ impl Default for DemoteNonDroppingParticle {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum DelimiterPrecedes {
     Contextual,
@@ -887,6 +973,8 @@ impl Default for DelimiterPrecedes {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum NameForm {
     Long,
@@ -896,6 +984,8 @@ pub enum NameForm {
 impl EnumGetAttribute for NameForm {}
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum NameAsSortOrder {
     First,
@@ -904,6 +994,8 @@ pub enum NameAsSortOrder {
 impl EnumGetAttribute for NameAsSortOrder {}
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum NamePartName {
     Given,
@@ -912,6 +1004,7 @@ pub enum NamePartName {
 impl EnumGetAttribute for NamePartName {}
 
 #[derive(Debug, Eq, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct NamePart {
     pub name: NamePartName,
     pub affixes: Option<Affixes>,
@@ -920,9 +1013,12 @@ pub struct NamePart {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Substitute(pub Vec<Element>);
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum GivenNameDisambiguationRule {
     AllNames,
@@ -941,6 +1037,8 @@ impl Default for GivenNameDisambiguationRule {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum Collapse {
     CitationNumber,
@@ -951,6 +1049,7 @@ pub enum Collapse {
 impl EnumGetAttribute for Collapse {}
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Citation {
     pub disambiguate_add_names: bool,
     pub disambiguate_add_givenname: bool,
@@ -1012,6 +1111,7 @@ impl Citation {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Bibliography {
     pub sort: Option<Sort>,
     pub layout: Layout,
@@ -1027,6 +1127,7 @@ pub struct Bibliography {
 
 /// cs:intext element
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct InText {
     pub layout: Layout,
     pub and: Option<NameAnd>,
@@ -1035,6 +1136,8 @@ pub struct InText {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum SecondFieldAlign {
     Flush,
@@ -1043,6 +1146,8 @@ pub enum SecondFieldAlign {
 impl EnumGetAttribute for SecondFieldAlign {}
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum SubsequentAuthorSubstituteRule {
     CompleteAll,
@@ -1059,11 +1164,13 @@ impl Default for SubsequentAuthorSubstituteRule {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Sort {
     pub keys: Vec<SortKey>,
 }
 
 #[derive(Debug, Eq, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct SortKey {
     pub sort_source: SortSource,
     pub names_min: Option<u32>,
@@ -1092,12 +1199,17 @@ impl SortKey {
 
 /// You must sort on either a variable or a macro
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum SortSource {
     Variable(AnyVariable),
     Macro(SmartString),
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum SortDirection {
     Ascending,
@@ -1113,6 +1225,7 @@ impl Default for SortDirection {
 
 // TODO: Multiple layouts in CSL-M with locale="en es de" etc
 #[derive(Default, Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Layout {
     pub affixes: Option<Affixes>,
     pub formatting: Option<Formatting>,
@@ -1146,8 +1259,10 @@ impl Default for StyleClass {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Style {
     pub class: StyleClass,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_sorted_macros"))]
     pub macros: FnvHashMap<SmartString, Vec<Element>>,
     pub citation: Citation,
     pub bibliography: Option<Bibliography>,
@@ -1157,6 +1272,12 @@ pub struct Style {
     pub name_inheritance: Name,
     pub names_delimiter: Option<SmartString>,
     /// `None` is the 'override everything' locale.
+    ///
+    /// Serialized as locale XML keyed by language, with `""` for the `None` locale.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_locale_overrides")
+    )]
     pub locale_overrides: FnvHashMap<Option<Lang>, Locale>,
     pub default_locale: Option<Lang>,
     pub version_req: CslVersionReq,
@@ -1165,6 +1286,36 @@ pub struct Style {
     pub initialize_with_hyphen: bool, // default is true
 }
 
+#[cfg(feature = "serde")]
+fn serialize_sorted_macros<S>(
+    macros: &FnvHashMap<SmartString, Vec<Element>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let sorted: std::collections::BTreeMap<_, _> = macros.iter().collect();
+    serializer.collect_map(sorted)
+}
+
+#[cfg(feature = "serde")]
+fn serialize_locale_overrides<S>(
+    overrides: &FnvHashMap<Option<Lang>, Locale>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let sorted: std::collections::BTreeMap<_, _> = overrides
+        .iter()
+        .map(|(lang, locale)| {
+            let key = lang.as_ref().map(|l| l.to_string()).unwrap_or_default();
+            (key, locale.to_xml())
+        })
+        .collect();
+    serializer.collect_map(sorted)
+}
+
 impl Default for Style {
     fn default() -> Self {
         Style {
@@ -1222,6 +1373,7 @@ impl Style {
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct RangeDelimiter(pub SmartString);
 
 impl Default for RangeDelimiter {
@@ -1244,6 +1396,8 @@ impl FromStr for RangeDelimiter {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum DateParts {
     YearMonthDay,
@@ -1259,9 +1413,10 @@ impl Default for DateParts {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
-/// Strictly used for parsing day/month/year
-pub(crate) enum DatePartName {
+pub enum DatePartName {
     Day,
     Month,
     Year,
@@ -1269,6 +1424,8 @@ pub(crate) enum DatePartName {
 impl EnumGetAttribute for DatePartName {}
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum DayForm {
     Numeric,
@@ -1283,6 +1440,8 @@ impl Default for DayForm {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum MonthForm {
     Long,
@@ -1298,6 +1457,8 @@ impl Default for MonthForm {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum YearForm {
     Long,
@@ -1311,6 +1472,8 @@ impl Default for YearForm {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum DateForm {
     Text,
@@ -1319,6 +1482,9 @@ pub enum DateForm {
 impl EnumGetAttribute for DateForm {}
 
 #[derive(Debug, Display, Eq, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum DatePartForm {
     Day(DayForm),
     Month(MonthForm, StripPeriods),
@@ -1350,6 +1516,7 @@ impl PartialOrd for DatePartForm {
 }
 
 #[derive(Debug, Default, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct DatePart {
     pub form: DatePartForm,
     pub affixes: Option<Affixes>,
@@ -1365,9 +1532,54 @@ impl Default for DatePartForm {
     }
 }
 
+/// A `<date-part>` in a date that calls a localized date format. The attributes it has override
+/// those on the locale's `<date-part>` of the same name; the rest are left as the locale has them.
+#[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DatePartOverride {
+    pub name: DatePartName,
+    /// The `form` attribute, if present. The month's [StripPeriods] lives in `strip_periods`.
+    pub form: Option<DatePartForm>,
+    pub strip_periods: Option<StripPeriods>,
+    pub formatting: Option<Formatting>,
+    pub text_case: Option<TextCase>,
+    pub range_delimiter: Option<RangeDelimiter>,
+}
+
+impl DatePartOverride {
+    /// Returns the locale's `part` with these overrides applied, or None if `part` is a
+    /// different part of the date. Affixes are always the locale's, as the style may not set
+    /// them on a localized date.
+    pub fn apply_to(&self, part: &DatePart) -> Option<DatePart> {
+        let form = match (self.name, part.form) {
+            (DatePartName::Day, DatePartForm::Day(_))
+            | (DatePartName::Year, DatePartForm::Year(_)) => self.form.unwrap_or(part.form),
+            (DatePartName::Month, DatePartForm::Month(form, strip_periods)) => {
+                let form = match self.form {
+                    Some(DatePartForm::Month(form, _)) => form,
+                    _ => form,
+                };
+                DatePartForm::Month(form, self.strip_periods.unwrap_or(strip_periods))
+            }
+            _ => return None,
+        };
+        Some(DatePart {
+            form,
+            affixes: part.affixes.clone(),
+            formatting: self.formatting.or(part.formatting),
+            text_case: self.text_case.or(part.text_case),
+            range_delimiter: self
+                .range_delimiter
+                .clone()
+                .or_else(|| part.range_delimiter.clone()),
+        })
+    }
+}
+
 /// A date element that fully defines its own output.
 /// It is 'independent' of any localization.
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct IndependentDate {
     pub variable: DateVariable,
     // TODO: limit each <date-part name="XXX"> to one per?
@@ -1381,10 +1593,11 @@ pub struct IndependentDate {
 
 /// A date element in the main body of a style that refers to a `LocaleDate`
 #[derive(Debug, Eq, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct LocalizedDate {
     pub variable: DateVariable,
     pub parts_selector: DateParts,
-    pub date_parts: Vec<DatePart>,
+    pub date_parts: Vec<DatePartOverride>,
     pub form: DateForm,
     pub affixes: Option<Affixes>,
     pub formatting: Option<Formatting>,
@@ -1392,7 +1605,11 @@ pub struct LocalizedDate {
     pub text_case: TextCase,
 }
 
-#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum Position {
     First,
@@ -1446,6 +1663,8 @@ impl Position {
 
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#appendix-v-page-range-formats)
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum PageRangeFormat {
     Chicago,
@@ -1455,7 +1674,22 @@ pub enum PageRangeFormat {
 }
 impl EnumGetAttribute for PageRangeFormat {}
 
-#[derive(AsRefStr, EnumProperty, EnumIter, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    AsRefStr,
+    EnumProperty,
+    EnumIter,
+    EnumString,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum CslType {
     Article,
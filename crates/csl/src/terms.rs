@@ -14,7 +14,10 @@ use std::str::FromStr;
 use super::attr::{EnumGetAttribute, GetAttribute};
 use super::variables::{NameVariable, NumberVariable};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum TextTermSelector {
     Simple(SimpleTermSelector),
     Gendered(GenderedTermSelector),
@@ -22,6 +25,26 @@ pub enum TextTermSelector {
     // You can't render ordinals using a <text> node, only using <number>
 }
 
+impl TextTermSelector {
+    /// The term's name and form as they would be written in a style or locale file, e.g.
+    /// `("and others", "long")`.
+    pub fn name_and_form(&self) -> (&str, &str) {
+        use crate::terms::GenderedTermSelector as G;
+        use crate::terms::SimpleTermSelector as S;
+        use crate::terms::TextTermSelector::*;
+        match self {
+            Simple(S::Misc(t, form)) => (t.as_ref(), form.as_ref()),
+            Simple(S::Category(t, form)) => (t.as_ref(), form.as_ref()),
+            Simple(S::Quote(t)) => (t.as_ref(), "long"),
+            Gendered(G::Number(t, form)) => (t.as_ref(), form.as_ref()),
+            Gendered(G::Locator(t, form)) => (t.as_ref(), form.as_ref()),
+            Gendered(G::Month(t, form)) => (t.as_ref(), form.as_ref()),
+            Gendered(G::Season(t, form)) => (t.as_ref(), form.as_ref()),
+            Role(RoleTermSelector(t, form)) => (t.as_ref(), form.as_ref()),
+        }
+    }
+}
+
 pub enum AnyTermName {
     Number(NumberVariable),
     Month(MonthTerm),
@@ -78,6 +101,9 @@ impl GetAttribute for AnyTermName {
 
 /// TermSelector is used
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum SimpleTermSelector {
     Misc(MiscTerm, TermFormExtended),
     Category(Category, TermForm),
@@ -104,6 +130,7 @@ impl SimpleTermSelector {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct OrdinalTermSelector(pub OrdinalTerm, pub Gender);
 
 struct OrdinalTermIter(Option<OrdinalTerm>);
@@ -154,6 +181,9 @@ impl OrdinalTermSelector {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum GenderedTermSelector {
     /// Edition is the only MiscTerm that can have a gender, so it's here instead
     Number(NumberVariable, TermForm),
@@ -242,6 +272,7 @@ impl GenderedTermSelector {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct RoleTermSelector(pub RoleTerm, pub TermFormExtended);
 
 impl RoleTermSelector {
@@ -255,9 +286,12 @@ impl RoleTermSelector {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct GenderedTerm(pub TermPlurality, pub Gender);
 
 #[derive(AsRefStr, EnumString, EnumProperty, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum TermForm {
     Long,
@@ -294,6 +328,8 @@ impl Iterator for TermFallbackIter {
 }
 /// Includes the extra Verb and VerbShort variants
 #[derive(AsRefStr, EnumString, EnumProperty, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum TermFormExtended {
     Long,
@@ -335,6 +371,9 @@ impl Iterator for TermFallbackExtendedIter {
 }
 
 #[derive(AsRefStr, EnumString, EnumProperty, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum TermPlurality {
     Pluralized { single: String, multiple: String },
@@ -405,6 +444,8 @@ impl TermPlurality {
 ///    2. Would also look up OridnalMatch::LastTwoDigits Neuter
 ///
 #[derive(AsStaticStr, EnumString, EnumProperty, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum Gender {
     Masculine,
@@ -422,6 +463,8 @@ impl Default for Gender {
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#ordinal-suffixes)
 /// LastTwoDigits is the default
 #[derive(AsStaticStr, EnumString, EnumProperty, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum OrdinalMatch {
     /// Default for `Mod100(n) if n < 10`. Matches 9, 29, 109, 129.
@@ -439,8 +482,10 @@ impl Default for OrdinalMatch {
 }
 
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#locators)
-#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[derive(
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[strum(serialize_all = "kebab_case")]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[non_exhaustive]
@@ -493,8 +538,24 @@ impl Default for LocatorType {
     }
 }
 
+impl LocatorType {
+    /// Locators numbered the way pages are, whose ranges are formatted with the style's
+    /// `page-range-format` just like the `page` variable. Ranges of other types (`chap. 3–5`)
+    /// still get the locale's page-range-delimiter, but are never collapsed.
+    pub fn is_paginated(self) -> bool {
+        match self {
+            LocatorType::Page | LocatorType::Folio | LocatorType::Column | LocatorType::Line => {
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#quotes)
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 pub enum QuoteTerm {
     OpenQuote,
@@ -504,6 +565,7 @@ pub enum QuoteTerm {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 // Strum's auto kebab_case doesn't hyphenate to "season-01", so manual it is
 pub enum SeasonTerm {
     #[strum(serialize = "season-01")]
@@ -519,6 +581,8 @@ pub enum SeasonTerm {
 /// Yes, this differs slightly from NameVariable.
 /// It includes "editortranslator" for the names special case.
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 #[non_exhaustive]
 pub enum RoleTerm {
@@ -640,6 +704,8 @@ pub enum Category {
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 #[non_exhaustive]
 pub enum MiscTerm {
@@ -687,6 +753,7 @@ pub enum MiscTerm {
 
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#months)
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[non_exhaustive]
 pub enum MonthTerm {
     #[strum(serialize = "month-01")]
@@ -738,6 +805,9 @@ impl MonthTerm {
 
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#quotes)
 #[derive(EnumProperty, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum OrdinalTerm {
     Ordinal,
     Mod100(u32, OrdinalMatch),
@@ -170,3 +170,67 @@ fn wrong_tag_name() {
     "#
     );
 }
+
+#[test]
+fn feature_gated_hints() {
+    fn hints(cond: &str, features: Features) -> Vec<String> {
+        let xml = format!(
+            r#"<style version="1.0" class="in-text">
+                <citation><layout>
+                    <choose><if {}><text value="x" /></if></choose>
+                </layout></citation>
+            </style>"#,
+            cond
+        );
+        let options = ParseOptions {
+            allow_no_info: true,
+            features: Some(features),
+            ..Default::default()
+        };
+        match Style::parse_with_opts(&xml, options) {
+            Ok(_) => vec![],
+            Err(StyleError::Invalid(CslError(errs))) => errs.into_iter().map(|e| e.hint).collect(),
+            Err(e) => panic!("{}", e),
+        }
+    }
+    let cslm_legal_types = Features {
+        cslm_legal_types: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        hints(r#"type="gazette""#, cslm_legal_types),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        hints(r#"type="gazette""#, Features::new()),
+        vec![r#"requires <feature name="cslm-legal-types"/> to be enabled"#]
+    );
+    assert_eq!(
+        hints(r#"variable="publication-date""#, Features::new()),
+        vec![r#"requires <feature name="var-publications"/> to be enabled"#]
+    );
+    let date_parts = Features {
+        condition_date_parts: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        hints(r#"has-day="issued""#, date_parts),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        hints(r#"has-day="issued""#, Features::new()),
+        vec![r#"requires <feature name="condition-date-parts"/> to be enabled"#]
+    );
+    let jurisdictions = Features {
+        jurisdictions: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        hints(r#"jurisdiction="us""#, jurisdictions),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        hints(r#"subjurisdictions="1""#, Features::new()),
+        vec![r#"requires <feature name="jurisdictions"/> to be enabled"#]
+    );
+}
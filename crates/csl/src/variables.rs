@@ -11,8 +11,13 @@ use super::error::*;
 use super::version::Features;
 use super::IsIndependent;
 use super::Style;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
-#[derive(Debug, Eq, Copy, Clone, PartialEq, EnumProperty, Hash)]
+#[derive(Debug, Eq, Copy, Clone, PartialEq, EnumProperty, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum AnyVariable {
     Ordinary(Variable),
     Name(NameVariable),
@@ -20,6 +25,18 @@ pub enum AnyVariable {
     Number(NumberVariable),
 }
 
+impl AnyVariable {
+    /// The name used in CSL and CSL-JSON, e.g. `container-title`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AnyVariable::Ordinary(v) => v.as_ref(),
+            AnyVariable::Name(v) => v.as_ref(),
+            AnyVariable::Date(v) => v.as_ref(),
+            AnyVariable::Number(v) => v.as_ref(),
+        }
+    }
+}
+
 impl FromStr for AnyVariable {
     type Err = strum::ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -45,16 +62,32 @@ impl EnumGetAttribute for DateVariable {}
 impl GetAttribute for AnyVariable {
     fn get_attr(s: &str, features: &Features) -> Result<Self, UnknownAttributeValue> {
         use self::AnyVariable::*;
-        if let Ok(v) = Variable::get_attr(s, features) {
-            return Ok(Ordinary(v));
-        } else if let Ok(v) = NameVariable::get_attr(s, features) {
-            return Ok(Name(v));
-        } else if let Ok(v) = DateVariable::get_attr(s, features) {
-            return Ok(Date(v));
-        } else if let Ok(v) = NumberVariable::get_attr(s, features) {
-            return Ok(Number(v));
-        }
-        Err(UnknownAttributeValue::new(s))
+        let ordinary = match Variable::get_attr(s, features) {
+            Ok(v) => return Ok(Ordinary(v)),
+            Err(e) => e,
+        };
+        let name = match NameVariable::get_attr(s, features) {
+            Ok(v) => return Ok(Name(v)),
+            Err(e) => e,
+        };
+        let date = match DateVariable::get_attr(s, features) {
+            Ok(v) => return Ok(Date(v)),
+            Err(e) => e,
+        };
+        let number = match NumberVariable::get_attr(s, features) {
+            Ok(v) => return Ok(Number(v)),
+            Err(e) => e,
+        };
+        // Keep the reason if it was only rejected for a disabled feature
+        let feature = ordinary
+            .feature
+            .or(name.feature)
+            .or(date.feature)
+            .or(number.feature);
+        Err(UnknownAttributeValue {
+            value: s.to_owned(),
+            feature,
+        })
     }
 }
 
@@ -64,6 +97,9 @@ impl GetAttribute for AnyVariable {
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#number-variables)
 
 #[derive(Debug, Eq, Copy, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum StandardVariable {
     Ordinary(Variable),
     Number(NumberVariable),
@@ -90,12 +126,17 @@ impl IsIndependent for StandardVariable {
 impl GetAttribute for StandardVariable {
     fn get_attr(s: &str, features: &Features) -> Result<Self, UnknownAttributeValue> {
         use self::StandardVariable::*;
-        if let Ok(v) = Variable::get_attr(s, features) {
-            return Ok(Ordinary(v));
-        } else if let Ok(v) = NumberVariable::get_attr(s, features) {
-            return Ok(Number(v));
+        let ordinary = match Variable::get_attr(s, features) {
+            Ok(v) => return Ok(Ordinary(v)),
+            Err(e) => e,
+        };
+        match NumberVariable::get_attr(s, features) {
+            Ok(v) => Ok(Number(v)),
+            Err(e) => Err(UnknownAttributeValue {
+                value: s.to_owned(),
+                feature: ordinary.feature.or(e.feature),
+            }),
         }
-        Err(UnknownAttributeValue::new(s))
     }
 }
 
@@ -120,7 +161,11 @@ impl IsIndependent for Variable {
     }
 }
 
-#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 #[non_exhaustive]
 pub enum Variable {
@@ -159,6 +204,7 @@ pub enum Variable {
     Dimensions,
     /// Digital Object Identifier (e.g. “10.1128/AEM.02591-07”)
     #[strum(serialize = "DOI", serialize = "doi")]
+    #[cfg_attr(feature = "serde", serde(rename = "DOI"))]
     DOI,
     /// name of the related event (e.g. the conference name when citing a conference paper)
     Event,
@@ -169,9 +215,11 @@ pub enum Variable {
     Genre,
     /// International Standard Book Number
     #[strum(serialize = "ISBN", serialize = "isbn")]
+    #[cfg_attr(feature = "serde", serde(rename = "ISBN"))]
     ISBN,
     /// International Standard Serial Number
     #[strum(serialize = "ISSN", serialize = "issn")]
+    #[cfg_attr(feature = "serde", serde(rename = "ISSN"))]
     ISSN,
     /// geographic scope of relevance (e.g. “US” for a US patent)
     Jurisdiction,
@@ -189,9 +237,11 @@ pub enum Variable {
     OriginalTitle,
     /// PubMed Central reference number
     #[strum(serialize = "PMCID", serialize = "pmcid")]
+    #[cfg_attr(feature = "serde", serde(rename = "PMCID"))]
     PMCID,
     /// PubMed reference number
     #[strum(serialize = "PMID", serialize = "pmid")]
+    #[cfg_attr(feature = "serde", serde(rename = "PMID"))]
     PMID,
     /// publisher
     Publisher,
@@ -217,6 +267,7 @@ pub enum Variable {
     TitleShort,
     ///  URL (e.g. “https://aem.asm.org/cgi/content/full/74/9/2766”)
     #[strum(serialize = "URL", serialize = "url")]
+    #[cfg_attr(feature = "serde", serde(rename = "URL"))]
     URL,
     /// version of the item (e.g. “2.0.9” for a software program)
     Version,
@@ -302,7 +353,11 @@ impl IsIndependent for NumberVariable {
     }
 }
 
-#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 #[non_exhaustive]
 pub enum NumberVariable {
@@ -366,8 +421,10 @@ impl NumberVariable {
 }
 
 #[derive(
-    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd,
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
 )]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 #[non_exhaustive]
 pub enum NameVariable {
@@ -438,7 +495,11 @@ impl Default for NameVariable {
     }
 }
 
-#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[strum(serialize_all = "kebab_case")]
 #[non_exhaustive]
 pub enum DateVariable {
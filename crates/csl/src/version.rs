@@ -213,8 +213,6 @@ declare_features!(
     (placeholder, multilingual, "1.0.1", None, None),
     (placeholder, hereinafter, "1.0.1", None, None),
     (placeholder, date_form_imperial, "1.0.1", None, None),
-    // (currently includes the dodgy macro label-form="..." business)
-    (placeholder, multiple_locators, "1.0.1", None, None),
     (placeholder, locator_extras, "1.0.1", None, None),
     (placeholder, leading_noise_words, "1.0.1", None, None),
     (placeholder, name_as_reverse_order, "1.0.1", None, None),
@@ -223,7 +221,6 @@ declare_features!(
     (placeholder, suppress_min_max, "1.0.1", None, None),
     (placeholder, text_case_normal, "1.0.1", None, None),
     (placeholder, year_range_format, "1.0.1", None, None),
-    (placeholder, jurisdictions, "1.0.1", None, None),
     // E.g. page and page-first become numeric variables
     (placeholder, more_numerics, "1.0.1", None, None),
     (placeholder, var_license, "1.0.1", None, None),
@@ -245,6 +242,9 @@ declare_features!(
     (active, conditions, "1.0.1", None, None),
     /// includes condition matchers `has-day="issued [date vars...]"`/`has-year-only="issued"`/`has-month-or-season="issued"`
     (active, condition_date_parts, "1.0.1", None, None),
+    /// includes condition matchers `jurisdiction="us"`, which also matches sub-jurisdictions like
+    /// `us:ca`, and `subjurisdictions="1"`
+    (active, jurisdictions, "1.0.1", None, None),
     /// `issued: "1981-09"`; `issued: "198X"` etc. Also via `"issued": { "edtf": "..." }`.
     (active, edtf_dates, "1.1", None, None),
     /// includes types: gazette, hearing, regulation
@@ -266,6 +266,9 @@ declare_features!(
     (active, legal_locators, "1.0.1", None, None),
     /// `<text term="unpublished">`
     (active, term_unpublished, "1.0.1", None, None),
+    /// Renders every locator in a cite's `locators` array, e.g. `pp. 3, 5; chap. 2`, instead of
+    /// only the first one.
+    (active, multiple_locators, "1.0.1", None, None),
 );
 
 // status, name, first added version, tracking issue, edition, None
@@ -10,7 +10,7 @@ use super::xml::{LocaleDatabase, StyleDatabase};
 use std::sync::Arc;
 
 use citeproc_io::output::markup::Markup;
-use citeproc_io::{Cite, ClusterMode, Reference};
+use citeproc_io::{Cite, ClusterMode, LangPrefs, Reference};
 use csl::Atom;
 
 use indexmap::set::IndexSet;
@@ -19,8 +19,13 @@ use indexmap::set::IndexSet;
 pub trait CiteDatabase: LocaleDatabase + StyleDatabase {
     #[salsa::input]
     fn reference_input(&self, key: Atom) -> Arc<Reference>;
+    /// Applies `lang_prefs` to the reference input
     fn reference(&self, key: Atom) -> Option<Arc<Reference>>;
 
+    /// How to render multilingual (Juris-M `multi`) variants of reference fields
+    #[salsa::input]
+    fn lang_prefs(&self) -> Arc<LangPrefs>;
+
     #[salsa::input]
     fn all_keys(&self) -> Arc<IndexSet<Atom>>;
 
@@ -37,6 +42,10 @@ pub trait CiteDatabase: LocaleDatabase + StyleDatabase {
     #[salsa::input]
     fn cluster_mode(&self, key: ClusterId) -> Option<ClusterMode>;
 
+    /// Whether to strip the cluster's terminal punctuation, for a host that puts it mid-sentence
+    #[salsa::input]
+    fn cluster_suppress_trailing_punctuation(&self, key: ClusterId) -> bool;
+
     #[salsa::input]
     fn cluster_cites(&self, key: ClusterId) -> Arc<Vec<CiteId>>;
 
@@ -50,6 +59,9 @@ pub trait CiteDatabase: LocaleDatabase + StyleDatabase {
     /// Filters out keys not in the library
     fn cited_keys(&self) -> Arc<IndexSet<Atom>>;
 
+    /// The opposite of `cited_keys`: keys cited in the document that are not in the library
+    fn missing_keys(&self) -> Arc<IndexSet<Atom>>;
+
     /// Equal to `all.intersection(cited U uncited)`
     /// Also represents "the refs that will be in the bibliography if we generate one"
     fn disamb_participants(&self) -> Arc<IndexSet<Atom>>;
@@ -113,11 +125,20 @@ pub struct ClusterData {
 }
 
 fn reference(db: &dyn CiteDatabase, key: Atom) -> Option<Arc<Reference>> {
-    if db.all_keys().contains(&key) {
-        Some(db.reference_input(key))
-    } else {
-        None
+    if !db.all_keys().contains(&key) {
+        return None;
     }
+    let input = db.reference_input(key);
+    if input.multi.keys.is_empty() {
+        return Some(input);
+    }
+    let prefs = db.lang_prefs();
+    if *prefs == LangPrefs::default() {
+        return Some(input);
+    }
+    let mut refr = Reference::clone(&input);
+    refr.apply_lang_prefs(&prefs);
+    Some(Arc::new(refr))
 }
 
 /// Type to represent which references should appear in a bibiliography even if they are not cited
@@ -152,6 +173,19 @@ fn cited_keys(db: &dyn CiteDatabase) -> Arc<IndexSet<Atom>> {
     Arc::new(keys)
 }
 
+fn missing_keys(db: &dyn CiteDatabase) -> Arc<IndexSet<Atom>> {
+    let all = db.all_keys();
+    let mut keys = IndexSet::new();
+    let all_cite_ids = db.all_cite_ids();
+    for &id in all_cite_ids.iter() {
+        let ref_id = &id.lookup(db).ref_id;
+        if !all.contains(ref_id) {
+            keys.insert(ref_id.clone());
+        }
+    }
+    Arc::new(keys)
+}
+
 fn disamb_participants(db: &dyn CiteDatabase) -> Arc<IndexSet<Atom>> {
     let cited = db.cited_keys();
     let all = db.all_keys();
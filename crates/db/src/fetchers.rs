@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! Ready-made [LocaleFetcher] implementations, for the common cases of a checkout of the
+//! [CSL locales repository](https://github.com/citation-style-language/locales) on disk, or a zip
+//! of it compiled into the binary.
+
+use crate::xml::{LocaleFetchError, LocaleFetcher};
+use csl::Lang;
+use std::path::PathBuf;
+use std::{fs, io};
+
+/// The file name the CSL locales repository uses for a given language, e.g. `locales-en-US.xml`.
+fn locale_file_name(lang: &Lang) -> String {
+    format!("locales-{}.xml", lang)
+}
+
+/// Reads `locales-xx-XX.xml` files from a directory. A missing file is not an error; the
+/// processor will fall back to another locale.
+#[derive(Debug, Clone)]
+pub struct DirLocaleFetcher {
+    root: PathBuf,
+}
+
+impl DirLocaleFetcher {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DirLocaleFetcher { root: dir.into() }
+    }
+}
+
+impl LocaleFetcher for DirLocaleFetcher {
+    fn fetch_string(&self, lang: &Lang) -> Result<Option<String>, LocaleFetchError> {
+        let path = self.root.join(locale_file_name(lang));
+        match fs::read_to_string(path) {
+            Ok(string) => Ok(Some(string)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(LocaleFetchError::Io(e)),
+        }
+    }
+}
+
+#[cfg(feature = "bundled-locales")]
+pub use self::bundled::BundledLocaleFetcher;
+
+#[cfg(feature = "bundled-locales")]
+mod bundled {
+    use super::locale_file_name;
+    use crate::xml::{LocaleFetchError, LocaleFetcher};
+    use csl::Lang;
+    use std::collections::HashMap;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    impl From<zip::result::ZipError> for LocaleFetchError {
+        fn from(err: zip::result::ZipError) -> LocaleFetchError {
+            LocaleFetchError::Other(err.to_string())
+        }
+    }
+
+    /// Reads locales out of a zip archive embedded in the binary, typically with
+    /// `include_bytes!`. The archive can be a plain zip of the locale files, or a GitHub download
+    /// of the locales repository, where they are all inside a `locales-master/` directory.
+    ///
+    /// Locales are only decompressed when the processor asks for them.
+    #[derive(Debug, Clone)]
+    pub struct BundledLocaleFetcher {
+        zip: &'static [u8],
+        /// File name => full path inside the archive
+        entries: HashMap<String, String>,
+    }
+
+    impl BundledLocaleFetcher {
+        pub fn new(zip: &'static [u8]) -> Result<Self, LocaleFetchError> {
+            let archive = ZipArchive::new(Cursor::new(zip))?;
+            let entries = archive
+                .file_names()
+                .filter_map(|path| {
+                    let file_name = path.rsplit('/').next()?;
+                    if file_name.starts_with("locales-") && file_name.ends_with(".xml") {
+                        Some((file_name.to_owned(), path.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            Ok(BundledLocaleFetcher { zip, entries })
+        }
+    }
+
+    impl LocaleFetcher for BundledLocaleFetcher {
+        fn fetch_string(&self, lang: &Lang) -> Result<Option<String>, LocaleFetchError> {
+            let path = match self.entries.get(&locale_file_name(lang)) {
+                Some(path) => path,
+                None => return Ok(None),
+            };
+            let mut archive = ZipArchive::new(Cursor::new(self.zip))?;
+            let mut file = archive.by_name(path)?;
+            let mut string = String::new();
+            file.read_to_string(&mut string)?;
+            Ok(Some(string))
+        }
+    }
+}
+
+#[test]
+fn test_dir_locale_fetcher() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("locales-fr-FR.xml"), "<locale />").unwrap();
+    let fetcher = DirLocaleFetcher::new(dir.path());
+    let fr_fr = Lang::Iso(csl::IsoLang::French, Some(csl::IsoCountry::FR));
+    assert_eq!(
+        fetcher.fetch_string(&fr_fr).unwrap().as_deref(),
+        Some("<locale />")
+    );
+    assert_eq!(fetcher.fetch_string(&Lang::en_us()).unwrap(), None);
+}
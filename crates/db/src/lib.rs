@@ -4,10 +4,12 @@ extern crate log;
 mod cite;
 mod xml;
 mod cluster;
+mod fetchers;
 
 pub use cite::*;
 pub use xml::*;
 pub use cluster::*;
+pub use fetchers::*;
 
 use salsa::Durability;
 
@@ -16,6 +18,7 @@ pub fn safe_default(db: &mut (impl cite::CiteDatabase + xml::LocaleDatabase + xm
     db.set_style_with_durability(Default::default(), Durability::HIGH);
     db.set_all_keys_with_durability(Default::default(), Durability::MEDIUM);
     db.set_all_uncited(Default::default());
+    db.set_lang_prefs_with_durability(Default::default(), Durability::MEDIUM);
     db.set_cluster_ids(Arc::new(Default::default()));
     db.set_locale_input_langs_with_durability(Default::default(), Durability::HIGH);
     db.set_default_lang_override_with_durability(Default::default(), Durability::HIGH);
@@ -7,6 +7,7 @@
 use std::io;
 use std::sync::Arc;
 
+use citeproc_io::output::LocalizedQuotes;
 use csl::{
     locale::{Lang, Locale, LocaleSource, EN_US},
     style::{Name, Style, TextElement, TextSource},
@@ -16,6 +17,9 @@ use fnv::FnvHashSet;
 
 pub trait HasFetcher {
     fn get_fetcher(&self) -> Arc<dyn LocaleFetcher>;
+    /// Called each time a fetched locale file is parsed, so the processor can count them. Does
+    /// nothing by default.
+    fn locale_file_parsed(&self) {}
 }
 
 /// Salsa interface to a CSL style.
@@ -186,8 +190,17 @@ pub trait LocaleDatabase: StyleDatabase + HasFetcher {
     #[salsa::transparent]
     fn merged_locale(&self, key: Lang) -> Arc<Locale>;
 
+    /// Like `merged_locale`, but without the en-US locale file every chain ends with, to find out
+    /// what a locale leaves to the en-US fallback. For en-US itself this is the same as
+    /// `merged_locale`.
+    fn requested_locale(&self, key: Lang) -> Arc<Locale>;
+
     fn default_locale(&self) -> Arc<Locale>;
 
+    /// The quotation marks from `default_locale`, so they are only looked up once per locale
+    /// rather than for every quoted element.
+    fn default_quotes(&self) -> Arc<LocalizedQuotes>;
+
     #[salsa::transparent]
     fn default_lang(&self) -> Lang;
 }
@@ -205,6 +218,10 @@ fn default_locale(db: &dyn LocaleDatabase) -> Arc<Locale> {
     db.merged_locale(db.default_lang())
 }
 
+fn default_quotes(db: &dyn LocaleDatabase) -> Arc<LocalizedQuotes> {
+    Arc::new(LocalizedQuotes::from_locale(&db.default_locale()))
+}
+
 fn locale_xml(db: &dyn LocaleDatabase, key: Lang) -> Option<Arc<String>> {
     let stored = db.locale_input_langs();
     if stored.contains(&key) {
@@ -228,13 +245,24 @@ fn inline_locale(db: &dyn LocaleDatabase, key: Option<Lang>) -> Option<Arc<Local
 fn parsed_locale(db: &dyn LocaleDatabase, key: LocaleSource) -> Option<Arc<Locale>> {
     match key {
         LocaleSource::File(ref lang) => {
-            let string = db.locale_xml(lang.clone());
+            // Every locale falls back to en-US, so if the fetcher doesn't have it, use the copy
+            // bundled with the processor rather than losing the terms.
+            let string = db.locale_xml(lang.clone()).or_else(|| {
+                if *lang == Lang::en_us() {
+                    Some(Arc::new(EN_US.to_owned()))
+                } else {
+                    None
+                }
+            });
             string
-                .and_then(|s| match Locale::parse(&s) {
-                    Ok(l) => Some(l),
-                    Err(e) => {
-                        error!("failed to parse locale for lang {}: {:?}", lang, e);
-                        None
+                .and_then(|s| {
+                    db.locale_file_parsed();
+                    match Locale::parse(&s) {
+                        Ok(l) => Some(l),
+                        Err(e) => {
+                            error!("failed to parse locale for lang {}: {:?}", lang, e);
+                            None
+                        }
                     }
                 })
                 .map(Arc::new)
@@ -245,27 +273,43 @@ fn parsed_locale(db: &dyn LocaleDatabase, key: LocaleSource) -> Option<Arc<Local
 
 fn merged_locale(db: &dyn LocaleDatabase, key: Lang) -> Arc<Locale> {
     debug!("requested locale {:?}", key);
-    let locales = key
-        .iter()
+    merge_locales(db, key.clone(), key.iter())
+}
+
+fn requested_locale(db: &dyn LocaleDatabase, key: Lang) -> Arc<Locale> {
+    if key == Lang::en_us() {
+        return db.merged_locale(key);
+    }
+    let en_us = LocaleSource::File(Lang::en_us());
+    merge_locales(db, key.clone(), key.iter().filter(|src| *src != en_us))
+}
+
+/// The result is labelled with `key`, whichever of its sources actually had an `xml:lang`.
+fn merge_locales(
+    db: &dyn LocaleDatabase,
+    key: Lang,
+    sources: impl Iterator<Item = LocaleSource>,
+) -> Arc<Locale> {
+    let locales = sources
         .filter_map(|src| db.parsed_locale(src))
         .collect::<Vec<_>>();
-    Arc::new(
-        locales
-            .into_iter()
-            .rev()
-            .fold(None, |mut acc, l| match acc {
-                None => Some((*l).clone()),
-                Some(ref mut base) => {
-                    debug!("merging locales: {:?} <- {:?}", base.lang, l.lang);
-                    base.merge(&l);
-                    acc
-                }
-            })
-            .unwrap_or_else(|| {
-                warn!("Using default, empty locale");
-                Locale::default()
-            }),
-    )
+    let mut merged = locales
+        .into_iter()
+        .rev()
+        .fold(None, |mut acc, l| match acc {
+            None => Some((*l).clone()),
+            Some(ref mut base) => {
+                debug!("merging locales: {:?} <- {:?}", base.lang, l.lang);
+                base.merge(&l);
+                acc
+            }
+        })
+        .unwrap_or_else(|| {
+            warn!("Using default, empty locale");
+            Locale::default()
+        });
+    merged.lang = Some(key);
+    Arc::new(merged)
 }
 
 cfg_if::cfg_if! {
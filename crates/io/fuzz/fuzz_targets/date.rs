@@ -0,0 +1,18 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+#![no_main]
+use citeproc_io::DateOrRange;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = input.parse::<DateOrRange>();
+    let literal = DateOrRange::Literal {
+        literal: input.into(),
+        circa: false,
+    };
+    let _ = literal.structured();
+});
@@ -0,0 +1,16 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+#![no_main]
+use citeproc_io::Name;
+use libfuzzer_sys::fuzz_target;
+
+// CSL-JSON names, e.g. {"family": "van Gogh", "given": "Vincent"}
+fuzz_target!(|data: &[u8]| {
+    if let Ok(Name::Person(mut pn)) = serde_json::from_slice::<Name>(data) {
+        pn.normalize_initials();
+    }
+});
@@ -0,0 +1,17 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+#![no_main]
+use citeproc_io::{NumberLike, NumericValue, NumericValueOwned};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let like = NumberLike::Str(input.into());
+    let value = NumericValue::from_localized("and")(&like);
+    let _ = value.page_first();
+    let _ = value.is_multiple(csl::NumberVariable::Page);
+    let _ = NumericValueOwned::from(value).to_plain();
+});
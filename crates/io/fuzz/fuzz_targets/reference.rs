@@ -0,0 +1,14 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+#![no_main]
+use citeproc_io::Reference;
+use libfuzzer_sys::fuzz_target;
+
+// A whole CSL-JSON reference, as a server would receive it.
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Reference>(data);
+});
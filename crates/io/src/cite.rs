@@ -4,12 +4,15 @@
 //
 // Copyright © 2018 Corporation for Digital Scholarship
 
-use super::output::{markup::Markup, OutputFormat};
+use super::output::{
+    markup::{Markup, MarkupInput},
+    OutputFormat,
+};
 use crate::NumberLike;
-use crate::String;
 use csl::Atom;
 use csl::LocatorType;
 use serde::de::{Deserialize, Deserializer};
+use serde::Serialize;
 
 /// Represents one cite in someone's document, to exactly one reference.
 ///
@@ -41,24 +44,29 @@ use serde::de::{Deserialize, Deserializer};
 ///     basic_mode("smith", CiteMode::AuthorOnly),
 /// ])
 /// ```
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", bound(deserialize = ""))]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", bound(serialize = "", deserialize = ""))]
 pub struct Cite<O: OutputFormat> {
     #[serde(rename = "id", deserialize_with = "get_ref_id")]
     pub ref_id: Atom,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub prefix: Option<O::Input>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub suffix: Option<O::Input>,
 
-    /// Multiple locator functionality needs CSL support, so it is disabled via using
-    /// `Locators::single_locator` for now.
-    #[serde(default, flatten, deserialize_with = "Locators::single_locator")]
+    /// Either a single locator or several. Styles only render more than the first one when they
+    /// enable the `multiple-locators` feature.
+    #[serde(
+        default,
+        flatten,
+        deserialize_with = "Locators::get_locators",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub locators: Option<Locators>,
 
-    #[serde(default, flatten)]
+    #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
     pub mode: Option<CiteMode>,
 }
 
@@ -96,12 +104,12 @@ pub struct CiteCompat {
     pub ref_id: Atom,
 
     #[serde(default)]
-    pub prefix: Option<String>,
+    pub prefix: Option<MarkupInput>,
 
     #[serde(default)]
-    pub suffix: Option<String>,
+    pub suffix: Option<MarkupInput>,
 
-    #[serde(default, flatten, deserialize_with = "Locators::single_locator")]
+    #[serde(default, flatten, deserialize_with = "Locators::get_locators")]
     pub locators: Option<Locators>,
 
     #[serde(default, flatten, deserialize_with = "CiteMode::compat")]
@@ -122,13 +130,91 @@ pub mod cite_compat_vec {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize)]
+/// A locator and its label. When the label is left out, a label abbreviation at the start of the
+/// locator is read as one, so a stored `{ "locator": "pp. 12-14" }` means the same as
+/// `{ "locator": "12-14", "label": "page" }`. Without either, the label is `page`.
+///
+/// ```
+/// use citeproc_io::{Locator, NumberLike};
+/// use csl::LocatorType;
+/// let locator: Locator = serde_json::from_str(r#"{ "locator": "ch. 3" }"#).unwrap();
+/// assert_eq!(locator.type_of(), LocatorType::Chapter);
+/// assert_eq!(locator.value(), &NumberLike::Str("3".into()));
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
 pub struct Locator {
     pub locator: NumberLike,
-    #[serde(default, rename = "label")]
+    #[serde(rename = "label")]
     pub loc_type: LocatorType,
 }
 
+impl<'de> Deserialize<'de> for Locator {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Helper {
+            locator: NumberLike,
+            #[serde(default)]
+            label: Option<LocatorType>,
+        }
+        let Helper { locator, label } = Helper::deserialize(d)?;
+        if let Some(loc_type) = label {
+            return Ok(Locator { locator, loc_type });
+        }
+        if let NumberLike::Str(string) = &locator {
+            if let Some((loc_type, rest)) = split_label(string) {
+                return Ok(Locator {
+                    locator: NumberLike::Str(rest.to_owned()),
+                    loc_type,
+                });
+            }
+        }
+        Ok(Locator {
+            locator,
+            loc_type: LocatorType::default(),
+        })
+    }
+}
+
+/// Splits a leading label like `pp.`, `chap.` or `§` off a locator string. These are the en-US
+/// short forms (and the odd common variant); anything else is left for the style to render as is.
+fn split_label(locator: &str) -> Option<(LocatorType, &str)> {
+    let trimmed = locator.trim_start();
+    let (label, rest) = match trimmed.chars().next()? {
+        c @ '§' | c @ '¶' => {
+            let rest = trimmed.trim_start_matches(c);
+            trimmed.split_at(trimmed.len() - rest.len())
+        }
+        _ => trimmed.split_at(trimmed.find(char::is_whitespace)?),
+    };
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    let loc_type = match label.to_lowercase().as_str() {
+        "bk." | "bks." => LocatorType::Book,
+        "ch." | "chap." | "chaps." => LocatorType::Chapter,
+        "col." | "cols." => LocatorType::Column,
+        "fig." | "figs." => LocatorType::Figure,
+        "fol." | "fols." => LocatorType::Folio,
+        "no." | "nos." => LocatorType::Issue,
+        "l." | "ll." => LocatorType::Line,
+        "n." | "nn." => LocatorType::Note,
+        "op." | "opp." => LocatorType::Opus,
+        "p." | "pp." => LocatorType::Page,
+        "para." | "paras." | "¶" | "¶¶" => LocatorType::Paragraph,
+        "pt." | "pts." => LocatorType::Part,
+        "sec." | "secs." | "§" | "§§" => LocatorType::Section,
+        "s.v." | "s.vv." => LocatorType::SubVerbo,
+        "v." | "vv." => LocatorType::Verse,
+        "vol." | "vols." => LocatorType::Volume,
+        _ => return None,
+    };
+    Some((loc_type, rest))
+}
+
 impl Locator {
     pub fn type_of(&self) -> LocatorType {
         self.loc_type
@@ -149,8 +235,8 @@ where
 
 /// Accepts either
 /// `{ "locator": "54", "label": "page" }` or
-/// `{ "locators": [["chapter", "19"], ["page", "581"]] }`.
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+/// `{ "locators": [{ "locator": "19", "label": "chapter" }, { "locator": "581" }] }`.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(untagged)]
 pub enum Locators {
     Single(Locator),
@@ -164,6 +250,13 @@ impl Locators {
             Locators::Multiple { locators } => locators.get(0),
         }
     }
+    /// All of the locators, in order.
+    pub fn as_slice(&self) -> &[Locator] {
+        match self {
+            Locators::Single(l) => std::slice::from_ref(l),
+            Locators::Multiple { locators } => locators,
+        }
+    }
     fn into_option(self) -> Option<Self> {
         match self {
             Locators::Multiple { locators } => {
@@ -180,16 +273,6 @@ impl Locators {
         }
     }
 
-    /// Only accepts `"locator": "abc", "label": "page|etc"`, no arrays.
-    ///
-    /// Locates singles in your area
-    fn single_locator<'de, D>(d: D) -> Result<Option<Locators>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        Ok(Option::<Locator>::deserialize(d)?.map(Locators::Single))
-    }
-
     /// Single length locators arrays => Some(Locators::Single)
     /// Zero length => None
     fn get_locators<'de, D>(d: D) -> Result<Option<Locators>, D::Error>
@@ -200,7 +283,7 @@ impl Locators {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 #[serde(tag = "mode")]
 pub enum CiteMode {
     AuthorOnly,
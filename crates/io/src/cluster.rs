@@ -39,7 +39,8 @@ pub enum ClusterMode {
     #[serde(rename_all = "camelCase")]
     SuppressAuthor {
         /// Suppress authors in the first `n` cites in the cluster, or if cite grouping is enabled,
-        /// the first `n` same-author groups. The default value is 1. If this is zero, then all
+        /// the first `n` same-author groups. A run collapsed by `collapse="year-suffix"` or
+        /// `collapse="year"` is one group. The default value is 1. If this is zero, then all
         /// cites have their authors suppressed.
         ///
         /// ```ignore
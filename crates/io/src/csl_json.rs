@@ -13,6 +13,7 @@ mod cow_str;
 use crate::names::Name;
 use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::de::{Error, IgnoredAny};
+use serde::Serialize;
 use std::borrow::Cow;
 use std::fmt;
 use std::str::FromStr;
@@ -29,7 +30,7 @@ use csl::GetAttribute;
 use csl::Lang;
 
 use super::date::{Date, DateOrRange};
-use super::reference::Reference;
+use super::reference::{Multilingual, Reference};
 use fnv::FnvHashMap;
 use std::marker::PhantomData;
 
@@ -46,6 +47,9 @@ impl<'de> Visitor<'de> for LanguageVisitor {
     where
         E: de::Error,
     {
+        if let Some(lang) = Lang::parse_bcp47(key) {
+            return Ok(lang);
+        }
         match Lang::parse(key) {
             Ok(lang) => Ok(lang),
             Err((_garbage, Some(half_parsed))) => Ok(half_parsed),
@@ -95,12 +99,13 @@ enum Field<'a> {
     Id,
     Type,
     Language,
+    Multi,
     // don't use plain `&'a str`, because that would fail when parsing from a serde::Value.
     #[serde(borrow, deserialize_with = "cow_str::deserialize_cow_str")]
     Any(Cow<'a, str>),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Hash)]
 #[serde(untagged)]
 pub enum NumberLike {
     Str(String),
@@ -141,21 +146,38 @@ impl CircaValue {
     }
 }
 
-struct WrapType(CslType);
+/// Unknown types are not an error; Zotero and friends add new ones faster than CSL does.
+enum WrapType {
+    Known(CslType),
+    Unknown(String),
+}
 
 impl<'de> Deserialize<'de> for WrapType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        const FIELDS: &[&str] = &["a legal CSL type"];
-        deserializer
-            .deserialize_identifier(CslVariantVisitor(
-                Features::new(),
-                FIELDS,
-                Default::default(),
-            ))
-            .map(WrapType)
+        struct TypeVisitor;
+
+        impl<'de> Visitor<'de> for TypeVisitor {
+            type Value = WrapType;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a CSL type")
+            }
+
+            fn visit_str<E>(self, key: &str) -> Result<WrapType, E>
+            where
+                E: de::Error,
+            {
+                Ok(match CslType::get_attr(key, &Features::new()) {
+                    Ok(csl_type) => WrapType::Known(csl_type),
+                    Err(_) => WrapType::Unknown(key.to_owned()),
+                })
+            }
+        }
+
+        deserializer.deserialize_identifier(TypeVisitor)
     }
 }
 
@@ -203,6 +225,7 @@ impl<'de> Deserialize<'de> for Reference {
                 let mut number = FnvHashMap::default();
                 let mut name = FnvHashMap::default();
                 let mut date = FnvHashMap::default();
+                let mut multi = Multilingual::default();
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Id => {
@@ -215,6 +238,10 @@ impl<'de> Deserialize<'de> for Reference {
                             let wrap: WrapLang = map.next_value()?;
                             language = wrap.0;
                         }
+                        Field::Multi => {
+                            let block: MultiBlock = map.next_value()?;
+                            multi = block.into_multilingual();
+                        }
                         Field::Any(var_name) => {
                             match AnyVariable::get_attr(&var_name, &Features::default()) {
                                 Err(_unknown) => {
@@ -241,16 +268,27 @@ impl<'de> Deserialize<'de> for Reference {
                         }
                     }
                 }
+                let id = id
+                    .map(|i| csl::Atom::from(i.into_string()))
+                    .ok_or_else(|| de::Error::missing_field("id"))?;
+                let (csl_type, original_type) = match csl_type {
+                    Some(WrapType::Known(csl_type)) => (csl_type, None),
+                    Some(WrapType::Unknown(original)) => {
+                        log::warn!("reference `{}` had unknown type `{}`", id, original);
+                        (CslType::Article, Some(original))
+                    }
+                    None => (CslType::Article, None),
+                };
                 Ok(Reference {
-                    id: id
-                        .map(|i| csl::Atom::from(i.into_string()))
-                        .ok_or_else(|| de::Error::missing_field("id"))?,
-                    csl_type: csl_type.unwrap_or(WrapType(CslType::Article)).0,
+                    id,
+                    csl_type,
+                    original_type,
                     language,
                     ordinary,
                     number,
                     name,
                     date,
+                    multi,
                 })
             }
         }
@@ -260,6 +298,47 @@ impl<'de> Deserialize<'de> for Reference {
     }
 }
 
+/// The Juris-M `multi` block. Only ordinary variables are retained. Name variants are not
+/// supported: the `multi` blocks Juris-M puts on each name are never parsed, and any name variable
+/// listed here is dropped with a warning.
+#[derive(Deserialize, Default)]
+struct MultiBlock {
+    #[serde(default)]
+    main: FnvHashMap<String, String>,
+    #[serde(default, rename = "_keys")]
+    keys: FnvHashMap<String, FnvHashMap<String, String>>,
+}
+
+impl MultiBlock {
+    fn into_multilingual(self) -> Multilingual {
+        fn ordinary(var_name: &str) -> Option<csl::Variable> {
+            match AnyVariable::get_attr(var_name, &Features::default()) {
+                Ok(AnyVariable::Ordinary(v)) => Some(v),
+                _ => {
+                    log::warn!("ignoring multilingual variants for `{}`", var_name);
+                    None
+                }
+            }
+        }
+        let mut multi = Multilingual::default();
+        for (var_name, lang) in self.main {
+            if let Some(var) = ordinary(&var_name) {
+                multi.main.insert(var, lang.into());
+            }
+        }
+        for (var_name, variants) in self.keys {
+            if let Some(var) = ordinary(&var_name) {
+                let variants = variants
+                    .into_iter()
+                    .map(|(lang, value)| (lang.into(), value))
+                    .collect();
+                multi.keys.insert(var, variants);
+            }
+        }
+        multi
+    }
+}
+
 // newtype these so we can have a different implementation
 struct DateParts(Option<DateOrRange>);
 
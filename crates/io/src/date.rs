@@ -5,6 +5,7 @@
 // Copyright © 2018 Corporation for Digital Scholarship
 
 use crate::String;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
 /// TODO: parse 2018-3-17 as if it were '03'
@@ -145,6 +146,24 @@ impl DateOrRange {
             DateOrRange::Literal { circa, .. } => circa,
         }
     }
+    /// The date with a literal read as a structured one where possible, for looking at which parts
+    /// it has. A literal `"Spring 2003"` has a season just like `[[2003, 13]]` does. Returns None
+    /// for a literal that isn't a date.
+    pub fn structured(&self) -> Option<Cow<'_, DateOrRange>> {
+        match self {
+            DateOrRange::Literal { literal, circa } => {
+                Self::parse_whole(literal).map(|parsed| Cow::Owned(parsed.with_circa(*circa)))
+            }
+            _ => Some(Cow::Borrowed(self)),
+        }
+    }
+    /// Only succeeds if all of `s` is a date, either ISO 8601 or written out.
+    fn parse_whole(s: &str) -> Option<Self> {
+        match range(s.as_bytes()) {
+            Ok((rest, parsed)) if rest.is_empty() => Some(parsed),
+            _ => textual_range(s),
+        }
+    }
     pub fn single(&self) -> Option<Date> {
         if let DateOrRange::Single(d) = self {
             Some(*d)
@@ -185,10 +204,14 @@ impl From<(Date, Date)> for DateOrRange {
     }
 }
 
+/// Parses raw dates. ISO 8601 is preferred, then dates written out like `12 May 2003`, and
+/// failing that, whatever ISO 8601 date the string starts with.
 impl FromStr for DateOrRange {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok((_left_overs, parsed)) = range(s.as_bytes()) {
+        if let Some(parsed) = Self::parse_whole(s) {
+            Ok(parsed)
+        } else if let Ok((_left_overs, parsed)) = range(s.as_bytes()) {
             Ok(parsed)
         } else {
             Err(())
@@ -235,6 +258,53 @@ fn test_date_parsing() {
     );
 }
 
+#[cfg(test)]
+#[test]
+fn test_textual_date_parsing() {
+    assert_eq!(
+        DateOrRange::from_str("12 May 2003"),
+        Ok(DateOrRange::new(2003, 5, 12))
+    );
+    assert_eq!(
+        DateOrRange::from_str("Sept. 9, 2003"),
+        Ok(DateOrRange::new(2003, 9, 9))
+    );
+    assert_eq!(
+        DateOrRange::from_str("Spring 2003"),
+        Ok(DateOrRange::new(2003, 13, 0))
+    );
+    // Better than taking the ISO year and dropping the rest
+    assert_eq!(
+        DateOrRange::from_str("2003 fall"),
+        Ok(DateOrRange::new(2003, 15, 0))
+    );
+    assert_eq!(
+        DateOrRange::from_str("May–June 2003"),
+        Ok(DateOrRange::Range(
+            Date::new(2003, 5, 0),
+            Date::new(2003, 6, 0)
+        ))
+    );
+    // Seasons don't have days, and days need a month
+    assert_eq!(DateOrRange::from_str("3 Spring 2003"), Err(()));
+    assert_eq!(DateOrRange::from_str("3 2003"), Err(()));
+    assert_eq!(DateOrRange::from_str("Mayday 2003"), Err(()));
+
+    let literal = DateOrRange::Literal {
+        literal: "Winter 1998".into(),
+        circa: true,
+    };
+    assert_eq!(
+        literal.structured().as_deref(),
+        Some(&DateOrRange::Single(Date::new_circa(1998, 16, 0)))
+    );
+    let literal = DateOrRange::Literal {
+        literal: "1998 or so".into(),
+        circa: false,
+    };
+    assert_eq!(literal.structured(), None);
+}
+
 #[cfg(test)]
 #[test]
 fn test_range_parsing() {
@@ -474,6 +544,89 @@ fn and_ymd(inp: &[u8]) -> IResult<&[u8], Date> {
     Ok(ymd_date(rem1)?)
 }
 
+/// `May`, `sept`, `Spring`. Months can be abbreviated to three letters or more.
+fn month_or_season(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    const SEASONS: [(&str, u32); 5] = [
+        ("spring", 13),
+        ("summer", 14),
+        ("autumn", 15),
+        ("fall", 15),
+        ("winter", 16),
+    ];
+    let word = word.to_lowercase();
+    if word.len() >= 3 {
+        if let Some(ix) = MONTHS.iter().position(|m| m.starts_with(word.as_str())) {
+            return Some(ix as u32 + 1);
+        }
+    }
+    SEASONS
+        .iter()
+        .find(|(season, _)| *season == word)
+        .map(|(_, month)| *month)
+}
+
+/// A date written out in English, in any order, like `May 12, 2003`, `12 May 2003` or
+/// `Spring 2003`. Every word has to be understood.
+fn textual_date(s: &str) -> Option<Date> {
+    let (mut y, mut m, mut d) = (None, None, None);
+    let words = s
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|word| word.trim_end_matches('.'))
+        .filter(|word| !word.is_empty());
+    for word in words {
+        let repeated = if let Some(month) = month_or_season(word) {
+            m.replace(month).is_some()
+        } else if word.len() <= 2 && word.bytes().all(|b| b.is_ascii_digit()) {
+            d.replace(word.parse::<u32>().ok()?).is_some()
+        } else {
+            match year(word.as_bytes()) {
+                Ok((rest, parsed)) if rest.is_empty() => y.replace(parsed).is_some(),
+                _ => return None,
+            }
+        };
+        if repeated {
+            return None;
+        }
+    }
+    // Seasons don't have days, and a day needs a month to be in
+    if let Some(d) = d {
+        if d == 0 || d > 31 || !(1..=12).contains(&m.unwrap_or(0)) {
+            return None;
+        }
+    }
+    Some(Date::new(y?, m.unwrap_or(0), d.unwrap_or(0)))
+}
+
+/// One textual date, or two separated by `/` or an en dash. The first may leave out the year,
+/// as in `May–June 2003`.
+fn textual_range(s: &str) -> Option<DateOrRange> {
+    let mut sides = s.splitn(2, |c| c == '/' || c == '–');
+    let first = sides.next()?;
+    match sides.next() {
+        None => textual_date(first).map(DateOrRange::Single),
+        Some(second) => {
+            let to = textual_date(second)?;
+            let from =
+                textual_date(first).or_else(|| textual_date(&format!("{} {}", first, to.year)))?;
+            Some(DateOrRange::Range(from, to))
+        }
+    }
+}
+
 fn range(inp: &[u8]) -> IResult<&[u8], DateOrRange> {
     let (rem1, d1) = ymd_date(inp)?;
     let (rem2, d2o) = opt(and_ymd)(rem1)?;
@@ -485,3 +638,77 @@ fn range(inp: &[u8]) -> IResult<&[u8], DateOrRange> {
         },
     ))
 }
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const MONTHS: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+
+    /// Any date ISO 8601 can write, with the month and day left out sometimes
+    fn date() -> impl Strategy<Value = Date> {
+        (-9999i32..=9999, 0u32..=12, 0u32..=31).prop_map(|(year, month, day)| {
+            let day = if month == 0 { 0 } else { day };
+            Date::new(year, month, day)
+        })
+    }
+
+    fn iso(date: &Date) -> String {
+        let mut s = String::new();
+        if date.year < 0 {
+            s.push('-');
+        }
+        s.push_str(&format!("{:04}", date.year.abs()));
+        if date.has_month() {
+            s.push_str(&format!("-{:02}", date.month));
+        }
+        if date.has_day() {
+            s.push_str(&format!("-{:02}", date.day));
+        }
+        s
+    }
+
+    proptest! {
+        #[test]
+        fn iso_round_trip(date in date()) {
+            prop_assert_eq!(iso(&date).parse::<DateOrRange>(), Ok(DateOrRange::Single(date)));
+        }
+
+        #[test]
+        fn iso_range_round_trip(from in date(), to in date()) {
+            let raw = format!("{}/{}", iso(&from), iso(&to));
+            prop_assert_eq!(raw.parse::<DateOrRange>(), Ok(DateOrRange::Range(from, to)));
+        }
+
+        #[test]
+        fn textual_round_trip(year in 1000i32..=9999, month in 1u32..=12, day in 1u32..=28) {
+            let name = MONTHS[month as usize - 1];
+            let expected = Ok(DateOrRange::new(year, month, day));
+            let day_first = format!("{} {} {}", day, name, year);
+            let month_first = format!("{} {}, {}", name, day, year);
+            prop_assert_eq!(day_first.parse::<DateOrRange>(), expected.clone());
+            prop_assert_eq!(month_first.parse::<DateOrRange>(), expected);
+        }
+
+        #[test]
+        fn parse_anything(input in "\\PC{0,24}") {
+            let _ = input.parse::<DateOrRange>();
+            let literal = DateOrRange::Literal { literal: input.as_str().into(), circa: false };
+            let _ = literal.structured();
+        }
+    }
+}
@@ -296,6 +296,84 @@ impl From<PersonNameInput> for PersonName {
     }
 }
 
+impl PersonName {
+    /// Rewrites any initials in the given name as `J. R. R.`, however they were typed (`J.R.R.`,
+    /// `J. R. R.`, `JRR`), so that sorting and disambiguation see one name, and `initialize-with`
+    /// can put the style's own separator between them. Undotted capitals only count as initials
+    /// in runs of up to three; `JOHN` is a name. Names that aren't in Latin or Cyrillic script are
+    /// left alone.
+    pub fn normalize_initials(&mut self) {
+        let given = match &self.given {
+            Some(given) if self.is_latin_cyrillic => given,
+            _ => return,
+        };
+        let mut normalized = String::new();
+        for word in given.split_whitespace() {
+            if !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            match word_initials(word) {
+                Some(initials) => {
+                    for (i, initial) in initials.into_iter().enumerate() {
+                        if i > 0 {
+                            normalized.push(' ');
+                        }
+                        normalized.push(initial);
+                        normalized.push('.');
+                    }
+                }
+                None => normalized.push_str(word),
+            }
+        }
+        if normalized != *given {
+            self.given = Some(normalized);
+        }
+    }
+}
+
+/// `J.R.R.`, `J.R.R` and `JRR` => `['J', 'R', 'R']`
+fn word_initials(word: &str) -> Option<Vec<char>> {
+    let is_initial = |part: &str| {
+        let mut chars = part.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_uppercase() => Some(c),
+            _ => None,
+        }
+    };
+    if word.contains('.') {
+        word.split('.')
+            .filter(|part| !part.is_empty())
+            .map(is_initial)
+            .collect()
+    } else if word.chars().count() <= 3 && word.chars().all(char::is_uppercase) {
+        Some(word.chars().collect())
+    } else {
+        None
+    }
+}
+
+#[test]
+fn normalize_initials() {
+    fn normalize(given: &str) -> Option<String> {
+        let mut pn = PersonName {
+            given: Some(given.into()),
+            is_latin_cyrillic: is_latin_cyrillic(given),
+            ..Default::default()
+        };
+        pn.normalize_initials();
+        pn.given
+    }
+    assert_eq!(normalize("J.R.R.").as_deref(), Some("J. R. R."));
+    assert_eq!(normalize("J. R. R.").as_deref(), Some("J. R. R."));
+    assert_eq!(normalize("JRR").as_deref(), Some("J. R. R."));
+    assert_eq!(normalize("J.R.R").as_deref(), Some("J. R. R."));
+    assert_eq!(normalize("John R.L.").as_deref(), Some("John R. L."));
+    assert_eq!(normalize("JOHN").as_deref(), Some("JOHN"));
+    assert_eq!(normalize("Ph. M.E.").as_deref(), Some("Ph. M. E."));
+    assert_eq!(normalize("Jean-Luc").as_deref(), Some("Jean-Luc"));
+    assert_eq!(normalize("소라").as_deref(), Some("소라"));
+}
+
 #[test]
 fn parse_particles() {
     impl PersonNameInput {
@@ -525,3 +603,38 @@ fn test_is_latin() {
     assert!(pn_is_latin_cyrillic(&pn));
 }
 
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn parse(family: Option<&str>, given: Option<&str>) -> PersonName {
+        PersonName::from(PersonNameInput {
+            family: family.map(Into::into),
+            given: given.map(Into::into),
+            ..Default::default()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn non_dropping_particle(particle in "[a-z]{1,4}", family in "[A-Z][a-z]{1,8}") {
+            let pn = parse(Some(&format!("{} {}", particle, family)), None);
+            prop_assert_eq!(pn.non_dropping_particle.as_deref(), Some(particle.as_str()));
+            prop_assert_eq!(pn.family.as_deref(), Some(family.as_str()));
+        }
+
+        #[test]
+        fn dropping_particle(given in "[A-Z][a-z]{1,8}", particle in "[a-z]{1,4}") {
+            let pn = parse(None, Some(&format!("{} {}", given, particle)));
+            prop_assert_eq!(pn.dropping_particle.as_deref(), Some(particle.as_str()));
+            prop_assert_eq!(pn.given.as_deref(), Some(given.as_str()));
+        }
+
+        #[test]
+        fn parse_anything(family in "\\PC{0,16}", given in "\\PC{0,16}") {
+            let mut pn = parse(Some(&family), Some(&given));
+            pn.normalize_initials();
+        }
+    }
+}
@@ -22,6 +22,30 @@ pub enum NumericValueOwned {
     Tokens(Vec<NumericToken>),
     Str(String),
 }
+
+impl NumericValueOwned {
+    /// Writes the value back out as text, with its separators spaced the usual way, so
+    /// `2 -5,9` comes back as `2-5, 9`.
+    pub fn to_plain(&self) -> String {
+        let tokens = match self {
+            NumericValueOwned::Tokens(tokens) => tokens,
+            NumericValueOwned::Str(s) => return s.clone(),
+        };
+        let mut out = String::new();
+        for token in tokens {
+            match token {
+                Str(s) => out.push_str(s),
+                Comma => out.push_str(", "),
+                Hyphen => out.push('-'),
+                Ampersand => out.push_str(" & "),
+                And => out.push_str(" and "),
+                CommaAnd => out.push_str(", and "),
+                numeric => out.push_str(&numeric.numeric_verbatim().unwrap_or_default()),
+            }
+        }
+        out
+    }
+}
 impl From<NumericValue<'_>> for NumericValueOwned {
     fn from(other: NumericValue) -> Self {
         match other {
@@ -54,6 +78,38 @@ impl NumericToken {
             _ => None,
         }
     }
+    /// The number in an ordinal like `2nd` or `11th`, which the tokenizer reads as an `Affixed`
+    /// token with an English ordinal suffix. The suffix has to fit the number, so `2th` is not
+    /// recognised.
+    pub fn ordinal_num(&self) -> Option<u32> {
+        match *self {
+            Affixed(ref pre, n, ref suf) if pre.is_empty() => {
+                let expected = match (n % 100, n % 10) {
+                    (11..=13, _) => "th",
+                    (_, 1) => "st",
+                    (_, 2) => "nd",
+                    (_, 3) => "rd",
+                    _ => "th",
+                };
+                if suf.eq_ignore_ascii_case(expected) {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+    /// Writes out a single numeric token as it appeared in the input, modulo escapes. Roman
+    /// numerals keep their case, and affixes are retained, so `xii` and `S12` survive as-is.
+    fn numeric_verbatim(&self) -> Option<std::string::String> {
+        match *self {
+            Num(u) => Some(format!("{}", u)),
+            Roman(u, upper) => roman::to(u).map(|r| if upper { r.to_ascii_uppercase() } else { r }),
+            Affixed(ref pre, u, ref suf) => Some(format!("{}{}{}", pre, u, suf)),
+            _ => None,
+        }
+    }
 }
 
 /// Either a parsed vector of numeric tokens, or the raw string input.
@@ -92,13 +148,36 @@ impl<'a> NumericValue<'a> {
     pub fn num(i: u32) -> Self {
         NumericValue::Tokens(format!("{}", i).into(), vec![Num(i)], true)
     }
+    /// The first page of a page range.
+    ///
+    /// ```text
+    /// "2-5, 9"    => Tokens("2", [Num(2)])
+    /// "xii-xiv"   => Tokens("xii", [Roman(12, false)])
+    /// "S12-S15"   => Tokens("S12", [Affixed("S", 12, "")])
+    /// "A-C"       => Str("A")
+    /// ```
+    ///
+    /// Unparseable values are split on the first hyphen or en-dash, and the part before it is
+    /// returned, still as a non-numeric `Str`.
     pub fn page_first(&self) -> Option<Self> {
-        self.first_num().map(NumericValue::num)
-    }
-    fn first_num(&self) -> Option<u32> {
         match *self {
-            NumericValue::Tokens(_, ref ts, _) => ts.get(0).and_then(|token| token.get_num()),
-            NumericValue::Str(_) => None,
+            NumericValue::Tokens(_, ref ts, _) => {
+                let first = ts.get(0)?;
+                let verbatim = first.numeric_verbatim()?;
+                Some(NumericValue::Tokens(
+                    verbatim.into(),
+                    vec![first.clone()],
+                    true,
+                ))
+            }
+            NumericValue::Str(ref s) => {
+                let first = s.split(&['-', '\u{2013}'][..]).next()?.trim();
+                if first.is_empty() {
+                    None
+                } else {
+                    Some(NumericValue::Str(Cow::Owned(first.to_owned())))
+                }
+            }
         }
     }
     pub fn is_numeric(&self) -> bool {
@@ -132,7 +211,34 @@ impl<'a> NumericValue<'a> {
                 }
             }
 
-            NumericValue::Str(_) => false,
+            NumericValue::Str(ref s) => str_is_multiple(s, var.is_quantity()),
+        }
+    }
+    /// [NumericValue::is_multiple] for `variable="locator"`, which also has to know the locator
+    /// type. A `sub verbo` locator is a list of headwords, not numbers, so it is plural when
+    /// there is more than one of them (`s.v. spring`, `s.vv. spring, summer`).
+    ///
+    /// Other locators are plural when they list or span more than one number. Values that are
+    /// not entirely numeric only count numbers separated by a list or range separator, so that
+    /// a single reference with several numbers in it stays singular (`v. 3:16`, `op. 27 no. 2`,
+    /// `p. 5 n. 3`), while `vv. 3:16–18` and `vv. 3:16; 4:2` are plural.
+    pub fn is_multiple_locator(&self, loc_type: csl::LocatorType) -> bool {
+        let parts = |separators: &[char], keep: fn(&str) -> bool| {
+            self.verbatim()
+                .split(separators)
+                .flat_map(|part| part.split(" and "))
+                .filter(|part| keep(part))
+                .nth(1)
+                .is_some()
+        };
+        match (loc_type, self) {
+            (csl::LocatorType::SubVerbo, _) => {
+                parts(&[',', ';', '&'][..], |part| !part.trim().is_empty())
+            }
+            (_, NumericValue::Tokens(_, _, true)) => self.is_multiple(csl::NumberVariable::Locator),
+            _ => parts(&[',', ';', '&', '-', '\u{2013}'][..], |part| {
+                part.chars().any(|c| c.is_ascii_digit())
+            }),
         }
     }
     pub fn verbatim(&self) -> &str {
@@ -143,6 +249,25 @@ impl<'a> NumericValue<'a> {
     }
 }
 
+/// The fallback for values that could not be parsed. A quantity is plural when the first number in
+/// it is higher than 1 (`approx. 300`). Anything else is plural when at least two numbers are
+/// separated by a range or list separator (`vols. 2 & 4`, `nos. 2–5`), but not when they are merely
+/// next to each other (`vol. 2 (1998)`).
+fn str_is_multiple(s: &str, quantity: bool) -> bool {
+    if quantity {
+        return s
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|run| !run.is_empty())
+            // overflowing numbers are certainly higher than 1
+            .map_or(false, |run| run.parse::<u32>().map_or(true, |n| n > 1));
+    }
+    s.split(&[',', '&', '-', '\u{2013}'][..])
+        .flat_map(|part| part.split(" and "))
+        .filter(|part| part.chars().any(|c| c.is_ascii_digit()))
+        .nth(1)
+        .is_some()
+}
+
 // Parsing
 
 impl<'a> NumericValue<'a> {
@@ -509,6 +634,17 @@ fn test_weird_affixes() {
     test_parse!("N0110", [afxd("N0", 110, "")]);
 }
 
+#[test]
+fn test_ordinal_num() {
+    assert_eq!(afxd("", 2, "nd").ordinal_num(), Some(2));
+    assert_eq!(afxd("", 11, "th").ordinal_num(), Some(11));
+    assert_eq!(afxd("", 21, "ST").ordinal_num(), Some(21));
+    assert_eq!(afxd("", 2, "th").ordinal_num(), None);
+    assert_eq!(afxd("S", 2, "nd").ordinal_num(), None);
+    assert_eq!(afxd("", 2, "b").ordinal_num(), None);
+    test_parse!("3rd-5th", [afxd("", 3, "rd"), Hyphen, afxd("", 5, "th")]);
+}
+
 #[test]
 fn test_numeric_escape() {
     test_parse!("3\\-B", [afxd("", 3, "-B")]);
@@ -528,4 +664,145 @@ fn test_page_first() {
         NumericValue::parse("2-5, 9").page_first().unwrap(),
         NumericValue::num(2)
     );
+    assert_eq!(
+        NumericValue::parse("xii-xiv").page_first().unwrap(),
+        NumericValue::Tokens("xii".into(), vec![Roman(12, false)], true)
+    );
+    assert_eq!(
+        NumericValue::parse("XII-XIV").page_first().unwrap(),
+        NumericValue::Tokens("XII".into(), vec![Roman(12, true)], true)
+    );
+    assert_eq!(
+        NumericValue::parse("S12-S15").page_first().unwrap(),
+        NumericValue::Tokens("S12".into(), vec![afxd("S", 12, "")], true)
+    );
+    assert!(NumericValue::parse("S12-S15").is_numeric());
+    assert!(NumericValue::parse("xii-xiv").is_numeric());
+    let unparseable = NumericValue::parse("A-C");
+    assert_eq!(unparseable, NumericValue::Str("A-C".into()));
+    assert_eq!(
+        unparseable.page_first().unwrap(),
+        NumericValue::Str("A".into())
+    );
+    assert!(!unparseable.page_first().unwrap().is_numeric());
+}
+
+#[test]
+fn test_str_is_multiple() {
+    use csl::NumberVariable::{NumberOfVolumes, Volume};
+    let multiple = |inp: &str, var| {
+        let val = NumericValue::parse(inp);
+        assert!(matches!(val, NumericValue::Str(_)), "{:?} parsed", inp);
+        val.is_multiple(var)
+    };
+    assert!(multiple("volumes 2 & 4", Volume));
+    assert!(multiple("vols. 2 and 4", Volume));
+    assert!(multiple("nos. 2–5", Volume));
+    assert!(!multiple("vol. 2", Volume));
+    assert!(!multiple("vol. 2 (1998)", Volume));
+    assert!(!multiple("unknown", Volume));
+    assert!(multiple("approx. 3", NumberOfVolumes));
+    assert!(!multiple("approx. 1", NumberOfVolumes));
+    assert!(!multiple("several", NumberOfVolumes));
+}
+
+#[test]
+fn test_is_multiple_locator() {
+    use csl::LocatorType::{Page, Paragraph, SubVerbo};
+    let multiple = |inp: &str, loc_type| NumericValue::parse(inp).is_multiple_locator(loc_type);
+    assert!(!multiple("spring", SubVerbo));
+    assert!(!multiple("spring,", SubVerbo));
+    assert!(!multiple("2", SubVerbo));
+    assert!(multiple("spring, summer", SubVerbo));
+    assert!(multiple("spring; summer", SubVerbo));
+    assert!(multiple("spring & summer", SubVerbo));
+    assert!(multiple("spring and summer", SubVerbo));
+    assert!(!multiple("12", Paragraph));
+    assert!(multiple("12, 14", Paragraph));
+    assert!(multiple("12-14", Page));
+    assert!(!multiple("spring and summer", Page));
+}
+
+#[test]
+fn test_is_multiple_locator_partly_numeric() {
+    use csl::LocatorType::{Opus, Page, Paragraph, Section, Verse};
+    let multiple = |inp: &str, loc_type| NumericValue::parse(inp).is_multiple_locator(loc_type);
+    assert!(!multiple("3:16", Verse));
+    assert!(multiple("3:16-18", Verse));
+    assert!(multiple("3:16\u{2013}4:2", Verse));
+    assert!(multiple("3:16; 4:2", Verse));
+    assert!(multiple("3:16 and 4:2", Verse));
+    assert!(!multiple("27 no. 2", Opus));
+    assert!(!multiple("5 n. 3", Page));
+    assert!(multiple("5, 7 n. 3", Page));
+    assert!(!multiple("12(a)(ii)", Section));
+    assert!(multiple("12(a), 14(b)", Section));
+    assert!(!multiple("para 4", Paragraph));
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn number() -> impl Strategy<Value = NumericToken> {
+        // Affixes leave out `a`, so that `, and2` can't be read as `, and` followed by `2`.
+        let affix = "[b-zA-Z]{0,3}";
+        prop_oneof![
+            any::<u32>().prop_map(Num),
+            (1..=roman::MAX, any::<bool>()).prop_map(|(n, upper)| Roman(n, upper)),
+            (affix, 1..=u32::MAX, affix)
+                .prop_filter("no affixes", |(pre, _, suf)| pre.len() + suf.len() > 0)
+                .prop_map(|(pre, n, suf)| Affixed(pre.as_str().into(), n, suf.as_str().into())),
+        ]
+    }
+
+    fn separator() -> impl Strategy<Value = NumericToken> {
+        prop_oneof![
+            Just(Comma),
+            Just(Hyphen),
+            Just(Ampersand),
+            Just(And),
+            Just(CommaAnd),
+        ]
+    }
+
+    fn tokens() -> impl Strategy<Value = Vec<NumericToken>> {
+        let rest = prop::collection::vec((separator(), number()), 0..6);
+        (number(), rest).prop_map(|(first, rest)| {
+            let mut tokens = vec![first];
+            for (sep, num) in rest {
+                tokens.push(sep);
+                tokens.push(num);
+            }
+            tokens
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn plain_round_trip(tokens in tokens()) {
+            let plain = NumericValueOwned::Tokens(tokens.clone()).to_plain();
+            prop_assert_eq!(
+                NumericValue::parse(&plain),
+                NumericValue::Tokens(plain.as_str().into(), tokens, true)
+            );
+        }
+
+        #[test]
+        fn parse_anything(input in "\\PC{0,24}") {
+            let value = NumericValue::parse(&input);
+            let _ = value.page_first();
+            let _ = value.is_multiple(csl::NumberVariable::NumberOfPages);
+            let _ = value.is_multiple_locator(csl::LocatorType::SubVerbo);
+            let _ = NumericValueOwned::from(value).to_plain();
+        }
+
+        #[test]
+        fn roman_anything(input in "[ivxlcdmIVXLCDM]{0,16}") {
+            if let Some(n) = roman::from(&input) {
+                prop_assert!(roman::to(n).unwrap().eq_ignore_ascii_case(&input));
+            }
+        }
+    }
 }
@@ -99,17 +99,19 @@ pub fn from(txt: &str) -> Option<u32> {
     }
 }
 
+/// None if the numeral is not valid, including when it goes below zero (`ivvx`) or overflows
+/// (a very long run of `m`s) along the way.
 fn from_lax(txt: &str) -> Option<u32> {
-    let (mut n, mut max) = (0, 0);
+    let (mut n, mut max) = (0u32, 0);
     for c in txt.chars().rev() {
         let &(_, val) = ROMAN.iter().find(|x| {
             let &(ch, _) = *x;
             ch == c
         })?;
         if val < max {
-            n -= val;
+            n = n.checked_sub(val)?;
         } else {
-            n += val;
+            n = n.checked_add(val)?;
             max = val;
         }
     }
@@ -119,6 +121,9 @@ fn from_lax(txt: &str) -> Option<u32> {
 #[test]
 fn test_from() {
     assert!(from("I").is_some());
+    assert_eq!(from("ivvx"), None);
+    assert_eq!(from("vvvx"), None);
+    assert_eq!(from(&"m".repeat(5_000_000)), None);
 }
 
 #[test]
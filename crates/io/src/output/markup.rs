@@ -15,14 +15,19 @@ use csl::{
 };
 
 mod rtf;
+pub use self::rtf::RtfOptions;
 use self::rtf::RtfWriter;
 
 mod html;
+pub use self::html::LinkOptions;
 use self::html::{HtmlOptions, HtmlWriter};
 
 mod plain;
+pub use self::plain::PlainOptions;
 use self::plain::PlainWriter;
 
+mod fallback;
+use self::fallback::apply_fallbacks;
 mod flip_flop;
 use self::flip_flop::FlipFlopState;
 mod move_punctuation;
@@ -34,12 +39,33 @@ pub use self::parse_quotes::parse_quotes;
 pub(self) mod puncttable;
 
 use crate::String;
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Markup {
     Html(HtmlOptions),
-    Rtf,
-    Plain,
+    Rtf(RtfOptions),
+    Plain(PlainOptions),
+}
+
+/// What to do with `font-variant="small-caps"` in formats that cannot express it (plain text), or
+/// whose consumers often ignore it (RTF pasted into a citation picker, for example).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmallCapsFallback {
+    /// Use the format's own small caps, e.g. `\scaps` in RTF. Plain text has none, so there this
+    /// is the same as `Ignore`.
+    Native,
+    /// Write small-caps text in uppercase instead.
+    Uppercase,
+    /// Drop the formatting and write the text as-is.
+    Ignore,
+}
+
+impl Default for SmallCapsFallback {
+    fn default() -> Self {
+        SmallCapsFallback::Native
+    }
 }
 
 /// TODO: serialize and deserialize using an HTML parser?
@@ -63,6 +89,69 @@ pub enum InlineElement {
         content: Vec<InlineElement>,
     },
     Div(DisplayMode, Vec<InlineElement>),
+    /// One cite of a cluster, with its prefix and suffix. `index` is where the cite is in the
+    /// cluster as it was given to the processor, so a host can tell which cite a piece of the
+    /// output belongs to. This only appears in cluster ASTs, and writes out as just its content.
+    Cite {
+        index: u32,
+        content: Vec<InlineElement>,
+    },
+    /// Trusted markup from a [`MarkupInput::Verbatim`], written out without escaping.
+    Verbatim(String),
+}
+
+/// The [`OutputFormat::Input`] for [`Markup`], i.e. what cite prefixes and suffixes are made of.
+///
+/// A plain string is parsed as micro-html, the same as fields on a reference, and everything else
+/// in it is escaped for the output format. `{ "verbatim": "..." }` is trusted markup in the output
+/// format (e.g. an `<a>` tag for HTML), and is passed through untouched, but only if the processor
+/// was told to trust it (`InitOptions::verbatim_affixes`); otherwise it is escaped like a plain
+/// string. Only use it for markup the host constructed itself.
+///
+/// ```
+/// use citeproc_io::output::markup::MarkupInput;
+/// let inputs: Vec<MarkupInput> =
+///     serde_json::from_str(r#"["see ", { "verbatim": "<a href=\"#\">here</a>" }]"#).unwrap();
+/// assert_eq!(inputs[0], MarkupInput::Text("see ".into()));
+/// assert!(inputs[1].is_verbatim());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MarkupInput {
+    Text(String),
+    Verbatim { verbatim: String },
+}
+
+impl Default for MarkupInput {
+    fn default() -> Self {
+        MarkupInput::Text(String::new())
+    }
+}
+
+impl MarkupInput {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MarkupInput::Text(s) | MarkupInput::Verbatim { verbatim: s } => s.as_str(),
+        }
+    }
+    pub fn is_verbatim(&self) -> bool {
+        match self {
+            MarkupInput::Verbatim { .. } => true,
+            MarkupInput::Text(_) => false,
+        }
+    }
+}
+
+impl AsRef<str> for MarkupInput {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for MarkupInput {
+    fn from(s: &str) -> Self {
+        MarkupInput::Text(s.into())
+    }
 }
 
 impl Markup {
@@ -73,10 +162,39 @@ impl Markup {
         Markup::Html(HtmlOptions::test_suite())
     }
     pub fn rtf() -> Self {
-        Markup::Rtf
+        Markup::Rtf(RtfOptions::default())
     }
     pub fn plain() -> Self {
-        Markup::Plain
+        Markup::Plain(PlainOptions::default())
+    }
+    /// Sets the small caps policy for plain text and RTF. HTML always has small caps, so it is
+    /// unaffected.
+    pub fn with_small_caps(self, small_caps: SmallCapsFallback) -> Self {
+        match self {
+            Markup::Html(_) => self,
+            Markup::Rtf(o) => Markup::Rtf(RtfOptions { small_caps, ..o }),
+            Markup::Plain(o) => Markup::Plain(PlainOptions { small_caps, ..o }),
+        }
+    }
+    /// Sets the extra attributes for `<a>` tags in HTML. Other formats don't write links.
+    pub fn with_links(self, links: LinkOptions) -> Self {
+        match self {
+            Markup::Html(o) => Markup::Html(HtmlOptions { links, ..o }),
+            _ => self,
+        }
+    }
+    pub fn links(&self) -> LinkOptions {
+        match self {
+            Markup::Html(o) => o.links.clone(),
+            _ => LinkOptions::default(),
+        }
+    }
+    pub fn small_caps(&self) -> SmallCapsFallback {
+        match self {
+            Markup::Html(_) => SmallCapsFallback::Native,
+            Markup::Rtf(o) => o.small_caps,
+            Markup::Plain(o) => o.small_caps,
+        }
     }
 }
 
@@ -95,7 +213,7 @@ pub struct MarkupBibMeta {
 }
 
 impl OutputFormat for Markup {
-    type Input = String;
+    type Input = MarkupInput;
     type Build = Vec<InlineElement>;
     type Output = String;
     type BibMeta = MarkupBibMeta;
@@ -103,8 +221,8 @@ impl OutputFormat for Markup {
     fn meta(&self) -> Self::BibMeta {
         let (pre, post) = match self {
             Markup::Html(_) => ("<div class=\"csl-bib-body\">", "</div>"),
-            Markup::Rtf => ("", ""),
-            Markup::Plain => ("", ""),
+            Markup::Rtf(_) => ("", ""),
+            Markup::Plain(_) => ("", ""),
         };
         MarkupBibMeta {
             markup_pre: pre.into(),
@@ -208,7 +326,11 @@ impl OutputFormat for Markup {
 
     #[inline]
     fn is_empty(&self, a: &Self::Build) -> bool {
-        a.is_empty()
+        renders_nothing(a)
+    }
+
+    fn reuse_markup(&self, built: &[InlineElement]) -> Option<Self::Build> {
+        Some(built.to_vec())
     }
 
     #[inline]
@@ -231,18 +353,20 @@ impl OutputFormat for Markup {
     #[inline]
     fn stack_preorder(&self, dest: &mut String, stack: &[FormatCmd]) {
         match *self {
-            Markup::Html(options) => HtmlWriter::new(dest, options).stack_preorder(stack),
-            Markup::Rtf => PlainWriter::new(dest).stack_preorder(stack),
-            Markup::Plain => PlainWriter::new(dest).stack_preorder(stack),
+            Markup::Html(ref options) => HtmlWriter::new(dest, options).stack_preorder(stack),
+            Markup::Rtf(_) => PlainWriter::new(dest, PlainOptions::default()).stack_preorder(stack),
+            Markup::Plain(options) => PlainWriter::new(dest, options).stack_preorder(stack),
         }
     }
 
     #[inline]
     fn stack_postorder(&self, dest: &mut String, stack: &[FormatCmd]) {
         match *self {
-            Markup::Html(options) => HtmlWriter::new(dest, options).stack_postorder(stack),
-            Markup::Rtf => PlainWriter::new(dest).stack_postorder(stack),
-            Markup::Plain => PlainWriter::new(dest).stack_postorder(stack),
+            Markup::Html(ref options) => HtmlWriter::new(dest, options).stack_postorder(stack),
+            Markup::Rtf(_) => {
+                PlainWriter::new(dest, PlainOptions::default()).stack_postorder(stack)
+            }
+            Markup::Plain(options) => PlainWriter::new(dest, options).stack_postorder(stack),
         }
     }
 
@@ -273,7 +397,62 @@ impl OutputFormat for Markup {
     }
 }
 
+/// Empty strings, and formatting with nothing inside it, e.g. a title that was all periods and
+/// `strip-periods="true"`. Quotes and links are visible even when empty.
+fn renders_nothing(inlines: &[InlineElement]) -> bool {
+    fn micro_renders_nothing(micros: &[MicroNode]) -> bool {
+        micros.iter().all(|micro| match micro {
+            MicroNode::Text(s) => s.is_empty(),
+            MicroNode::Formatted(children, _)
+            | MicroNode::NoCase(children)
+            | MicroNode::NoDecor(children) => micro_renders_nothing(children),
+            MicroNode::Quoted { .. } => false,
+        })
+    }
+    inlines.iter().all(|inline| match inline {
+        Text(s) | Verbatim(s) => s.is_empty(),
+        Micro(micros) => micro_renders_nothing(micros),
+        Formatted(inlines, _)
+        | Div(_, inlines)
+        | Cite {
+            content: inlines, ..
+        } => renders_nothing(inlines),
+        Quoted { .. } | Anchor { .. } => false,
+    })
+}
+
 impl Markup {
+    /// Passes trusted markup straight through to the output. See [`MarkupInput::Verbatim`].
+    pub fn verbatim(&self, markup: &str) -> Vec<InlineElement> {
+        if markup.is_empty() {
+            return vec![];
+        }
+        vec![Verbatim(markup.into())]
+    }
+
+    /// Removes the full stop (or other terminal punctuation) from the end of a whole cluster, so
+    /// that it can be dropped into the middle of a sentence.
+    pub fn trim_trailing_punctuation(&self, build: &mut Vec<InlineElement>) {
+        move_punctuation::trim_trailing_punctuation(build)
+    }
+
+    /// The width in characters of the first `display="left-margin"` block in a bibliography
+    /// entry, e.g. 4 for `[12]`. This is what `second-field-align` lines the rest of the entry up
+    /// against.
+    pub fn left_margin_width(&self, build: &[InlineElement]) -> Option<u32> {
+        fn find(inlines: &[InlineElement]) -> Option<&[InlineElement]> {
+            inlines.iter().find_map(|inline| match inline {
+                Div(DisplayMode::LeftMargin, inner) => Some(inner.as_slice()),
+                Formatted(inner, _) | Div(_, inner) | Cite { content: inner, .. } => find(inner),
+                Quoted { inlines: inner, .. } | Anchor { content: inner, .. } => find(inner),
+                _ => None,
+            })
+        }
+        let inner = find(build)?;
+        let text = Markup::plain().output(inner.to_vec(), false);
+        Some(text.trim().chars().count() as u32)
+    }
+
     fn fmt_vec(
         &self,
         inlines: Vec<InlineElement>,
@@ -286,6 +465,18 @@ impl Markup {
         }
     }
 
+    /// The inline tree that [OutputFormat::output] writes out, after flip-flopping and moving
+    /// punctuation, but before it is turned into HTML, RTF or plain text.
+    pub fn output_tree(
+        &self,
+        intermediate: Vec<InlineElement>,
+        punctuation_in_quote: bool,
+    ) -> Vec<InlineElement> {
+        let mut flipped = FlipFlopState::default().flip_flop_inlines(&intermediate);
+        move_punctuation(&mut flipped, Some(punctuation_in_quote));
+        flipped
+    }
+
     fn output_with_state(
         &self,
         intermediate: <Self as OutputFormat>::Build,
@@ -294,11 +485,18 @@ impl Markup {
     ) -> <Self as OutputFormat>::Output {
         let mut flipped = initial_state.flip_flop_inlines(&intermediate);
         move_punctuation(&mut flipped, punctuation_in_quote);
+        let flipped = apply_fallbacks(flipped, self.capabilities());
         let mut dest = String::new();
         match *self {
-            Markup::Html(options) => HtmlWriter::new(&mut dest, options).write_inlines(&flipped, false),
-            Markup::Rtf => RtfWriter::new(&mut dest).write_inlines(&flipped, false),
-            Markup::Plain => PlainWriter::new(&mut dest).write_inlines(&flipped, false),
+            Markup::Html(ref options) => {
+                HtmlWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
+            Markup::Rtf(options) => {
+                RtfWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
+            Markup::Plain(options) => {
+                PlainWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
         }
         dest
     }
@@ -383,6 +581,37 @@ fn tag_stack(formatting: Formatting, display: Option<DisplayMode>) -> Vec<Format
     stack
 }
 
+/// For writers that emulate small caps, tracks whether the text being written is in small caps.
+/// Flip-flopping means a `FontVariantNormal` can turn it off again inside.
+#[derive(Debug, Default)]
+struct SmallCapsState {
+    stack: Vec<bool>,
+}
+
+impl SmallCapsState {
+    fn push(&mut self, cmd: FormatCmd) {
+        match cmd {
+            FormatCmd::FontVariantSmallCaps => self.stack.push(true),
+            FormatCmd::FontVariantNormal => self.stack.push(false),
+            _ => {}
+        }
+    }
+    fn pop(&mut self, cmd: FormatCmd) {
+        if let FormatCmd::FontVariantSmallCaps | FormatCmd::FontVariantNormal = cmd {
+            self.stack.pop();
+        }
+    }
+    /// Returns the text as it should be written, given the fallback policy.
+    fn apply<'a>(&self, fallback: SmallCapsFallback, text: &'a str) -> Cow<'a, str> {
+        let active = self.stack.last().cloned().unwrap_or(false);
+        if active && fallback == SmallCapsFallback::Uppercase {
+            Cow::Owned(text.to_uppercase())
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+}
+
 trait MaybeTrimStart {
     fn trim_start_if<'a>(&'a self, trim_if: bool) -> &'a Self;
 }
@@ -0,0 +1,251 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! Stand-ins for constructs an output format cannot write, so that a style asking for them (e.g.
+//! `display="block"` in plain text) still gets something readable instead of losing content.
+
+use super::{renders_nothing, InlineElement, Markup};
+use crate::output::OutputFormat;
+use csl::DisplayMode;
+
+/// What an output format can write natively.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct Capabilities {
+    /// `display="block"` and `display="indent"`
+    pub block_display: bool,
+    /// `display="left-margin"` and `display="right-inline"`
+    pub margin_display: bool,
+    pub links: bool,
+}
+
+impl Markup {
+    pub(super) fn capabilities(&self) -> Capabilities {
+        match self {
+            Markup::Html(_) => Capabilities {
+                block_display: true,
+                margin_display: true,
+                links: true,
+            },
+            // The RTF writer lines up margins with a tab, but has no paragraphs within an entry.
+            Markup::Rtf(_) => Capabilities {
+                block_display: false,
+                margin_display: true,
+                links: true,
+            },
+            Markup::Plain(_) => Capabilities {
+                block_display: false,
+                margin_display: false,
+                links: false,
+            },
+        }
+    }
+}
+
+/// Rewrites everything `capabilities` rules out:
+///
+/// - block and indent displays go on lines of their own (indented by four spaces), using a
+///   newline that the writer escapes however it writes line breaks;
+/// - a left margin is separated from what follows by a space;
+/// - a link becomes `text (url)`, or just the text if it is the url already.
+pub(super) fn apply_fallbacks(
+    inlines: Vec<InlineElement>,
+    capabilities: Capabilities,
+) -> Vec<InlineElement> {
+    let caps = capabilities;
+    if caps.block_display && caps.margin_display && caps.links {
+        return inlines;
+    }
+    Fallbacks {
+        caps,
+        written: false,
+        pending: None,
+    }
+    .apply(inlines)
+}
+
+struct Fallbacks {
+    caps: Capabilities,
+    /// Whether anything has been written yet. A line break at the very start is dropped.
+    written: bool,
+    /// Separator to write before the next piece of content, if there is any.
+    pending: Option<&'static str>,
+}
+
+impl Fallbacks {
+    fn supports(&self, display: DisplayMode) -> bool {
+        match display {
+            DisplayMode::Block | DisplayMode::Indent => self.caps.block_display,
+            DisplayMode::LeftMargin | DisplayMode::RightInline => self.caps.margin_display,
+        }
+    }
+
+    /// Call before pushing an element that writes something.
+    fn content(&mut self, out: &mut Vec<InlineElement>) {
+        if let Some(sep) = self.pending.take() {
+            let sep = if self.written {
+                sep
+            } else {
+                sep.trim_start_matches('\n')
+            };
+            if !sep.is_empty() {
+                out.push(InlineElement::Text(sep.into()));
+            }
+        }
+        self.written = true;
+    }
+
+    fn apply(&mut self, inlines: Vec<InlineElement>) -> Vec<InlineElement> {
+        use InlineElement::*;
+        let mut out = Vec::with_capacity(inlines.len());
+        for inline in inlines {
+            match inline {
+                Div(display, inner) if !self.supports(display) => {
+                    match display {
+                        DisplayMode::Block => self.pending = Some("\n"),
+                        DisplayMode::Indent => self.pending = Some("\n    "),
+                        _ => {}
+                    }
+                    out.extend(self.apply(inner));
+                    match display {
+                        DisplayMode::Block | DisplayMode::Indent => self.pending = Some("\n"),
+                        DisplayMode::LeftMargin => self.pending = Some(" "),
+                        DisplayMode::RightInline => {}
+                    }
+                }
+                Div(display, inner) => out.push(Div(display, self.apply(inner))),
+                Anchor {
+                    title,
+                    url,
+                    content,
+                } => {
+                    let content = self.apply(content);
+                    if self.caps.links {
+                        out.push(Anchor {
+                            title,
+                            url,
+                            content,
+                        });
+                        continue;
+                    }
+                    let text = Markup::plain().output(content.clone(), false);
+                    out.extend(content);
+                    if text.trim() != url.trim() {
+                        self.content(&mut out);
+                        out.push(Text(format!(" ({})", url).into()));
+                    }
+                }
+                Formatted(inner, formatting) => out.push(Formatted(self.apply(inner), formatting)),
+                Cite { index, content } => out.push(Cite {
+                    index,
+                    content: self.apply(content),
+                }),
+                Quoted {
+                    is_inner,
+                    localized,
+                    inlines,
+                } => {
+                    self.content(&mut out);
+                    let inlines = self.apply(inlines);
+                    out.push(Quoted {
+                        is_inner,
+                        localized,
+                        inlines,
+                    });
+                }
+                leaf => {
+                    if !renders_nothing(std::slice::from_ref(&leaf)) {
+                        self.content(&mut out);
+                    }
+                    out.push(leaf);
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use InlineElement::*;
+
+    fn text(s: &str) -> InlineElement {
+        Text(s.into())
+    }
+
+    fn div(display: DisplayMode, s: &str) -> InlineElement {
+        Div(display, vec![text(s)])
+    }
+
+    fn link(s: &str, url: &str) -> InlineElement {
+        Anchor {
+            title: "".into(),
+            url: url.into(),
+            content: vec![text(s)],
+        }
+    }
+
+    fn entry() -> Vec<InlineElement> {
+        vec![
+            div(DisplayMode::Block, "Smith"),
+            div(DisplayMode::Indent, "Title"),
+            div(DisplayMode::Block, "2000"),
+        ]
+    }
+
+    #[test]
+    fn blocks_become_lines() {
+        assert_eq!(
+            Markup::plain().output(entry(), false),
+            "Smith\n    Title\n2000"
+        );
+        assert_eq!(
+            Markup::rtf().output(entry(), false),
+            r"Smith\line     Title\line 2000"
+        );
+        assert_eq!(
+            Markup::plain().output(vec![div(DisplayMode::Block, "Smith"), text(".")], false),
+            "Smith\n."
+        );
+    }
+
+    #[test]
+    fn left_margin_gets_a_space() {
+        let entry = vec![
+            div(DisplayMode::LeftMargin, "[1]"),
+            div(DisplayMode::RightInline, "Smith"),
+        ];
+        assert_eq!(Markup::plain().output(entry.clone(), false), "[1] Smith");
+        assert_eq!(Markup::rtf().output(entry, false), r"{[1]}\tab {Smith}");
+    }
+
+    #[test]
+    fn links_keep_their_url() {
+        let url = "https://example.com";
+        assert_eq!(
+            Markup::plain().output(vec![link("Home", url), text(".")], false),
+            "Home (https://example.com)."
+        );
+        assert_eq!(
+            Markup::plain().output(vec![link(url, url)], false),
+            "https://example.com"
+        );
+        assert_eq!(
+            Markup::rtf().output(vec![link("Home", url)], false),
+            r#"{\field{\*\fldinst HYPERLINK \"https://example.com"}{\fldrslt Home}}"#
+        );
+    }
+
+    #[test]
+    fn html_unchanged() {
+        let mut inlines = entry();
+        inlines.push(link("Home", "https://example.com"));
+        assert_eq!(
+            apply_fallbacks(inlines.clone(), Markup::html().capabilities()),
+            inlines
+        );
+    }
+}
@@ -138,6 +138,14 @@ fn flip_flop(
             Ok(InlineElement::Div(dm, nodes))
         }
 
+        InlineElement::Cite { index, ref content } => {
+            let nodes = state.flip_flop_inlines(content);
+            Ok(InlineElement::Cite {
+                index,
+                content: nodes,
+            })
+        }
+
         InlineElement::Text(ref string) if string.is_empty() => Err(vec![]),
 
         _ => Ok(inline.clone()),
@@ -12,12 +12,13 @@ use crate::output::FormatCmd;
 use csl::Formatting;
 use super::MaybeTrimStart;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HtmlOptions {
     // TODO: is it enough to have one set of localized quotes for the entire style?
     // quotes: LocalizedQuotes,
     use_b_for_strong: bool,
     link_anchors: bool,
+    pub(super) links: LinkOptions,
 }
 
 impl Default for HtmlOptions {
@@ -25,6 +26,7 @@ impl Default for HtmlOptions {
         HtmlOptions {
             use_b_for_strong: false,
             link_anchors: true,
+            links: LinkOptions::default(),
         }
     }
 }
@@ -34,20 +36,42 @@ impl HtmlOptions {
         HtmlOptions {
             use_b_for_strong: true,
             link_anchors: false,
+            links: LinkOptions::default(),
         }
     }
 }
 
+/// Extra attributes for the `<a>` tags in HTML output, e.g. to open links in a new tab
+/// (`target="_blank"`, `rel="noopener noreferrer"`) or to style them. Each is left off when it is
+/// `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LinkOptions {
+    pub target: Option<String>,
+    pub rel: Option<String>,
+    pub class: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct HtmlWriter<'a> {
     dest: &'a mut String,
-    options: HtmlOptions,
+    options: &'a HtmlOptions,
 }
 
 impl<'a> HtmlWriter<'a> {
-    pub fn new(dest: &'a mut String, options: HtmlOptions) -> Self {
+    pub fn new(dest: &'a mut String, options: &'a HtmlOptions) -> Self {
         HtmlWriter { dest, options }
     }
+
+    fn write_attribute(&mut self, name: &str, value: Option<&String>) {
+        if let Some(value) = value {
+            self.dest.push(' ');
+            self.dest.push_str(name);
+            self.dest.push_str(r#"=""#);
+            self.write_escaped(value);
+            self.dest.push('"');
+        }
+    }
 }
 
 impl<'a> MarkupWriter for HtmlWriter<'a> {
@@ -57,7 +81,7 @@ impl<'a> MarkupWriter for HtmlWriter<'a> {
     }
     fn stack_preorder(&mut self, stack: &[FormatCmd]) {
         for cmd in stack.iter() {
-            let tag = cmd.html_tag(&self.options);
+            let tag = cmd.html_tag(self.options);
             self.dest.push_str("<");
             self.dest.push_str(tag.0);
             self.dest.push_str(tag.1);
@@ -71,7 +95,7 @@ impl<'a> MarkupWriter for HtmlWriter<'a> {
                 let tlen = self.dest.trim_end_matches(' ').len();
                 self.dest.truncate(tlen)
             }
-            let tag = cmd.html_tag(&self.options);
+            let tag = cmd.html_tag(self.options);
             self.dest.push_str("</");
             self.dest.push_str(tag.0);
             self.dest.push_str(">");
@@ -113,9 +137,15 @@ impl<'a> MarkupWriter for HtmlWriter<'a> {
             Text(text) => {
                 self.write_escaped(text.trim_start_if(trim_start));
             }
+            Verbatim(markup) => {
+                self.dest.push_str(markup);
+            }
             Div(display, inlines) => {
                 self.stack_formats(inlines, Formatting::default(), Some(*display));
             }
+            Cite { content, .. } => {
+                self.write_inlines(content, trim_start);
+            }
             Micro(micros) => {
                 self.write_micros(micros, trim_start);
             }
@@ -137,7 +167,12 @@ impl<'a> MarkupWriter for HtmlWriter<'a> {
                     self.dest.push_str(r#"<a href=""#);
                     // TODO: HTML-quoted-escape? the url?
                     self.dest.push_str(&url.trim());
-                    self.dest.push_str(r#"">"#);
+                    self.dest.push('"');
+                    let links = &self.options.links;
+                    self.write_attribute("target", links.target.as_ref());
+                    self.write_attribute("rel", links.rel.as_ref());
+                    self.write_attribute("class", links.class.as_ref());
+                    self.dest.push('>');
                     self.write_inlines(content, false);
                     self.dest.push_str("</a>");
                 } else {
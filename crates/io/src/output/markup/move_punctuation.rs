@@ -18,6 +18,50 @@ fn normalise() {
     assert_eq!(&nodes[..], &[InlineElement::Text("ab".into())][..]);
 }
 
+#[test]
+fn squash_affix_boundaries() {
+    fn flatten(els: &[InlineElement], into: &mut String) {
+        for el in els {
+            match el {
+                InlineElement::Text(s) => into.push_str(s),
+                InlineElement::Formatted(inlines, _) => flatten(inlines, into),
+                _ => panic!("unexpected {:?}", el),
+            }
+        }
+    }
+    // (left, right, squashed)
+    let table = [
+        ("a", "b", "ab"),
+        ("a", " b", "a b"),
+        ("a ", " b", "a b"),
+        ("a.", ". b", "a. b"),
+        ("a. ", ". b", "a. b"),
+        ("a:", ": b", "a: b"),
+        ("a;", ". b", "a; b"),
+        ("a!", ". b", "a! b"),
+        ("a?", "? b", "a? b"),
+        ("a,", ", b", "a, b"),
+        ("a,", ". b", "a,. b"),
+        ("a.", ", b", "a., b"),
+        ("a.", "; b", "a.; b"),
+        ("a?", "! b", "a?! b"),
+    ];
+    for &(left, right, expected) in table.iter() {
+        // Between two plain strings, and where the right one is formatted.
+        let right_formatted = InlineElement::Formatted(
+            vec![InlineElement::Text(right.into())],
+            csl::Formatting::italic(),
+        );
+        for right in vec![InlineElement::Text(right.into()), right_formatted] {
+            let mut nodes = vec![InlineElement::Text(left.into()), right];
+            normalise_text_elements(&mut nodes);
+            let mut squashed = String::new();
+            flatten(&nodes, &mut squashed);
+            assert_eq!(squashed, expected, "squashing {:?}", nodes);
+        }
+    }
+}
+
 fn smash_string_push(base: &mut String, suff: &str) {
     trace!("smash_string_push {:?} <- {:?}", base, suff);
     let btrim = base.trim_end_matches(smash_trim);
@@ -195,6 +239,22 @@ pub fn normalise_text_elements(slice: &mut Vec<InlineElement>) {
                             None => {}
                         }
                     }
+                    (InlineElement::Text(s1), InlineElement::Formatted(children2, _)) => {
+                        match children2.first_mut().and_then(find_string_left_f) {
+                            Some(s2) => smash_just_punc(s1, s2),
+                            None => {}
+                        }
+                    }
+                    (
+                        InlineElement::Formatted(children, _),
+                        InlineElement::Formatted(children2, _),
+                    ) => {
+                        let s1 = children.last_mut().and_then(find_string_right_f);
+                        let s2 = children2.first_mut().and_then(find_string_left_f);
+                        if let (Some(s1), Some(s2)) = (s1, s2) {
+                            smash_just_punc(s1, s2)
+                        }
+                    }
                     (InlineElement::Formatted(children, _), InlineElement::Micro(ms2)) => {
                         trace!("formatted, micro");
                         match children.last_mut().and_then(find_string_right_f) {
@@ -232,6 +292,9 @@ pub fn normalise_text_elements(slice: &mut Vec<InlineElement>) {
             | InlineElement::Anchor {
                 content: inlines, ..
             }
+            | InlineElement::Cite {
+                content: inlines, ..
+            }
             | InlineElement::Formatted(inlines, _) => normalise_text_elements(inlines),
             InlineElement::Micro(micros) => normalise_text_elements_micro(micros),
             _ => {}
@@ -316,6 +379,9 @@ pub fn move_punctuation(slice: &mut Vec<InlineElement>, punctuation_in_quote: Op
             | InlineElement::Anchor {
                 content: inlines, ..
             }
+            | InlineElement::Cite {
+                content: inlines, ..
+            }
             | InlineElement::Formatted(inlines, _) => {
                 move_punctuation(inlines, punctuation_in_quote)
             }
@@ -521,6 +587,14 @@ fn find_string_left(next: &mut InlineElement) -> Option<&mut String> {
     }
 }
 
+/// Like `find_string_left`, but also looks inside formatting.
+fn find_string_left_f(next: &mut InlineElement) -> Option<&mut String> {
+    match next {
+        InlineElement::Formatted(children, _) => children.first_mut().and_then(find_string_left_f),
+        _ => find_string_left(next),
+    }
+}
+
 /// Allows finding formatting?
 fn find_string_right_f(next: &mut InlineElement) -> Option<&mut String> {
     match next {
@@ -642,7 +716,11 @@ fn find_right_quote_inside<'a>(
         InlineElement::Micro(micros) => micros
             .last_mut()
             .and_then(move |x| find_right_quote_inside_micro(x, next)),
-        InlineElement::Div(_, inlines) | InlineElement::Formatted(inlines, _) => inlines
+        InlineElement::Div(_, inlines)
+        | InlineElement::Cite {
+            content: inlines, ..
+        }
+        | InlineElement::Formatted(inlines, _) => inlines
             .last_mut()
             .and_then(move |x| find_right_quote_inside(x, next)),
         _ => None,
@@ -761,6 +839,9 @@ fn last_string(is: &mut [InlineElement]) -> Option<&mut String> {
         InlineElement::Micro(micros) => last_string_micro(micros),
         InlineElement::Quoted { inlines, .. }
         | InlineElement::Div(_, inlines)
+        | InlineElement::Cite {
+            content: inlines, ..
+        }
         | InlineElement::Formatted(inlines, _) => last_string(inlines),
         InlineElement::Text(string) => Some(string),
         _ => None,
@@ -777,6 +858,19 @@ fn last_string_micro(ms: &mut [MicroNode]) -> Option<&mut String> {
     })
 }
 
+/// Strips a cluster's terminal `.`, `,`, `;` or `:`, wherever it ended up: inside formatting, or
+/// inside a closing quote after punctuation-in-quote. Punctuation before a closing bracket is left
+/// alone, so `(ed.)` keeps its full stop.
+pub fn trim_trailing_punctuation(els: &mut [InlineElement]) {
+    if let Some(string) = last_string(els) {
+        let trimmed = string
+            .trim_end()
+            .trim_end_matches(|c| matches!(c, '.' | ',' | ';' | ':'))
+            .len();
+        string.truncate(trimmed);
+    }
+}
+
 pub fn append_suffix(pre_and_content: &mut Vec<InlineElement>, suffix: Vec<MicroNode>) {
     // if let Some(last) = pre_and_content.last_mut() {
     //     // Must be followed by some text
@@ -956,8 +1050,11 @@ pub fn ends_with_full_stop(els: &[InlineElement], top: bool) -> bool {
         InlineElement::Quoted { inlines, .. } => {
             ends_with_full_stop(inlines, false)
         }
+        InlineElement::Cite { content, .. } => ends_with_full_stop(content, false),
         InlineElement::Anchor { .. } |
         InlineElement::Div(..) => true,
+        // Can't see inside someone else's markup
+        InlineElement::Verbatim(_) => false,
 
         InlineElement::Micro(micros) => {
             return micro_ends_fs(micros, top);
@@ -11,25 +11,46 @@ use crate::output::micro_html::MicroNode;
 use crate::output::FormatCmd;
 use csl::Formatting;
 use super::MaybeTrimStart;
+use super::{SmallCapsFallback, SmallCapsState};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlainOptions {
+    pub small_caps: SmallCapsFallback,
+}
 
 #[derive(Debug)]
 pub struct PlainWriter<'a> {
     dest: &'a mut String,
+    options: PlainOptions,
+    small_caps: SmallCapsState,
 }
 
 impl<'a> PlainWriter<'a> {
-    pub fn new(dest: &'a mut String) -> Self {
-        PlainWriter { dest }
+    pub fn new(dest: &'a mut String, options: PlainOptions) -> Self {
+        PlainWriter {
+            dest,
+            options,
+            small_caps: SmallCapsState::default(),
+        }
     }
 }
 
 impl<'a> MarkupWriter for PlainWriter<'a> {
     fn write_escaped(&mut self, text: &str) {
-        self.dest.push_str(text);
+        let text = self.small_caps.apply(self.options.small_caps, text);
+        self.dest.push_str(&text);
+    }
+    fn stack_preorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter() {
+            self.small_caps.push(*cmd);
+        }
     }
-    fn stack_preorder(&mut self, _stack: &[FormatCmd]) {}
 
-    fn stack_postorder(&mut self, _stack: &[FormatCmd]) {}
+    fn stack_postorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter().rev() {
+            self.small_caps.pop(*cmd);
+        }
+    }
 
     fn write_micro(&mut self, micro: &MicroNode, trim_start: bool) {
         use MicroNode::*;
@@ -46,8 +67,10 @@ impl<'a> MarkupWriter for PlainWriter<'a> {
                 self.write_micros(children, false);
                 self.dest.push_str(localized.closing(*is_inner));
             }
-            Formatted(nodes, _cmd) => {
+            Formatted(nodes, cmd) => {
+                self.small_caps.push(*cmd);
                 self.write_micros(nodes, trim_start);
+                self.small_caps.pop(*cmd);
             }
             NoCase(inners) => {
                 self.write_micros(inners, trim_start);
@@ -64,9 +87,15 @@ impl<'a> MarkupWriter for PlainWriter<'a> {
             Text(text) => {
                 self.write_escaped(text.trim_start_if(trim_start));
             }
+            Verbatim(markup) => {
+                self.dest.push_str(markup);
+            }
             Div(display, inlines) => {
                 self.stack_formats(inlines, Formatting::default(), Some(*display));
             }
+            Cite { content, .. } => {
+                self.write_inlines(content, trim_start);
+            }
             Micro(micros) => {
                 self.write_micros(micros, trim_start);
             }
@@ -11,37 +11,67 @@ use crate::output::micro_html::MicroNode;
 use crate::output::FormatCmd;
 use csl::Formatting;
 use super::MaybeTrimStart;
+use super::{SmallCapsFallback, SmallCapsState};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RtfOptions {
+    pub small_caps: SmallCapsFallback,
+}
 
 #[derive(Debug)]
 pub struct RtfWriter<'a> {
     dest: &'a mut String,
+    options: RtfOptions,
+    small_caps: SmallCapsState,
 }
 
 impl<'a> RtfWriter<'a> {
-    pub fn new(dest: &'a mut String) -> Self {
-        RtfWriter { dest }
+    pub fn new(dest: &'a mut String, options: RtfOptions) -> Self {
+        RtfWriter {
+            dest,
+            options,
+            small_caps: SmallCapsState::default(),
+        }
+    }
+
+    /// Opens a group for `cmd`. The group is still opened when the command is being emulated or
+    /// dropped, so that the closing braces always balance.
+    fn open_group(&mut self, cmd: FormatCmd) {
+        self.small_caps.push(cmd);
+        self.dest.push('{');
+        match (cmd, self.options.small_caps) {
+            (FormatCmd::FontVariantSmallCaps, fallback)
+            | (FormatCmd::FontVariantNormal, fallback)
+                if fallback != SmallCapsFallback::Native => {}
+            _ => self.dest.push_str(cmd.rtf_tag()),
+        }
     }
 }
 
 impl<'a> MarkupWriter for RtfWriter<'a> {
     fn write_escaped(&mut self, text: &str) {
-        rtf_escape_into(text, self.dest);
+        let text = self.small_caps.apply(self.options.small_caps, text);
+        rtf_escape_into(&text, self.dest);
     }
     fn stack_preorder(&mut self, stack: &[FormatCmd]) {
         for cmd in stack.iter() {
-            let tag = cmd.rtf_tag();
-            self.dest.push('{');
-            self.dest.push_str(tag);
+            self.open_group(*cmd);
         }
     }
 
     fn stack_postorder(&mut self, stack: &[FormatCmd]) {
         for cmd in stack.iter() {
+            self.small_caps.pop(*cmd);
             if *cmd == FormatCmd::DisplayRightInline {
                 let tlen = self.dest.trim_end_matches(' ').len();
                 self.dest.truncate(tlen);
             }
             self.dest.push('}');
+            // Word etc. line the rest of the entry up with a tab stop, see
+            // BibEntry::paragraph_props in the citeproc crate.
+            if *cmd == FormatCmd::DisplayLeftMargin {
+                self.dest.push_str("\\tab ");
+            }
         }
     }
 
@@ -61,10 +91,9 @@ impl<'a> MarkupWriter for RtfWriter<'a> {
                 self.write_escaped(localized.closing(*is_inner));
             }
             Formatted(nodes, cmd) => {
-                let tag = cmd.rtf_tag();
-                self.dest.push('{');
-                self.dest.push_str(tag);
+                self.open_group(*cmd);
                 self.write_micros(nodes, trim_start);
+                self.small_caps.pop(*cmd);
                 self.dest.push('}');
             }
             NoCase(inners) => {
@@ -80,11 +109,17 @@ impl<'a> MarkupWriter for RtfWriter<'a> {
         use super::InlineElement::*;
         match inline {
             Text(text) => {
-                rtf_escape_into(text.trim_start_if(trim_start), self.dest);
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Verbatim(markup) => {
+                self.dest.push_str(markup);
             }
             Div(display, inlines) => {
                 self.stack_formats(inlines, Formatting::default(), Some(*display))
             }
+            Cite { content, .. } => {
+                self.write_inlines(content, trim_start);
+            }
             Micro(micros) => {
                 self.write_micros(micros, trim_start);
             }
@@ -56,21 +56,27 @@ impl LocalizedQuotes {
         }
     }
 
+    /// Reads the `open-quote`, `close-quote`, `open-inner-quote` and `close-inner-quote` terms,
+    /// which a style can override like any other. Any that are missing (which only happens with
+    /// an incomplete locale) fall back to the English curly quotes.
     pub fn from_locale(locale: &Locale) -> Self {
-        let getter = |qt: QuoteTerm| {
+        let simple = LocalizedQuotes::simple();
+        let getter = |qt: QuoteTerm, fallback: &Atom| {
             locale
                 .simple_terms
                 .get(&SimpleTermSelector::Quote(qt))
-                .unwrap()
-                .singular()
+                .map(|term| Atom::from(term.singular()))
+                .unwrap_or_else(|| fallback.clone())
         };
-        let open_outer = getter(QuoteTerm::OpenQuote);
-        let close_outer = getter(QuoteTerm::CloseQuote);
-        let open_inner = getter(QuoteTerm::OpenInnerQuote);
-        let close_inner = getter(QuoteTerm::CloseInnerQuote);
         LocalizedQuotes {
-            outer: (Atom::from(open_outer), Atom::from(close_outer)),
-            inner: (Atom::from(open_inner), Atom::from(close_inner)),
+            outer: (
+                getter(QuoteTerm::OpenQuote, &simple.outer.0),
+                getter(QuoteTerm::CloseQuote, &simple.outer.1),
+            ),
+            inner: (
+                getter(QuoteTerm::OpenInnerQuote, &simple.inner.0),
+                getter(QuoteTerm::CloseInnerQuote, &simple.inner.1),
+            ),
             punctuation_in_quote: locale.options_node.punctuation_in_quote.unwrap_or(false),
         }
     }
@@ -106,7 +112,15 @@ pub enum FormatCmd {
 use std::hash::Hash;
 
 pub trait OutputFormat: Send + Sync + Clone + Default + PartialEq + std::fmt::Debug {
-    type Input: std::fmt::Debug + DeserializeOwned + Default + Clone + Send + Sync + Eq + Hash;
+    type Input: std::fmt::Debug
+        + DeserializeOwned
+        + Serialize
+        + Default
+        + Clone
+        + Send
+        + Sync
+        + Eq
+        + Hash;
     type Build: std::fmt::Debug + Default + Clone + Send + Sync + Eq;
     type Output: Default + Clone + Send + Sync + Eq + Serialize;
     type BibMeta: Serialize;
@@ -136,6 +150,14 @@ pub trait OutputFormat: Send + Sync + Clone + Default + PartialEq + std::fmt::De
     fn join_delim(&self, a: Self::Build, delim: &str, b: Self::Build) -> Self::Build;
 
     fn is_empty(&self, a: &Self::Build) -> bool;
+
+    /// Lets code that is generic over the output format use work that was cached as
+    /// [Markup](markup::Markup). Only Markup itself can, so this is None for anything else.
+    #[cfg(feature = "markup")]
+    fn reuse_markup(&self, _built: &[markup::InlineElement]) -> Option<Self::Build> {
+        None
+    }
+
     fn output(&self, intermediate: Self::Build, punctuation_in_quote: bool) -> Self::Output {
         self.output_in_context(
             intermediate,
@@ -12,14 +12,17 @@ use fnv::FnvHashMap;
 
 use super::date::DateOrRange;
 use super::names::Name;
-use crate::NumberLike;
-use csl::{Atom, CslType, DateVariable, Lang, NameVariable, NumberVariable, Variable};
+use crate::{NumberLike, SmartString};
+use csl::{Affixes, Atom, CslType, DateVariable, Lang, NameVariable, NumberVariable, Variable};
 
 // We're saving copies and allocations by not using String here.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Reference {
     pub id: Atom,
     pub csl_type: CslType,
+    /// The `type` from the input, if it wasn't one we know about. `csl_type` then holds whatever
+    /// the unknown type was mapped to, but this is kept so the reference can be written back out.
+    pub original_type: Option<String>,
     pub language: Option<Lang>,
 
     // each field type gets its own hashmap, as its data type is different
@@ -30,6 +33,9 @@ pub struct Reference {
     pub number: FnvHashMap<NumberVariable, NumberLike>,
     pub name: FnvHashMap<NameVariable, Vec<Name>>,
     pub date: FnvHashMap<DateVariable, DateOrRange>,
+
+    /// Alternate versions of ordinary variables, from a Juris-M `multi` block.
+    pub multi: Multilingual,
 }
 
 impl Reference {
@@ -37,11 +43,110 @@ impl Reference {
         Reference {
             id,
             csl_type,
+            original_type: None,
             language: None,
             ordinary: FnvHashMap::default(),
             number: FnvHashMap::default(),
             name: FnvHashMap::default(),
             date: FnvHashMap::default(),
+            multi: Multilingual::default(),
+        }
+    }
+
+    /// Rewrites the ordinary variables that have multilingual variants, according to `prefs`.
+    ///
+    /// When a secondary form is requested and available, it is appended, wrapped in
+    /// [LangPrefs::secondary_affixes]: by default `Title [Translated Title]`.
+    pub fn apply_lang_prefs(&mut self, prefs: &LangPrefs) {
+        for (var, variants) in self.multi.keys.iter() {
+            let original = self.ordinary.get(var);
+            let pick = |form: MultiForm| {
+                let tags = match form {
+                    MultiForm::Original => return original,
+                    MultiForm::Transliterated => &prefs.transliterations,
+                    MultiForm::Translated => &prefs.translations,
+                };
+                tags.iter().find_map(|tag| variants.get(tag))
+            };
+            let primary = match pick(prefs.primary).or(original) {
+                Some(p) => p,
+                None => continue,
+            };
+            let mut value = primary.clone();
+            if let Some(secondary) = prefs.secondary.and_then(pick) {
+                if secondary != primary {
+                    let affixes = &prefs.secondary_affixes;
+                    value.push_str(&affixes.prefix);
+                    value.push_str(secondary);
+                    value.push_str(&affixes.suffix);
+                }
+            }
+            self.ordinary.insert(*var, value);
+        }
+    }
+}
+
+/// Juris-M multilingual field data, i.e. the `multi` block in CSL-JSON.
+///
+/// Only ordinary variables are supported. Names have no variants: the `multi` blocks on individual
+/// names are ignored, and names always render as written.
+///
+/// Language tags are kept as they were written (e.g. `ja-Latn`), as they frequently carry script
+/// subtags that [Lang] does not model.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Multilingual {
+    /// `multi.main`: the language each variable's primary value is written in.
+    pub main: FnvHashMap<Variable, SmartString>,
+    /// `multi._keys`: transliterations and translations of each variable, keyed by language tag.
+    pub keys: FnvHashMap<Variable, FnvHashMap<SmartString, String>>,
+}
+
+/// Which version of a multilingual field to render.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MultiForm {
+    /// The value of the variable itself
+    Original,
+    /// The first available variant from [LangPrefs::transliterations]
+    Transliterated,
+    /// The first available variant from [LangPrefs::translations]
+    Translated,
+}
+
+impl Default for MultiForm {
+    fn default() -> Self {
+        MultiForm::Original
+    }
+}
+
+/// Rendering policy for multilingual fields, along the lines of citeproc-js'
+/// `setLangPrefsForCites` and `setLangTagsForCslTransliteration`/`...Translation`.
+///
+/// The default renders only the original values, i.e. ignores the `multi` block entirely. Only
+/// ordinary variables (titles and the like) are affected; see [Multilingual].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LangPrefs {
+    pub primary: MultiForm,
+    /// Rendered after the primary form, if it is available and different
+    pub secondary: Option<MultiForm>,
+    /// Put around the secondary form. By default, `" ["` and `"]"`.
+    pub secondary_affixes: Affixes,
+    /// Language tags to look for, in order of preference, e.g. `["ja-Latn"]`
+    pub transliterations: Vec<SmartString>,
+    /// Language tags to look for, in order of preference, e.g. `["en", "en-US"]`
+    pub translations: Vec<SmartString>,
+}
+
+impl Default for LangPrefs {
+    fn default() -> Self {
+        LangPrefs {
+            primary: MultiForm::default(),
+            secondary: None,
+            secondary_affixes: Affixes {
+                prefix: " [".into(),
+                suffix: "]".into(),
+            },
+            transliterations: Vec::new(),
+            translations: Vec::new(),
         }
     }
 }
@@ -327,11 +327,10 @@ where
                     .map_or(false, |bt| unic_ucd_category::GeneralCategory::of(bt).is_letter())
                });
         if wordy {
-            let before = &s[..ix].chars().rev().filter(|c| !c.is_whitespace()).nth(0);
-            let follows_colon = *before == Some(':')
-                || *before == Some('?')
-                || *before == Some('!')
-                || *before == Some('.');
+            let follows_colon = matches!(
+                char_before_word(&s[..ix]),
+                Some(':') | Some('?') | Some('!') | Some('.')
+            );
             let rest = &s[ix + substr.len()..];
             let is_last = is_last && (rest.is_empty() || !is_word(rest));
             let no_stopword = is_first || is_last || follows_colon;
@@ -351,6 +350,22 @@ where
     acc
 }
 
+/// The last character before a word, skipping whitespace and opening quotes or brackets. Chicago
+/// capitalizes the first word after a colon, and that includes `Title: “the Subtitle”`.
+fn char_before_word(before: &str) -> Option<char> {
+    before
+        .chars()
+        .rev()
+        .find(|&c| !c.is_whitespace() && !is_opening_punc(c))
+}
+
+fn is_opening_punc(c: char) -> bool {
+    matches!(
+        c,
+        '"' | '\'' | '\u{201C}' | '\u{2018}' | '\u{00AB}' | '\u{2039}' | '(' | '['
+    )
+}
+
 fn transform_first_word<'a>(s: String, transform: impl Fn(&str) -> SmartCow) -> String {
     let mut bounds = WordBoundIndices::new(&s);
     while let Some((ix, bound)) = bounds.next() {
@@ -419,7 +434,8 @@ impl IngestOptions {
                     inlines: content, ..
                 }
                 | InlineElement::Div(_, content)
-                | InlineElement::Anchor { content, .. } => {
+                | InlineElement::Anchor { content, .. }
+                | InlineElement::Cite { content, .. } => {
                     seen_one = self.apply_text_case_inner(content.as_mut(), seen_one, is_uppercase)
                         || seen_one;
                 }
@@ -435,6 +451,7 @@ impl IngestOptions {
                 InlineElement::Formatted(content, _) => {
                     seen_one = seen_one || self.contains_word(content.as_ref());
                 }
+                InlineElement::Verbatim(_) => {}
             }
         }
         seen_one
@@ -539,7 +556,11 @@ fn any_inlines<F: Fn(&str) -> bool + Copy>(f: F, invert: bool, inlines: &[Inline
         | InlineElement::Anchor {
             content: inlines, ..
         }
+        | InlineElement::Cite {
+            content: inlines, ..
+        }
         | InlineElement::Formatted(inlines, _) => any_inlines(f, invert, inlines.as_ref()) ^ invert,
+        InlineElement::Verbatim(_) => false,
     }) ^ invert
 }
 
@@ -553,6 +574,50 @@ fn any_micros<F: Fn(&str) -> bool + Copy>(f: F, invert: bool, micros: &[MicroNod
     }) ^ invert
 }
 
+#[test]
+fn title_case_punctuation() {
+    fn title(x: &str) -> std::string::String {
+        transform_title_case(x, false, true).to_string()
+    }
+    // Apostrophes inside a word do not start a new one
+    assert_eq!(title("don't stop believing"), "Don't Stop Believing");
+    assert_eq!(title("don\u{2019}t stop"), "Don\u{2019}t Stop");
+    assert_eq!(title("the writer's craft"), "The Writer's Craft");
+    // Stopwords are capitalized after a colon, even inside quotes
+    assert_eq!(title("a history: of the world"), "A History: Of the World");
+    assert_eq!(
+        title("a history: \u{201C}the world\u{201D}"),
+        "A History: \u{201C}The World\u{201D}"
+    );
+    assert_eq!(title("a history: (the world)"), "A History: (The World)");
+    // But not just because they are quoted
+    assert_eq!(
+        title("on \u{201C}the world\u{201D}"),
+        "On \u{201C}the World\u{201D}"
+    );
+}
+
+#[test]
+fn title_case_nocase() {
+    let options = IngestOptions {
+        text_case: TextCase::Title,
+        is_english: true,
+        ..Default::default()
+    };
+    let mut micros = MicroNode::parse(
+        r#"a study of <span class="nocase">iPhone</span> users"#,
+        &Default::default(),
+    );
+    options.apply_text_case_micro(&mut micros);
+    assert_eq!(
+        micros,
+        MicroNode::parse(
+            r#"A Study of <span class="nocase">iPhone</span> Users"#,
+            &Default::default()
+        )
+    );
+}
+
 #[test]
 fn test_any_micros() {
     fn parse(x: &str) -> Vec<MicroNode> {
@@ -165,6 +165,17 @@ test_parse!(
         assert_key_deref!(r.ordinary, TitleShort, Some("title"));
     }
 );
+test_parse!(
+    language_normalized,
+    r#" { "id": 1, "language": "en_gb" } "#,
+    |r: Reference| {
+        use csl::{IsoCountry, IsoLang, Lang};
+        assert_eq!(
+            r.language,
+            Some(Lang::Iso(IsoLang::English, Some(IsoCountry::GB)))
+        );
+    }
+);
 test_equiv_all!(
     title_short_shortTitle,
     r#"[
@@ -186,6 +197,23 @@ test_equiv!(ignore_unknown_weird_keys, r#" { "id": 1, "with\"quote": "title" } "
 test_equiv!(ignore_unknown_keys_weird_data, r#" { "id": 1, "asdklfjhhjkl": { "completely": "unrecognizable" } } "# => EMPTY);
 test_equiv!(ignore_unknown_weird_keys_weird_data, r#" { "id": 1, "\"\"\"": { "completely": -0.9999 } } "# => EMPTY);
 
+test_parse!(
+    unknown_type_kept,
+    r#" { "id": 1, "type": "preprint-of-the-future" } "#,
+    |r: Reference| {
+        assert_eq!(r.csl_type, csl::CslType::Article);
+        assert_eq!(r.original_type.as_deref(), Some("preprint-of-the-future"));
+    }
+);
+test_parse!(
+    known_type_not_kept,
+    r#" { "id": 1, "type": "book" } "#,
+    |r: Reference| {
+        assert_eq!(r.csl_type, csl::CslType::Book);
+        assert_eq!(r.original_type, None);
+    }
+);
+
 test_parse!(
     duplicate_keys_ok,
     r#" { "id": 1, "title": "first", "title": "second"} "#,
@@ -199,6 +227,53 @@ test_parse!(
     }
 );
 
+test_parse!(
+    multilingual_keys,
+    r#" {
+        "id": 1,
+        "title": "Nihon no rekishi",
+        "multi": {
+            "main": { "title": "ja-Latn" },
+            "_keys": { "title": { "en": "History of Japan" }, "author": { "en": "Ignored" } }
+        }
+    } "#,
+    |r: Reference| {
+        assert_eq!(
+            r.multi.main.get(&Title).map(|x| x.as_str()),
+            Some("ja-Latn")
+        );
+        let en = r.multi.keys[&Title].get("en").map(|x| x.as_str());
+        assert_eq!(en, Some("History of Japan"));
+        assert_eq!(r.multi.keys.len(), 1);
+    }
+);
+
+#[test]
+fn multilingual_lang_prefs() {
+    let mut r: Reference = serde_json::from_str(
+        r#"{ "id": 1, "title": "Nihon", "multi": { "_keys": { "title": { "en": "Japan" } } } }"#,
+    )
+    .unwrap();
+    let prefs = LangPrefs {
+        secondary: Some(MultiForm::Translated),
+        translations: vec!["en".into()],
+        ..Default::default()
+    };
+    let mut bracketed = r.clone();
+    bracketed.apply_lang_prefs(&prefs);
+    assert_key_deref!(bracketed.ordinary, Title, Some("Nihon [Japan]"));
+
+    let prefs = LangPrefs {
+        secondary_affixes: csl::Affixes {
+            prefix: " / ".into(),
+            suffix: "".into(),
+        },
+        ..prefs
+    };
+    r.apply_lang_prefs(&prefs);
+    assert_key_deref!(r.ordinary, Title, Some("Nihon / Japan"));
+}
+
 // From the spec
 
 fn parse_errors<'a, T: FromStr>(varnames: &[&'a str]) -> Vec<&'a str> {
@@ -27,14 +27,12 @@ where
         let maybe_leave_unresolved =
             |unresolved: bool, sub_node: NodeId, arena: &mut IrArena<O>| {
                 if unresolved {
-                    let gv = arena.get(sub_node).unwrap().get().1;
-                    let cond = arena.new_node((
-                        IR::ConditionalDisamb(ConditionalDisambIR {
-                            choose: self.clone(),
-                            done: false,
-                        }),
-                        gv, // TODO: should this be unresolved?
-                    ));
+                    let cond_ir = ConditionalDisambIR {
+                        choose: self.clone(),
+                        done: false,
+                    };
+                    let gv = cond_ir.group_vars(arena.get(sub_node).unwrap().get());
+                    let cond = arena.new_node((IR::ConditionalDisamb(cond_ir), gv));
                     cond.append(sub_node, arena);
                     cond
                 } else {
@@ -270,11 +268,14 @@ where
             Cond::HasMonthOrSeason(dvar) => checker.has_month_or_season(*dvar),
             Cond::HasDay(dvar) => checker.has_day(*dvar),
 
+            Cond::Jurisdiction(_) | Cond::SubJurisdiction(_) if !features.jurisdictions => {
+                return None;
+            }
+            Cond::Jurisdiction(list) => list.split_whitespace().any(|j| checker.in_jurisdiction(j)),
+            Cond::SubJurisdiction(n) => checker.subjurisdiction_count() >= *n,
+
             // Not implemented
-            Cond::Context(_)
-            | Cond::IsPlural(_)
-            | Cond::Jurisdiction(_)
-            | Cond::SubJurisdiction(_) => {
+            Cond::Context(_) | Cond::IsPlural(_) => {
                 log::warn!("unimplemented choose condition: {:?}", cond);
                 return None;
             }
@@ -310,6 +311,9 @@ impl CondChecker for UselessCondChecker {
     fn position(&self) -> Option<Position> {
         None
     }
+    fn jurisdiction(&self) -> Option<&str> {
+        None
+    }
     fn features(&self) -> &csl::version::Features {
         lazy_static::lazy_static! {
             static ref NO_FEATURES: Features = {
@@ -333,9 +337,25 @@ pub trait CondChecker {
     fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange>;
     fn position(&self) -> Option<Position>;
     fn features(&self) -> &Features;
+    /// The CSL-M `jurisdiction` variable, a hierarchy separated by colons, e.g. `us:ca`.
+    fn jurisdiction(&self) -> Option<&str>;
+    /// Whether the item is in `jurisdiction` or any of its sub-jurisdictions, so `us` covers an
+    /// item from `us:ca`, but not the other way around.
+    fn in_jurisdiction(&self, jurisdiction: &str) -> bool {
+        self.jurisdiction().map_or(false, |item| {
+            item.strip_prefix(jurisdiction)
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with(':'))
+        })
+    }
+    /// How many levels below the top the item's jurisdiction goes; `us:ca` has one.
+    fn subjurisdiction_count(&self) -> u32 {
+        self.jurisdiction()
+            .map_or(0, |item| item.matches(':').count() as u32)
+    }
     fn has_year_only(&self, dvar: DateVariable) -> bool {
         self.get_date(dvar)
-            .map(|dor| match dor {
+            .and_then(DateOrRange::structured)
+            .map(|dor| match dor.as_ref() {
                 DateOrRange::Single(d) => d.month == 0 && d.day == 0,
                 DateOrRange::Range(d1, d2) => {
                     d1.month == 0 && d1.day == 0 && d2.month == 0 && d2.day == 0
@@ -346,7 +366,8 @@ pub trait CondChecker {
     }
     fn has_month_or_season(&self, dvar: DateVariable) -> bool {
         self.get_date(dvar)
-            .map(|dor| match dor {
+            .and_then(DateOrRange::structured)
+            .map(|dor| match dor.as_ref() {
                 DateOrRange::Single(d) => d.month != 0,
                 DateOrRange::Range(d1, d2) => {
                     // XXX: is OR the right operator here?
@@ -358,7 +379,8 @@ pub trait CondChecker {
     }
     fn has_day(&self, dvar: DateVariable) -> bool {
         self.get_date(dvar)
-            .map(|dor| match dor {
+            .and_then(DateOrRange::structured)
+            .map(|dor| match dor.as_ref() {
                 DateOrRange::Single(d) => d.day != 0,
                 DateOrRange::Range(d1, d2) => {
                     // XXX: is OR the right operator here?
@@ -8,6 +8,7 @@ use crate::prelude::*;
 
 use super::DisambPass;
 use crate::choose::CondChecker;
+use crate::db::TextCaseLocales;
 use crate::SmartString;
 use citeproc_io::output::markup::Markup;
 use citeproc_io::{Cite, DateOrRange, Locator, Name, NumericValue, Reference};
@@ -29,6 +30,8 @@ pub struct CiteContext<
     pub cite_id: Option<CiteId>,
     pub style: &'c Style,
     pub locale: &'c Locale,
+    /// From `locale`, looked up once rather than for every quoted element
+    pub quotes: &'c LocalizedQuotes,
     pub name_citation: Arc<NameEl>,
     pub names_delimiter: Option<SmartString>,
 
@@ -56,6 +59,8 @@ pub struct CiteContext<
     /// et-al-subsequent-* are used. Also, should not be reference-specific, so none of the
     /// normally-dependent variables can be used.)
     pub year_suffix: Option<u32>,
+
+    pub text_case_locales: TextCaseLocales,
 }
 
 use std::fmt;
@@ -85,6 +90,7 @@ impl<'c, O: OutputFormat, I: OutputFormat> CiteContext<'c, O, I> {
             cite: self.cite,
             style: self.style,
             locale: self.locale,
+            quotes: self.quotes,
             name_citation: self.name_citation.clone(),
             names_delimiter: self.names_delimiter.clone(),
             position: self.position,
@@ -93,6 +99,7 @@ impl<'c, O: OutputFormat, I: OutputFormat> CiteContext<'c, O, I> {
             in_bibliography: self.in_bibliography,
             sort_key: self.sort_key.clone(),
             year_suffix: self.year_suffix,
+            text_case_locales: self.text_case_locales,
         }
     }
 }
@@ -107,6 +114,9 @@ impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for CiteContext<'a, O,
     fn locale(&self) -> &Locale {
         self.locale
     }
+    fn quotes(&self) -> &LocalizedQuotes {
+        self.quotes
+    }
 
     fn get_number(&self, var: NumberVariable) -> Option<NumericValue<'_>> {
         // TODO: always use the default locale
@@ -122,8 +132,8 @@ impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for CiteContext<'a, O,
                 .cite
                 .locators
                 .as_ref()
-                // You'd need new CSL syntax to render more than one locator properly.
-                // For now we'll just ignore any more than the one.
+                // Only the first one is a "variable"; with the multiple-locators feature, the rest
+                // are appended when rendering it (see Renderer::number).
                 .and_then(|ls| ls.single())
                 .map(Locator::value)
                 .map(NumericValue::from_localized(and_term)),
@@ -139,6 +149,7 @@ pub trait RenderContext {
     fn style(&self) -> &Style;
     fn reference(&self) -> &Reference;
     fn locale(&self) -> &Locale;
+    fn quotes(&self) -> &LocalizedQuotes;
     fn get_number(&self, var: NumberVariable) -> Option<NumericValue<'_>>;
 
     fn cite_lang(&self) -> Option<&Lang> {
@@ -244,6 +255,12 @@ where
     fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange> {
         self.reference.date.get(&dvar)
     }
+    fn jurisdiction(&self) -> Option<&str> {
+        self.reference
+            .ordinary
+            .get(&Variable::Jurisdiction)
+            .map(|j| j.as_str())
+    }
     fn position(&self) -> Option<Position> {
         if self.in_bibliography {
             return None;
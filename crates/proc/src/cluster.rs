@@ -13,7 +13,7 @@ use csl::Collapse;
 
 use crate::helpers::slice_group_by::{group_by, group_by_mut};
 
-use crate::db::IrGen;
+use crate::db::{CiteCapitalization, IrGen};
 use crate::ir::transforms;
 use crate::prelude::*;
 
@@ -21,10 +21,33 @@ mod layout;
 use layout::DelimKind;
 pub(crate) use layout::WhichStream;
 
+/// `layout_delimiter` replaces the delimiter between cites from the style's citation `<layout>`,
+/// for previews only.
 pub fn built_cluster_before_output(
     db: &dyn IrDatabase,
     cluster_id: ClusterId,
     fmt: &Markup,
+    layout_delimiter: Option<&str>,
+) -> MarkupBuild {
+    build_cluster(db, cluster_id, fmt, layout_delimiter, false)
+}
+
+/// Like [built_cluster_before_output], but each cite in the citation layout is wrapped in an
+/// `InlineElement::Cite` with its index in the cluster.
+pub(crate) fn built_cluster_with_cite_tags(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+) -> MarkupBuild {
+    build_cluster(db, cluster_id, fmt, None, true)
+}
+
+fn build_cluster(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+    layout_delimiter: Option<&str>,
+    tag_cites: bool,
 ) -> MarkupBuild {
     let cite_ids = if let Some(x) = db.cluster_cites_sorted(cluster_id) {
         x
@@ -33,12 +56,18 @@ pub fn built_cluster_before_output(
     };
     let style = db.style();
     let sorted_refs_arc = db.sorted_refs();
+    let verbatim_affixes = db.verbatim_affixes();
     let mut irs: Vec<_> = cite_ids
         .iter()
         .map(|&id| {
             let gen4 = db.ir_fully_disambiguated(id);
             let position = db.cite_position(id).0;
             let cite = id.lookup(db);
+            let cite = if verbatim_affixes {
+                cite
+            } else {
+                escape_verbatim_affixes(cite)
+            };
             let (_keys, citation_numbers_by_id) = &*sorted_refs_arc;
             let cnum = citation_numbers_by_id.get(&cite.ref_id).cloned();
             CiteInCluster::new(id, cite, position, cnum.map(|x| x.get()), gen4, &fmt)
@@ -69,34 +98,47 @@ pub fn built_cluster_before_output(
     }
 
     // Cite capitalization
-    // TODO: allow clients to pass a flag to prevent this (on ix==0) when a cluster is in the
-    // middle of an existing footnote, and isn't preceded by a period (or however else a client
-    // wants to judge that).
-    // We capitalize all cites whose prefixes end with full stops.
-    if style.class != csl::StyleClass::InText {
+    // We capitalize all cites whose prefixes end with full stops, and the first cite in a note if
+    // it has no prefix. By default only note styles do this; clients that judge this themselves
+    // (e.g. a cluster in the middle of an existing footnote) can turn it off entirely.
+    let is_note = style.class != csl::StyleClass::InText;
+    let capitalize = match db.cite_capitalization() {
+        CiteCapitalization::NoteStyles => is_note,
+        CiteCapitalization::Always => true,
+        CiteCapitalization::Never => false,
+    };
+    if capitalize {
         for (ix, cite) in irs.iter_mut().enumerate() {
             if cite
                 .prefix_parsed
                 .as_ref()
-                .map_or(ix == 0, |pre| fmt.ends_with_full_stop(pre))
+                .map_or(is_note && ix == 0, |pre| fmt.ends_with_full_stop(pre))
             {
-                // dbg!(ix, prefix_parsed);
                 let gen_mut = Arc::make_mut(&mut cite.gen4);
                 gen_mut.tree_mut().capitalize_first_term_of_cluster(&fmt);
             }
         }
     }
 
+    let raw_affixes = db.raw_cite_affixes();
     let default_locale = db.default_locale();
-    let citation_delims = layout::LayoutDelimiters::from_citation(&style.citation);
+    let mut citation_delims = layout::LayoutDelimiters::from_citation(&style.citation);
+    if let Some(delim) = layout_delimiter {
+        citation_delims.layout_delim = delim;
+    }
     let intext_delimiters = layout::LayoutDelimiters::from_intext(
         style.intext.as_ref(),
         &style.citation,
         &default_locale,
     );
 
-    let mut citation_stream = layout::LayoutStream::new(irs.len() * 2, citation_delims, fmt);
-    let mut intext_stream = layout::LayoutStream::new(0, intext_delimiters, fmt);
+    let unsorted_cite_ids = db.cluster_cites(cluster_id);
+    let mut citation_stream =
+        layout::LayoutStream::new(irs.len() * 2, citation_delims, fmt, raw_affixes);
+    if tag_cites {
+        citation_stream.tag_cites(&unsorted_cite_ids);
+    }
+    let mut intext_stream = layout::LayoutStream::new(0, intext_delimiters, fmt, raw_affixes);
 
     // render the intext stream
     let intext_authors = group_by(&irs, |a, b| a.by_name() == b.by_name())
@@ -167,6 +209,23 @@ pub fn built_cluster_before_output(
     fmt.seq(seq)
 }
 
+/// Turns a cite's `{ verbatim }` prefix or suffix into a plain one, so it is escaped like any
+/// other. Used unless the processor has been told to trust verbatim affixes.
+fn escape_verbatim_affixes(mut cite: Arc<Cite<Markup>>) -> Arc<Cite<Markup>> {
+    let is_verbatim =
+        |affix: &Option<MarkupInput>| affix.as_ref().map_or(false, |a| a.is_verbatim());
+    if !is_verbatim(&cite.prefix) && !is_verbatim(&cite.suffix) {
+        return cite;
+    }
+    let cite_mut = Arc::make_mut(&mut cite);
+    for affix in vec![&mut cite_mut.prefix, &mut cite_mut.suffix] {
+        if let Some(input @ MarkupInput::Verbatim { .. }) = affix {
+            *input = MarkupInput::Text(input.as_str().into());
+        }
+    }
+    cite
+}
+
 /// A wrapper for Option where `a == b` evaluates to false if either is empty
 ///
 /// Implements PartialEq, but does not implement Eq, of course.
@@ -295,9 +354,12 @@ impl CiteInCluster<Markup> {
         gen4: Arc<IrGen>,
         fmt: &Markup,
     ) -> Self {
-        let prefix_parsed = cite.prefix.as_opt_str().map(|p| {
+        let prefix_parsed = cite.prefix.as_ref().map(|p| {
+            if p.is_verbatim() {
+                return fmt.verbatim(p.as_str());
+            }
             fmt.ingest(
-                p,
+                p.as_str(),
                 &IngestOptions {
                     is_external: true,
                     ..Default::default()
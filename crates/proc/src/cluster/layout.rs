@@ -6,6 +6,7 @@
 
 use super::CiteInCluster;
 use crate::prelude::*;
+use citeproc_io::output::markup::InlineElement;
 use citeproc_io::TrimInPlace;
 
 #[derive(Debug)]
@@ -13,16 +14,44 @@ pub(crate) struct LayoutStream<'a> {
     chunks: Vec<Chunk>,
     delimiters: LayoutDelimiters<'a>,
     fmt: &'a Markup,
+    /// Write cite prefixes and suffixes without adding any spaces between them and the cite
+    raw_affixes: bool,
+    /// The cluster's cites in their original order, if each cite should be wrapped in an
+    /// `InlineElement::Cite` with its index in this list.
+    tag_cites: Option<&'a [CiteId]>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Chunk {
-    Cite { built: MarkupBuild },
-    Prefix(SmartString),
-    Suffix(SmartString),
+    Cite {
+        built: MarkupBuild,
+        cite: Option<u32>,
+    },
+    Prefix(Affix),
+    Suffix(Affix),
     Delim(DelimKind),
 }
 
+/// A cite's prefix or suffix, before it is ingested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Affix {
+    text: SmartString,
+    /// Came from a `MarkupInput::Verbatim`, so is output as-is instead of being parsed.
+    verbatim: bool,
+    /// The index of the cite it belongs to, when cites are tagged
+    cite: Option<u32>,
+}
+
+impl Affix {
+    fn new(input: &MarkupInput, text: SmartString) -> Self {
+        Affix {
+            text,
+            verbatim: input.is_verbatim(),
+            cite: None,
+        }
+    }
+}
+
 impl Chunk {
     fn as_delim_mut(&mut self) -> Option<&mut DelimKind> {
         match self {
@@ -32,36 +61,51 @@ impl Chunk {
     }
     fn as_prefix_mut(&mut self) -> Option<&mut SmartString> {
         match self {
-            Chunk::Prefix(d) => Some(d),
+            Chunk::Prefix(d) => Some(&mut d.text),
             _ => None,
         }
     }
     fn as_suffix_mut(&mut self) -> Option<&mut SmartString> {
         match self {
-            Chunk::Suffix(d) => Some(d),
+            Chunk::Suffix(d) => Some(&mut d.text),
             _ => None,
         }
     }
 }
 
 impl<'a> LayoutStream<'a> {
-    pub(crate) fn new(cap: usize, delimiters: LayoutDelimiters<'a>, fmt: &'a Markup) -> Self {
+    pub(crate) fn new(
+        cap: usize,
+        delimiters: LayoutDelimiters<'a>,
+        fmt: &'a Markup,
+        raw_affixes: bool,
+    ) -> Self {
         Self {
             chunks: Vec::with_capacity(cap),
             delimiters,
             fmt,
+            raw_affixes,
+            tag_cites: None,
         }
     }
+
+    /// Wraps each cite written with [LayoutStream::write_flat], and its affixes, in an
+    /// `InlineElement::Cite` carrying its index in `cite_ids`.
+    pub(crate) fn tag_cites(&mut self, cite_ids: &'a [CiteId]) {
+        self.tag_cites = Some(cite_ids);
+    }
+
     pub(crate) fn write_interspersed(
         &mut self,
         iter: impl IntoIterator<Item = MarkupBuild>,
         delim_kind: DelimKind,
     ) {
         use itertools::Itertools;
-        self.chunks.extend(Itertools::intersperse(
-            iter.into_iter().map(|built| Chunk::Cite { built }),
-            Chunk::Delim(delim_kind),
-        ))
+        let cites = iter
+            .into_iter()
+            .map(|built| Chunk::Cite { built, cite: None });
+        self.chunks
+            .extend(Itertools::intersperse(cites, Chunk::Delim(delim_kind)))
     }
 
     pub(crate) fn overwrite_and_position(&mut self) {
@@ -86,13 +130,50 @@ impl<'a> LayoutStream<'a> {
         }
     }
 
+    /// A layout that wraps the cluster in brackets, next to a first prefix or last suffix that
+    /// brings its own, would otherwise give `((see Smith 2000)`. The layout's bracket wins. A
+    /// bracket is only taken from an affix that doesn't close it itself, like `(see` or `at 5)`,
+    /// so a suffix like `(n. 3)` is left alone.
+    pub(crate) fn merge_layout_brackets(&mut self) {
+        let affixes = match self.delimiters.affixes {
+            Some(affixes) => affixes,
+            None => return,
+        };
+        let layout_open = affixes.prefix.trim_end().chars().next_back();
+        if let Some((open, close)) = layout_open.and_then(bracket_pair) {
+            if let Some(Chunk::Prefix(pre)) = self.chunks.first_mut() {
+                if !pre.verbatim && pre.text.starts_with(open) && unbalanced(&pre.text, open, close)
+                {
+                    pre.text = SmartString::from(pre.text[open.len_utf8()..].trim_start());
+                }
+            }
+        }
+        let layout_close = affixes.suffix.trim_start().chars().next();
+        if let Some((open, close)) = layout_close.and_then(closing_bracket_pair) {
+            if let Some(Chunk::Suffix(suf)) = self.chunks.last_mut() {
+                if !suf.verbatim && suf.text.ends_with(close) && unbalanced(&suf.text, close, open)
+                {
+                    let end = suf.text.len() - close.len_utf8();
+                    suf.text = SmartString::from(suf.text[..end].trim_end());
+                }
+            }
+        }
+    }
+
     pub(crate) fn write_flat(
         &mut self,
         single: &CiteInCluster<Markup>,
         override_delim_kind: Option<DelimKind>,
     ) {
-        let (pre, built, suf) = flatten_with_affixes(single, self.fmt);
-        self.write_cite(pre, built, suf);
+        let (mut pre, built, mut suf) = flatten_with_affixes(single, self.fmt, self.raw_affixes);
+        let cite = self
+            .tag_cites
+            .and_then(|ids| ids.iter().position(|&id| id == single.cite_id))
+            .map(|index| index as u32);
+        for affix in pre.iter_mut().chain(suf.iter_mut()) {
+            affix.cite = cite;
+        }
+        self.write_cite(pre, built, cite, suf);
         self.write_delim(override_delim_kind.or(single.own_delimiter));
     }
 
@@ -100,18 +181,19 @@ impl<'a> LayoutStream<'a> {
     /// replace them with more appropriate ones later
     pub(crate) fn write_cite(
         &mut self,
-        prefix: Option<SmartString>,
+        prefix: Option<Affix>,
         built: MarkupBuild,
-        suffix: Option<SmartString>,
+        cite: Option<u32>,
+        suffix: Option<Affix>,
     ) {
         if let Some(pre) = prefix {
             // XXX: should also maybe rewrite a delimiter to be AfterCollapseDelimiter if it's CiteGroup
-            if starts_punc(pre.as_ref()) {
+            if starts_punc(pre.text.as_ref()) {
                 self.pop_delim();
             }
             self.chunks.push(Chunk::Prefix(pre))
         }
-        self.chunks.push(Chunk::Cite { built });
+        self.chunks.push(Chunk::Cite { built, cite });
         if let Some(suf) = suffix {
             self.chunks.push(Chunk::Suffix(suf))
         }
@@ -126,7 +208,7 @@ impl<'a> LayoutStream<'a> {
             return;
         };
         let push_chunk = match self.chunks.last_mut() {
-            Some(Chunk::Suffix(a)) => !ends_punc(&a),
+            Some(Chunk::Suffix(a)) => !ends_punc(&a.text),
             Some(Chunk::Prefix(_)) => true,
             Some(Chunk::Cite { .. }) => true,
             Some(Chunk::Delim(d)) => {
@@ -155,6 +237,7 @@ impl<'a> LayoutStream<'a> {
         self.pop_delim();
         self.overwrite_and_position();
         self.trim_first_last_affixes();
+        self.merge_layout_brackets();
         if self.chunks.is_empty() {
             return None;
         }
@@ -164,13 +247,34 @@ impl<'a> LayoutStream<'a> {
             is_external: true,
             ..Default::default()
         };
-        let seq = self.chunks.into_iter().filter_map(|x| match x {
-            Chunk::Cite { built, .. } => Some(built),
-            Chunk::Prefix(s) if !s.is_empty() => Some(fmt.ingest(&s, &external)),
-            Chunk::Suffix(s) if !s.is_empty() => Some(fmt.ingest(&s, &external)),
-            Chunk::Delim(d) => delimiters.delim(d).map(|x| fmt.plain(x)),
+        let pieces = self.chunks.into_iter().filter_map(|x| match x {
+            Chunk::Cite { built, cite } => Some((cite, built)),
+            Chunk::Prefix(a) | Chunk::Suffix(a) if a.verbatim => {
+                Some((a.cite, fmt.verbatim(&a.text)))
+            }
+            Chunk::Prefix(a) | Chunk::Suffix(a) if !a.text.is_empty() => {
+                Some((a.cite, fmt.ingest(&a.text, &external)))
+            }
+            Chunk::Delim(d) => delimiters.delim(d).map(|x| (None, fmt.plain(x))),
             _ => None,
         });
+        use itertools::Itertools;
+        // Untagged pieces are left as they are, so this is just a concatenation unless the cites
+        // are being tagged.
+        let grouped = pieces.group_by(|(cite, _)| *cite);
+        let seq: Vec<MarkupBuild> = grouped
+            .into_iter()
+            .flat_map(|(cite, group)| {
+                let builds = group.map(|(_, built)| built);
+                match cite {
+                    Some(index) => vec![vec![InlineElement::Cite {
+                        index,
+                        content: fmt.seq(builds),
+                    }]],
+                    None => builds.collect(),
+                }
+            })
+            .collect();
         Some(fmt.with_format(
             fmt.affixed(fmt.seq(seq), delimiters.affixes),
             delimiters.formatting,
@@ -307,7 +411,28 @@ impl<'a> LayoutDelimiters<'a> {
 }
 
 fn is_no_delim_punc(c: char) -> bool {
-    c == ',' || c == '.' || c == '?' || c == '!'
+    c == ',' || c == '.' || c == '?' || c == '!' || c == ';' || c == ':'
+}
+
+fn bracket_pair(open: char) -> Option<(char, char)> {
+    match open {
+        '(' => Some(('(', ')')),
+        '[' => Some(('[', ']')),
+        _ => None,
+    }
+}
+
+fn closing_bracket_pair(close: char) -> Option<(char, char)> {
+    match close {
+        ')' => Some(('(', ')')),
+        ']' => Some(('[', ']')),
+        _ => None,
+    }
+}
+
+/// More of `this` than of `other` in `string`.
+fn unbalanced(string: &str, this: char, other: char) -> bool {
+    string.matches(this).count() > string.matches(other).count()
 }
 fn ends_punc(string: &str) -> bool {
     // got to trim spaces first, people might input a suffix like "hello; "
@@ -329,8 +454,9 @@ fn starts_punc(string: &str) -> bool {
 pub(crate) fn flatten_with_affixes(
     cite_in_cluster: &CiteInCluster<Markup>,
     fmt: &Markup,
-) -> (Option<SmartString>, MarkupBuild, Option<SmartString>) {
-    let CiteInCluster { gen4, .. } = cite_in_cluster;
+    raw_affixes: bool,
+) -> (Option<Affix>, MarkupBuild, Option<Affix>) {
+    let CiteInCluster { gen4, cite, .. } = cite_in_cluster;
     let flattened = gen4.tree_ref().flatten_or_plain(&fmt, CSL_STYLE_ERROR);
 
     // we treat the None cases as empty strings because we would otherwise need a case
@@ -338,12 +464,12 @@ pub(crate) fn flatten_with_affixes(
     //
     let mut pre = cite_in_cluster.prefix_str().map(SmartString::from);
     let mut suf = cite_in_cluster.suffix_str().map(SmartString::from);
-    if let Some(pre) = pre.as_mut() {
+    if let Some(pre) = pre.as_mut().filter(|_| !raw_affixes) {
         if !pre.is_empty() && !pre.ends_with(' ') {
             pre.push(' ');
         }
     }
-    if let Some(suf) = suf.as_mut() {
+    if let Some(suf) = suf.as_mut().filter(|_| !raw_affixes) {
         let suf_first = suf.chars().nth(0);
         if suf_first.map_or(false, |x| {
             x != ' ' && !citeproc_io::output::markup::is_punc(x)
@@ -358,5 +484,7 @@ pub(crate) fn flatten_with_affixes(
             suf.push(' ');
         }
     }
+    let pre = cite.prefix.as_ref().zip(pre).map(|(i, s)| Affix::new(i, s));
+    let suf = cite.suffix.as_ref().zip(suf).map(|(i, s)| Affix::new(i, s));
     (pre, flattened, suf)
 }
@@ -366,21 +366,12 @@ where
     };
     let mut parts = Vec::with_capacity(locale_date.date_parts.len());
     for part in &locale_date.date_parts {
-        let form = WhichDelim::from_form(&part.form);
-        if let Some(localized) = local.date_parts.iter().find(|p| form.matches_form(&p.form)) {
-            let merged = DatePart {
-                form: localized.form,
-                // Attributes for affixes are allowed, unless cs:date calls a localized date format.
-                // So localized.affixes should be ignored.
-                affixes: part.affixes.clone(),
-                formatting: localized.formatting.or(part.formatting),
-                text_case: localized.text_case.or(part.text_case),
-                range_delimiter: localized.range_delimiter.clone(),
-            };
-            parts.push(merged);
-        } else {
-            parts.push(part.clone());
-        }
+        let merged = local
+            .date_parts
+            .iter()
+            .find_map(|over| over.apply_to(part))
+            .unwrap_or_else(|| part.clone());
+        parts.push(merged);
     }
     if gen_date.sorting {
         parts.sort_by_key(|part| part.form)
@@ -895,21 +886,20 @@ fn dp_render_string<'c, O: OutputFormat, I: OutputFormat>(
                     || date.day == 1 =>
             {
                 use citeproc_io::NumericToken;
-                // The 'target noun' is the month term.
-                MonthTerm::from_u32(date.month)
-                    .map(|month| locale.get_month_gender(month))
-                    .map(|gender| {
-                        // the specific number variable does not matter as the tokens do not
-                        // contain any hyphens to pick \u{2013} for
-                        render_ordinal(
-                            &[NumericToken::Num(date.day)],
-                            locale,
-                            NumberVariable::Number,
-                            None,
-                            gender,
-                            false,
-                        )
-                    })
+                // The 'target noun' is the month term. Without a month (or with a season), there
+                // is nothing to agree with, so use the neuter forms.
+                let gender = MonthTerm::from_u32(date.month)
+                    .map_or(Gender::Neuter, |month| locale.get_month_gender(month));
+                // the specific number variable does not matter as the tokens do not
+                // contain any hyphens to pick \u{2013} for
+                Some(render_ordinal(
+                    &[NumericToken::Num(date.day)],
+                    locale,
+                    NumberVariable::Number,
+                    None,
+                    gender,
+                    false,
+                ))
             }
             // Numeric or ordinal with limit-day-ordinals-to-day-1
             _ => Some(smart_format!("{}", date.day)),
@@ -7,27 +7,46 @@
 // For the query group macro expansion
 #![allow(clippy::large_enum_variant)]
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use std::sync::Arc;
 
 use crate::cluster;
 use crate::disamb::names::{replace_single_child, NameDisambPass};
-use crate::disamb::{Dfa, DisambName, DisambNameData, EdgeData, FreeCondSets};
+use crate::disamb::{
+    Dfa, DisambName, DisambNameData, EdgeData, FormattedNameKey, FormattedNameListKey, FreeCondSets,
+};
 use crate::prelude::*;
 use crate::sort::BibNumber;
-use crate::{CiteContext, DisambPass, IrState, Proc, IR};
+use crate::{CiteContext, DisambPass, FallbackTerm, IrState, Proc, UndefinedTerm, IR};
 use citeproc_db::{CiteData, ClusterData, ClusterId, ClusterNumber, IntraNote};
-use citeproc_io::output::{markup::Markup, OutputFormat};
+use citeproc_io::output::{markup::InlineElement, markup::Markup, OutputFormat};
 use citeproc_io::{Cite, Name};
 use csl::GivenNameDisambiguationRule as GNDR;
-use csl::{Atom, Bibliography, Position, SortKey};
+use csl::{AnyVariable, Atom, Bibliography, NameVariable, Position, SortKey};
 
 use indextree::NodeId;
 
+/// Hooks the processor needs from whatever implements [IrDatabase]. This and the database traits
+/// change along with the processor's internals; tools that want a stable view of how cites are
+/// rendered should use `citeproc::unstable` instead.
 pub trait ImplementationDetails {
     fn get_formatter(&self) -> Markup;
     fn lookup_interned_string(&self, symbol: string_interner::DefaultSymbol)
         -> Option<SmartString>;
+    /// Called each time a query does one of the expensive steps in [Work], so the processor can
+    /// count them. Does nothing by default.
+    fn count_work(&self, _work: Work) {}
+}
+
+/// The steps reported to [ImplementationDetails::count_work].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Work {
+    /// `built_cluster` rendered a cluster
+    BuiltCluster,
+    /// `ir_gen0` rendered a cite before any disambiguation
+    IrGen,
+    /// `ir_gen2_add_given_name` or `ir_fully_disambiguated` ran over a cite
+    Disambiguation,
 }
 
 // trait ParallelIrDatabase {
@@ -61,10 +80,31 @@ pub trait IrDatabase:
 
     /// render the `<intext>` element on demand
     fn intext(&self, key: CiteId) -> Option<Arc<IrGen>>;
+    /// What the cite would render if it were author-only, without building its cluster.
+    #[salsa::invoke(crate::ir::transforms::author_only_outcome)]
+    fn author_only_outcome(&self, key: CiteId) -> AuthorOnlyOutcome;
 
     fn bib_item_gen0(&self, ref_id: Atom) -> Option<Arc<IrGen>>;
     fn bib_item(&self, ref_id: Atom) -> Arc<MarkupOutput>;
+    /// `bib_item` without the bibliography layout's prefix and suffix, or None if it has neither
+    fn bib_item_content(&self, ref_id: Atom) -> Option<Arc<MarkupOutput>>;
+    /// See [Markup::left_margin_width]
+    fn bib_item_left_margin_width(&self, ref_id: Atom) -> Option<u32>;
+    /// The widest `display="left-margin"` block across the whole bibliography, 0 if there are none
+    fn bib_max_offset(&self) -> u32;
+
+    /// Terms the style uses that the default locale doesn't define
+    fn undefined_terms(&self) -> Arc<Vec<UndefinedTerm>>;
+    /// Terms the style uses that the default locale only gets from the en-US fallback
+    fn fallback_terms(&self) -> Arc<Vec<FallbackTerm>>;
+    /// Every variable the style could render, test or sort by, in any branch
+    fn style_variables(&self) -> Arc<FnvHashSet<AnyVariable>>;
+    /// Each entry's IR in bibliography order, after subsequent-author-substitute
+    fn bib_items_substituted(&self) -> Arc<Vec<(Atom, Arc<IrGen>)>>;
     fn get_bibliography_map(&self) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>>;
+    /// The entries in `get_bibliography_map` without the bibliography layout's prefix and suffix.
+    /// Empty if the layout has neither.
+    fn get_bibliography_content_map(&self) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>>;
 
     fn branch_runs(&self) -> Arc<FreeCondSets>;
 
@@ -85,6 +125,17 @@ pub trait IrDatabase:
     #[salsa::interned]
     fn disamb_name(&self, e: DisambNameData) -> DisambName;
 
+    /// A person's name rendered with one set of name options. Unlike `disamb_name`, this does
+    /// not care which reference or variable the name came from, so every cite and reference that
+    /// renders the same name the same way shares the result.
+    #[salsa::invoke(crate::disamb::names::formatted_person_name)]
+    fn formatted_person_name(&self, key: FormattedNameKey) -> Arc<MarkupBuild>;
+
+    /// Every person's name in a name list rendered in one go, for the names renderer. Cites and
+    /// references with the same list of names and the same options share the result.
+    #[salsa::invoke(crate::disamb::names::formatted_name_list)]
+    fn formatted_name_list(&self, key: FormattedNameListKey) -> Arc<Vec<MarkupBuild>>;
+
     // Sorting
 
     // Includes intra-cluster sorting
@@ -112,6 +163,36 @@ pub trait IrDatabase:
     fn sorted_refs(&self) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)>;
     #[salsa::input]
     fn bibliography_no_sort(&self) -> bool;
+    #[salsa::input]
+    fn missing_reference_policy(&self) -> MissingReferencePolicy;
+    /// Leaves cite prefixes and suffixes as they were given, instead of spacing them off from the
+    /// cite.
+    #[salsa::input]
+    fn raw_cite_affixes(&self) -> bool;
+    /// Passes `{ verbatim }` cite prefixes and suffixes through unescaped. When off, they are
+    /// escaped like any other affix.
+    #[salsa::input]
+    fn verbatim_affixes(&self) -> bool;
+    /// Which styles capitalize a cite whose prefix ends in a full stop.
+    #[salsa::input]
+    fn cite_capitalization(&self) -> CiteCapitalization;
+    /// Added to every rendered `citation-number`, so a document split across several processors
+    /// can number continuously. Sorting and collapsing work on the un-offset numbers.
+    #[salsa::input]
+    fn citation_number_offset(&self) -> u32;
+    /// Cites in in-text clusters are never ibid; one that would be is only subsequent, like any
+    /// other repeated cite. Note clusters are unaffected.
+    #[salsa::input]
+    fn in_text_no_ibid(&self) -> bool;
+    /// Caps how many names any one name variable renders, whatever the style's et-al settings.
+    #[salsa::input]
+    fn name_limit(&self) -> Option<NameLimit>;
+    /// Whether an author-only cite can be whatever a `<substitute>` rendered, or only names.
+    #[salsa::input]
+    fn author_only_substitute(&self) -> AuthorOnlySubstitute;
+    /// Which items `text-case="title"` treats as English.
+    #[salsa::input]
+    fn text_case_locales(&self) -> TextCaseLocales;
 
     #[salsa::invoke(crate::sort::bib_number)]
     fn bib_number(&self, id: CiteId) -> Option<BibNumber>;
@@ -119,6 +200,148 @@ pub trait IrDatabase:
 
 pub fn safe_default(db: &mut dyn IrDatabase) {
     db.set_bibliography_no_sort_with_durability(false, salsa::Durability::HIGH);
+    db.set_missing_reference_policy_with_durability(Default::default(), salsa::Durability::HIGH);
+    db.set_raw_cite_affixes_with_durability(false, salsa::Durability::HIGH);
+    db.set_verbatim_affixes_with_durability(false, salsa::Durability::HIGH);
+    db.set_cite_capitalization_with_durability(Default::default(), salsa::Durability::HIGH);
+    db.set_citation_number_offset_with_durability(0, salsa::Durability::HIGH);
+    db.set_in_text_no_ibid_with_durability(false, salsa::Durability::HIGH);
+    db.set_name_limit_with_durability(None, salsa::Durability::HIGH);
+    db.set_author_only_substitute_with_durability(Default::default(), salsa::Durability::HIGH);
+    db.set_text_case_locales_with_durability(Default::default(), salsa::Durability::HIGH);
+}
+
+/// What to render in place of a cite whose reference is not in the library.
+#[derive(Debug, Clone, PartialEq, Eq, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MissingReferencePolicy {
+    /// `???`
+    QuestionMarks,
+    /// The reference id in square brackets, like `[smith2008?]`
+    BracketedKey,
+    /// Nothing at all
+    Empty,
+    /// A template supplied by the host, in which `{id}` is replaced with the reference id
+    Template(String),
+}
+
+impl Default for MissingReferencePolicy {
+    fn default() -> Self {
+        MissingReferencePolicy::QuestionMarks
+    }
+}
+
+/// When to capitalize the first term of a cite whose prefix ends in a full stop (`see the
+/// discussion above. Ibid.`), or of the first cite in a note that has no prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CiteCapitalization {
+    /// Only in note styles, where a cluster is usually a sentence of its own. This is what
+    /// citeproc-js does.
+    NoteStyles,
+    /// In in-text styles as well.
+    Always,
+    /// Never, e.g. if the client already judges whether a cluster starts a sentence.
+    Never,
+}
+
+impl Default for CiteCapitalization {
+    fn default() -> Self {
+        CiteCapitalization::NoteStyles
+    }
+}
+
+/// Which items count as English, for `text-case="title"`, which only applies to English items.
+/// Either way, an item whose `language` is not English is not title-cased.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextCaseLocales {
+    /// As the spec says, an item without a `language` is in the style's locale, so it is only
+    /// title-cased if that is English.
+    Strict,
+    /// An item without a `language` is English, whatever the style's locale. This is what
+    /// citeproc-js does unless its `strict_text_case_locales` option is set.
+    Lenient,
+}
+
+impl Default for TextCaseLocales {
+    fn default() -> Self {
+        TextCaseLocales::Strict
+    }
+}
+
+/// What an author-only cite can render when its reference has nothing for the style's first
+/// `<names>` element, and the `<substitute>` rendered something else in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthorOnlySubstitute {
+    /// Anything, including a title, as citeproc-js does
+    Any,
+    /// Only the names a substitute fell back to, e.g. the editors or translators. Otherwise, the
+    /// cite has no printed form; see [AuthorOnlyOutcome::NoPrintedForm].
+    NamesOnly,
+}
+
+impl Default for AuthorOnlySubstitute {
+    fn default() -> Self {
+        AuthorOnlySubstitute::Any
+    }
+}
+
+/// What an author-only cite renders in place of the whole cite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde_derive::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthorOnlyOutcome {
+    /// The style's `<intext>` element
+    Intext,
+    /// The names from the first `<names>` element, or the names its `<substitute>` fell back to
+    Names,
+    /// Something else the `<substitute>` rendered, like a title. Only with
+    /// [AuthorOnlySubstitute::Any].
+    Substitute,
+    /// Nothing; the cite renders as `[NO_PRINTED_FORM]`, which a host may prefer to leave out.
+    NoPrintedForm,
+}
+
+impl MissingReferencePolicy {
+    /// The plain text to render for a missing `ref_id`.
+    pub fn render(&self, ref_id: &str) -> String {
+        match self {
+            MissingReferencePolicy::QuestionMarks => "???".into(),
+            MissingReferencePolicy::BracketedKey => format!("[{}?]", ref_id),
+            // Not Rendered(None), which would give the "no printed form" error text instead.
+            MissingReferencePolicy::Empty => String::new(),
+            MissingReferencePolicy::Template(template) => template.replace("{id}", ref_id),
+        }
+    }
+}
+
+/// A hard cap on the number of names rendered from any one name variable, for references with
+/// hundreds or thousands of authors. It applies on top of the style's et-al settings, only when
+/// they would render more than `max_names` names. Sorting and `form="count"` still see every name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameLimit {
+    pub max_names: u32,
+    /// What stands in for the names that are left out
+    #[serde(default)]
+    pub truncation: NameTruncation,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, serde_derive::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NameTruncation {
+    /// The first `max_names` names and the et-al term, as if the style had set `et-al-use-first`
+    EtAl,
+    /// The first `max_names - 1` names, an ellipsis and the last name, like `et-al-use-last`.
+    /// With a `max_names` under 2, this is the same as `EtAl`.
+    Ellipsis,
+}
+
+impl Default for NameTruncation {
+    fn default() -> Self {
+        NameTruncation::EtAl
+    }
 }
 
 fn all_person_names(db: &dyn IrDatabase) -> Arc<Vec<DisambNameData>> {
@@ -349,15 +572,36 @@ impl IrGen {
     pub(crate) fn tree_mut(&mut self) -> &mut IrTree {
         &mut self.tree
     }
+    /// An indented outline of the tree, one node per line.
+    pub fn debug_tree(&self) -> String {
+        format!("{:?}", self.tree)
+    }
+    /// The tokens that are matched against each reference's [Dfa] during disambiguation.
+    pub fn to_edge_stream(&self, fmt: &Markup) -> Vec<EdgeData> {
+        self.tree_ref().to_edge_stream(fmt)
+    }
+    /// Each name variable that rendered fewer names than the reference has, because of et-al or
+    /// a [NameLimit], with how many names were left out.
+    pub fn omitted_names(&self) -> Vec<(NameVariable, usize)> {
+        let IrTree { root, arena } = &self.tree;
+        root.descendants(arena)
+            .filter_map(|node| match &arena.get(node)?.get().0 {
+                IR::Name(nir) => Some((nir.variable(), nir.omitted_names())),
+                _ => None,
+            })
+            .filter(|&(_, omitted)| omitted > 0)
+            .collect()
+    }
 }
 
 fn ref_not_found(db: &dyn IrDatabase, ref_id: &Atom, log: bool) -> Arc<IrGen> {
     if log {
         info!("citeproc-rs: reference {} not found", ref_id);
     }
+    let text = db.missing_reference_policy().render(ref_id);
     let mut arena = IrArena::new();
     let root = arena.new_node((
-        IR::Rendered(Some(CiteEdgeData::Output(db.get_formatter().plain("???")))),
+        IR::Rendered(Some(CiteEdgeData::Output(db.get_formatter().plain(&text)))),
         GroupVars::Plain,
     ));
     Arc::new(IrGen::new(IrTree::new(root, arena), IrState::new()))
@@ -372,9 +616,10 @@ fn ref_not_found(db: &dyn IrDatabase, ref_id: &Atom, log: bool) -> Arc<IrGen> {
 // bib_number
 //  - sorted_refs
 macro_rules! preamble {
-    ($style:ident, $locale:ident, $cite:ident, $refr:ident, $ctx:ident, $db:expr, $id:expr, $pass:expr) => {{
+    ($style:ident, $locale:ident, $quotes:ident, $cite:ident, $refr:ident, $ctx:ident, $db:expr, $id:expr, $pass:expr) => {{
         $style = $db.style();
         $locale = $db.default_locale();
+        $quotes = $db.default_quotes();
         // Avoid making bibliography ghosts all depend any positional / note num info
         let cite_stuff = match $db.lookup_cite($id) {
             CiteData::RealCite { cite, .. } => (cite, $db.cite_position($id)),
@@ -400,12 +645,16 @@ macro_rules! preamble {
             disamb_pass: $pass,
             style: &$style,
             locale: &$locale,
-            bib_number: $db.bib_number($id).map(|x| x.get()),
+            quotes: &$quotes,
+            bib_number: $db
+                .bib_number($id)
+                .map(|x| x.get() + $db.citation_number_offset()),
             in_bibliography: false,
             names_delimiter,
             name_citation: name_el,
             sort_key: None,
             year_suffix: None,
+            text_case_locales: $db.text_case_locales(),
         };
     }};
 }
@@ -834,6 +1083,7 @@ fn expand_one_name_ir(
         }
     }
     nir.intermediate_custom(
+        Some(db),
         &ctx.format,
         ctx.position.0,
         ctx.sort_key.is_some(),
@@ -927,9 +1177,29 @@ fn disambiguate_add_year_suffix(tree: &mut IrTree, ctx: &CiteContext<'_, Markup>
         let (ys, ys_gv) = get_ys_mut(yid, &mut tree.arena);
         *ys_gv = gv;
         ys.suffix_num = Some(suffix);
+        added_suffix = true;
         break;
     }
 
+    // The cite renders neither a year-suffix variable nor a date to hang one on, so it goes at
+    // the end of the cite. Layout affixes are applied to the whole cluster, so this is still
+    // before the layout suffix.
+    if !added_suffix {
+        if let IR::Seq(_) = tree.arena[tree.root].get().0 {
+            let hook = YearSuffixHook::Plain;
+            let sum: IrSum<Markup> = hook.render(ctx, suffix);
+            let gv = sum.1;
+            let rendered = tree.arena.new_node(sum);
+            let ys = YearSuffix {
+                hook,
+                suffix_num: Some(suffix),
+            };
+            let yid = tree.arena.new_node((IR::YearSuffix(ys), gv));
+            yid.append(rendered, &mut tree.arena);
+            tree.root.append(yid, &mut tree.arena);
+        }
+    }
+
     tree.recompute_group_vars();
 }
 
@@ -970,12 +1240,14 @@ fn disambiguate_true(
 }
 
 fn ir_gen0(db: &dyn IrDatabase, id: CiteId) -> Arc<IrGen> {
+    db.count_work(Work::IrGen);
     let style;
     let locale;
+    let quotes;
     let cite;
     let refr;
     let ctx;
-    preamble!(style, locale, cite, refr, ctx, db, id, None);
+    preamble!(style, locale, quotes, cite, refr, ctx, db, id, None);
     let mut state = IrState::new();
     let mut arena = IrArena::new();
     let root = style
@@ -1088,12 +1360,14 @@ impl IrGenCow {
 
 /// Starts with ir_gen0, and disambiguates through add_names and add_givenname
 fn ir_gen2_add_given_name(db: &dyn IrDatabase, id: CiteId) -> Arc<IrGen> {
+    db.count_work(Work::Disambiguation);
     let style;
     let locale;
+    let quotes;
     let cite;
     let refr;
     let mut ctx;
-    preamble!(style, locale, cite, refr, ctx, db, id, None);
+    preamble!(style, locale, quotes, cite, refr, ctx, db, id, None);
 
     let mut irgen = IrGenCow::Arc(db.ir_gen0(id));
     if is_unambiguous(db, irgen.tree_ref(), &ctx.reference.id) {
@@ -1108,12 +1382,14 @@ fn ir_gen2_add_given_name(db: &dyn IrDatabase, id: CiteId) -> Arc<IrGen> {
 }
 
 fn ir_fully_disambiguated(db: &dyn IrDatabase, id: CiteId) -> Arc<IrGen> {
+    db.count_work(Work::Disambiguation);
     let style;
     let locale;
+    let quotes;
     let cite;
     let refr;
     let mut ctx;
-    preamble!(style, locale, cite, refr, ctx, db, id, None);
+    preamble!(style, locale, quotes, cite, refr, ctx, db, id, None);
 
     // Start with the given names done.
     let mut irgen = IrGenCow::Arc(db.ir_gen2_add_given_name(id));
@@ -1139,22 +1415,36 @@ fn built_cluster(
     db: &dyn IrDatabase,
     cluster_id: ClusterId,
 ) -> Arc<<Markup as OutputFormat>::Output> {
+    db.count_work(Work::BuiltCluster);
     let fmt = db.get_formatter();
-    let build = cluster::built_cluster_before_output(db, cluster_id, &fmt);
-    let string = fmt.output(build, get_piq(db));
-    Arc::new(string)
+    built_cluster_preview(db, cluster_id, &fmt, None)
 }
 
 pub fn built_cluster_preview(
     db: &dyn IrDatabase,
     cluster_id: ClusterId,
     fmt: &Markup,
+    layout_delimiter: Option<&str>,
 ) -> Arc<<Markup as OutputFormat>::Output> {
-    let build = cluster::built_cluster_before_output(db, cluster_id, &fmt);
+    let mut build = cluster::built_cluster_before_output(db, cluster_id, &fmt, layout_delimiter);
+    if db.cluster_suppress_trailing_punctuation(cluster_id) {
+        fmt.trim_trailing_punctuation(&mut build);
+    }
     let string = fmt.output(build, get_piq(db));
     Arc::new(string)
 }
 
+/// The inline tree that [built_cluster] writes out, for debugging formatting or writing another
+/// output format. Not cached.
+pub fn built_cluster_tree(db: &dyn IrDatabase, cluster_id: ClusterId) -> Vec<InlineElement> {
+    let fmt = db.get_formatter();
+    let mut build = cluster::built_cluster_with_cite_tags(db, cluster_id, &fmt);
+    if db.cluster_suppress_trailing_punctuation(cluster_id) {
+        fmt.trim_trailing_punctuation(&mut build);
+    }
+    fmt.output_tree(build, get_piq(db))
+}
+
 #[test]
 pub fn test_preview_unicode_escape_issue_91() {
     use crate::test::{test_style_layout, MockProcessor};
@@ -1206,7 +1496,7 @@ pub fn test_preview_unicode_escape_issue_91() {
     );
 
     let plain = Markup::plain();
-    let preview = built_cluster_preview(&proc, cluster, &plain);
+    let preview = built_cluster_preview(&proc, cluster, &plain, None);
     println!("{}", preview);
     assert_eq!(preview.as_str(), "text: Čotar, name: Čotar, number: Čotar");
 }
@@ -1228,6 +1518,7 @@ pub fn with_cite_context<T>(
 ) -> Option<T> {
     let style = db.style();
     let locale = db.default_locale();
+    let quotes = db.default_quotes();
     let cite = id.lookup(db);
     let refr = db.reference(cite.ref_id.clone())?;
     let (names_delimiter, name_el) = db.name_info_citation();
@@ -1244,12 +1535,14 @@ pub fn with_cite_context<T>(
         disamb_pass: None,
         style: &style,
         locale: &locale,
+        quotes: &quotes,
         bib_number,
         in_bibliography: false,
         names_delimiter,
         name_citation: name_el,
         sort_key,
         year_suffix,
+        text_case_locales: db.text_case_locales(),
     };
     Some(f(ctx))
 }
@@ -1269,6 +1562,7 @@ pub fn with_bib_context<T>(
     let style = db.style();
     let bib = style.bibliography.as_ref()?;
     let locale = db.default_locale();
+    let quotes = db.default_quotes();
     let cite = Cite::basic(ref_id.clone());
     let refr_arc = db.reference(ref_id);
     let null_ref = citeproc_io::Reference::empty("empty_ref".into(), csl::CslType::Article);
@@ -1287,12 +1581,14 @@ pub fn with_bib_context<T>(
         disamb_pass: None,
         style: &style,
         locale: &locale,
+        quotes: &quotes,
         bib_number,
         in_bibliography: true,
         names_delimiter,
         name_citation: name_el,
         sort_key,
         year_suffix,
+        text_case_locales: db.text_case_locales(),
     };
     if is_ref_missing {
         ref_missing(bib, ctx, false)
@@ -1307,7 +1603,8 @@ fn bib_item_gen0(db: &dyn IrDatabase, ref_id: Atom) -> Option<Arc<IrGen>> {
     let bib_number = citation_numbers_by_id
         .get(&ref_id)
         .expect("sorted_refs should contain a bib_item key")
-        .get();
+        .get()
+        + db.citation_number_offset();
 
     with_bib_context(
         db,
@@ -1339,7 +1636,9 @@ fn bib_item_gen0(db: &dyn IrDatabase, ref_id: Atom) -> Option<Arc<IrGen>> {
                 disambiguate_true(db, &mut tree, &mut state, &ctx);
             }
 
-            if bib.second_field_align == Some(csl::SecondFieldAlign::Flush) {
+            // Both alignments put the first field in its own block; they only differ in where the
+            // host draws it.
+            if bib.second_field_align.is_some() {
                 if let Some(new_root) = IR::split_first_field(tree.root, &mut tree.arena) {
                     tree.root = new_root;
                 }
@@ -1384,7 +1683,7 @@ fn bib_item_gen0(db: &dyn IrDatabase, ref_id: Atom) -> Option<Arc<IrGen>> {
                 }
             };
 
-            if bib.second_field_align == Some(csl::SecondFieldAlign::Flush) {
+            if bib.second_field_align.is_some() {
                 if let Some(new_root) = IR::split_first_field(tree.root, &mut tree.arena) {
                     tree.root = new_root;
                 }
@@ -1427,72 +1726,192 @@ fn bib_item(db: &dyn IrDatabase, ref_id: Atom) -> Arc<MarkupOutput> {
     }
 }
 
-fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
+fn bib_item_content(db: &dyn IrDatabase, ref_id: Atom) -> Option<Arc<MarkupOutput>> {
+    db.get_bibliography_content_map().get(&ref_id).cloned()
+}
+
+fn bib_item_left_margin_width(db: &dyn IrDatabase, ref_id: Atom) -> Option<u32> {
+    let fmt = db.get_formatter();
+    let gen0 = db.bib_item_gen0(ref_id)?;
+    let flat = gen0.tree_ref().flatten(&fmt, None)?;
+    fmt.left_margin_width(&flat)
+}
+
+fn bib_max_offset(db: &dyn IrDatabase) -> u32 {
+    db.sorted_refs()
+        .0
+        .iter()
+        .filter_map(|ref_id| db.bib_item_left_margin_width(ref_id.clone()))
+        .max()
+        .unwrap_or(0)
+}
+
+fn undefined_terms(db: &dyn IrDatabase) -> Arc<Vec<UndefinedTerm>> {
+    let terms = crate::undefined_terms(&db.style(), &db.default_locale());
+    Arc::new(terms)
+}
+
+fn fallback_terms(db: &dyn IrDatabase) -> Arc<Vec<FallbackTerm>> {
+    let lang = db.default_lang();
+    let requested = db.requested_locale(lang.clone());
+    let terms = crate::fallback_terms(&db.style(), &lang, &requested, &db.default_locale());
+    Arc::new(terms)
+}
+
+fn style_variables(db: &dyn IrDatabase) -> Arc<FnvHashSet<AnyVariable>> {
+    Arc::new(crate::style_variables(&db.style()))
+}
+
+fn bib_items_substituted(db: &dyn IrDatabase) -> Arc<Vec<(Atom, Arc<IrGen>)>> {
     let fmt = db.get_formatter();
     let style = db.style();
     let sorted_refs = db.sorted_refs();
-    let mut m =
-        FnvHashMap::with_capacity_and_hasher(sorted_refs.0.len(), fnv::FnvBuildHasher::default());
+    let mut items = Vec::with_capacity(sorted_refs.0.len());
     let mut prev: Option<(NodeId, Arc<IrGen>)> = None;
     for key in sorted_refs.0.iter() {
         // TODO: put Nones in there so they can be updated
         if let Some(mut gen0) = db.bib_item_gen0(key.clone()) {
-            // in a bibliography, we do the affixes etc inside Layout, so they're not here
-            let current = gen0.tree_ref().first_names_block();
+            let current = gen0.tree_ref().first_names_output();
             let sas = style.bibliography.as_ref().and_then(|bib| {
                 bib.subsequent_author_substitute
                     .as_ref()
                     .map(|x| (x.as_ref(), bib.subsequent_author_substitute_rule))
             });
-            if let (Some(prev_name_block), Some(current_name_block), Some((sas, sas_rule))) = (
-                prev.as_ref()
-                    .and_then(|(first_block, gen)| gen.tree.arena.get(*first_block)),
-                current,
-                sas,
-            ) {
+            if let (Some((prev_block, prev_gen)), Some(current_block), Some((sas, sas_rule))) =
+                (prev.as_ref(), current, sas)
+            {
+                let prev_block = prev_gen.tree_ref().with_node(*prev_block);
                 let mutated = Arc::make_mut(&mut gen0);
-                let did = transforms::subsequent_author_substitute(
-                    &fmt,
-                    // In order to unwrap this here, you must only replace the NameIR node's
-                    // children, not the IR.
-                    prev_name_block.get().0.unwrap_name_ir(),
-                    current_name_block,
-                    &mut mutated.tree.arena,
-                    sas,
-                    sas_rule,
+                let arena = &mut mutated.tree.arena;
+                let current_is_names = matches!(
+                    arena.get(current_block).map(|node| &node.get().0),
+                    Some(IR::Name(_))
                 );
+                // Names only match names, and a substitute only matches another substitute.
+                let prev_ir = prev_block.get_node().map(|node| &node.get().0);
+                let did = match (prev_ir, current_is_names) {
+                    // In order to unwrap the current block in here, you must only replace the
+                    // NameIR node's children, not the IR.
+                    (Some(IR::Name(prev_nir)), true) => transforms::subsequent_author_substitute(
+                        &fmt,
+                        prev_nir,
+                        current_block,
+                        arena,
+                        sas,
+                        sas_rule,
+                    ),
+                    (Some(IR::Substitute), false) => {
+                        transforms::subsequent_author_substitute_for_substitute(
+                            &fmt,
+                            prev_block,
+                            current_block,
+                            arena,
+                            sas,
+                        )
+                    }
+                    _ => false,
+                };
                 if did {
                     mutated.tree_mut().recompute_group_vars();
                 }
             }
-            let flat = gen0
-                .tree_ref()
-                .flatten(&fmt, None)
-                .unwrap_or_else(|| fmt.plain(""));
-            let string = fmt.output(flat, get_piq(db));
-            if !string.is_empty() {
-                m.insert(key.clone(), Arc::new(string));
-            }
-            prev = current.map(|cur| (cur, gen0));
+            prev = current.map(|cur| (cur, gen0.clone()));
+            items.push((key.clone(), gen0));
+        }
+    }
+    Arc::new(items)
+}
+
+fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
+    let fmt = db.get_formatter();
+    let items = db.bib_items_substituted();
+    let mut m = FnvHashMap::with_capacity_and_hasher(items.len(), fnv::FnvBuildHasher::default());
+    for (key, gen) in items.iter() {
+        // in a bibliography, we do the affixes etc inside Layout, so they're not here
+        let flat = gen
+            .tree_ref()
+            .flatten(&fmt, None)
+            .unwrap_or_else(|| fmt.plain(""));
+        let string = fmt.output(flat, get_piq(db));
+        if !string.is_empty() {
+            m.insert(key.clone(), Arc::new(string));
+        }
+    }
+    Arc::new(m)
+}
+
+fn get_bibliography_content_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
+    let style = db.style();
+    let bib = style.bibliography.as_ref();
+    let layout_affixes = match bib.and_then(|bib| bib.layout.affixes.as_ref()) {
+        Some(affixes) => affixes,
+        None => return Arc::new(FnvHashMap::default()),
+    };
+    let fmt = db.get_formatter();
+    let entries = db.get_bibliography_map();
+    let mut m = FnvHashMap::default();
+    for (key, gen) in db.bib_items_substituted().iter() {
+        if !entries.contains_key(key) {
+            continue;
         }
+        let mut tree = gen.tree.clone();
+        transforms::remove_layout_affixes(tree.root, &mut tree.arena, layout_affixes);
+        let flat = tree
+            .tree_ref()
+            .flatten(&fmt, None)
+            .unwrap_or_else(|| fmt.plain(""));
+        m.insert(key.clone(), Arc::new(fmt.output(flat, get_piq(db))));
     }
     Arc::new(m)
 }
 
+/// An in-text style has no notes of its own, but a document can still put its clusters in
+/// footnotes. Those are positioned as if they were in the text: positions follow document order
+/// across both kinds of cluster, and nothing gets a first-reference-note-number.
+fn in_text_style_order(db: &dyn IrDatabase, clusters: &[ClusterData]) -> Vec<ClusterData> {
+    let by_id: FnvHashMap<ClusterId, &ClusterData> = clusters
+        .iter()
+        .map(|cluster| (cluster.id, cluster))
+        .collect();
+    let mut in_text_number = 0;
+    db.cluster_ids()
+        .iter()
+        .filter_map(|id| by_id.get(id))
+        .map(|&cluster| {
+            let mut cluster = cluster.clone();
+            if cluster.number != ClusterNumber::OutsideFlow {
+                in_text_number += 1;
+                cluster.number = ClusterNumber::InText(in_text_number);
+            }
+            cluster
+        })
+        .collect()
+}
+
 // See https://github.com/jgm/pandoc-citeproc/blob/e36c73ac45c54dec381920e92b199787601713d1/src/Text/CSL/Reference.hs#L910
 fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Option<u32>)>> {
-    let clusters = db.clusters_cites_sorted();
+    let style = db.style();
+    let sorted = db.clusters_cites_sorted();
+    let in_text_order;
+    let clusters = if style.class == csl::StyleClass::InText {
+        in_text_order = in_text_style_order(db, &sorted);
+        &in_text_order
+    } else {
+        &*sorted
+    };
 
     let mut map = FnvHashMap::default();
 
-    let style = db.style();
     let near_note_distance = style.citation.near_note_distance;
+    let in_text_no_ibid = db.in_text_no_ibid();
 
     // Backref table for FRNN
     // No entries for first ref == an in-text reference, only first time it appeared in a
     // footnote. This makes sense because note styles usually have a near-bibliography level of
     // detail, but in-text styles are often just author-date or a bibligraphy item number.
     let mut first_seen: FnvHashMap<Atom, ClusterNumber> = FnvHashMap::default();
+    // near-note is measured from the most recent cite to the same reference, not the first.
+    let mut last_seen: FnvHashMap<Atom, ClusterNumber> = FnvHashMap::default();
 
     let mut last_note_num = None;
     let mut clusters_in_last_note: Vec<ClusterId> = Vec::new();
@@ -1501,8 +1920,10 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
     let mut prev_note: Option<&ClusterData> = None;
 
     for cluster in clusters.iter() {
-        let prev_in_group = if let ClusterNumber::Note(_) = cluster.number {
-            !clusters_in_last_note.is_empty()
+        // Only consider the whole of the previous note when this cluster is in a new note. Within
+        // a single note, the immediately preceding cluster is what counts.
+        let prev_in_group = if let ClusterNumber::Note(n) = cluster.number {
+            !clusters_in_last_note.is_empty() && last_note_num != Some(n.note_number())
         } else {
             false
         };
@@ -1532,15 +1953,13 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                 // other in the document.
                 PrevCluster(Arc<Cite<Markup>>, Option<u32>),
             }
-            let matching_prev: Option<Position> = prev_cite
-                .and_then(|p| {
-                    if p.ref_id == cite.ref_id {
-                        Some(Where::SameCluster(p))
-                    } else {
-                        None
-                    }
-                })
-                .or_else(|| {
+            let matching_prev: Option<Position> = match prev_cite {
+                // Any intervening cite to another reference, even in the same cluster, means
+                // this is not an ibid.
+                Some(p) => Some(p)
+                    .filter(|p| p.ref_id == cite.ref_id)
+                    .map(Where::SameCluster),
+                None => {
                     if let Some(prev_cluster) = match cluster.number {
                         ClusterNumber::OutsideFlow => None,
                         ClusterNumber::InText(_) => prev_in_text,
@@ -1582,59 +2001,61 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                     } else {
                         None
                     }
-                })
-                .map(|prev| {
-                    enum Num {
-                        SameCluster,
-                        PrevButInText,
-                        PrevNote(u32),
-                    }
-                    let nn = match &prev {
-                        Where::SameCluster(_) => Num::SameCluster,
-                        Where::PrevCluster(_, None) => Num::PrevButInText,
-                        Where::PrevCluster(_, Some(n)) => Num::PrevNote(*n),
-                    };
-                    let near = match nn {
-                        Num::SameCluster => true,
-                        Num::PrevButInText => false,
-                        Num::PrevNote(n) => is_near(n),
-                    };
-                    let prev = match &prev {
-                        Where::SameCluster(prev) | Where::PrevCluster(prev, _) => prev,
-                    };
-                    match (prev.locators.as_ref(), cite.locators.as_ref(), near) {
-                        // no locators
-                        (None, None, false) => Position::Ibid,
-                        (None, None, true) => Position::IbidNear,
-                        // prev no locator, cur has locator
-                        (None, Some(_cur), false) => Position::IbidWithLocator,
-                        (None, Some(_cur), true) => Position::IbidWithLocatorNear,
-                        // Despite "position can only be subsequent", we get
-                        // near/far note, as they imply subsequent.
-                        (Some(_pre), None, x) => {
-                            if x {
-                                Position::NearNote
-                            } else {
-                                Position::FarNote
-                            }
+                }
+            }
+            .filter(|_| !(in_text && in_text_no_ibid))
+            .map(|prev| {
+                enum Num {
+                    SameCluster,
+                    PrevButInText,
+                    PrevNote(u32),
+                }
+                let nn = match &prev {
+                    Where::SameCluster(_) => Num::SameCluster,
+                    Where::PrevCluster(_, None) => Num::PrevButInText,
+                    Where::PrevCluster(_, Some(n)) => Num::PrevNote(*n),
+                };
+                let near = match nn {
+                    Num::SameCluster => true,
+                    Num::PrevButInText => false,
+                    Num::PrevNote(n) => is_near(n),
+                };
+                let prev = match &prev {
+                    Where::SameCluster(prev) | Where::PrevCluster(prev, _) => prev,
+                };
+                match (prev.locators.as_ref(), cite.locators.as_ref(), near) {
+                    // no locators
+                    (None, None, false) => Position::Ibid,
+                    (None, None, true) => Position::IbidNear,
+                    // prev no locator, cur has locator
+                    (None, Some(_cur), false) => Position::IbidWithLocator,
+                    (None, Some(_cur), true) => Position::IbidWithLocatorNear,
+                    // Despite "position can only be subsequent", we get
+                    // near/far note, as they imply subsequent.
+                    (Some(_pre), None, x) => {
+                        if x {
+                            Position::NearNote
+                        } else {
+                            Position::FarNote
                         }
-                        // both have locator, but it's the same locator
-                        (Some(pre), Some(cur), x) if pre == cur => {
-                            if x {
-                                Position::IbidNear
-                            } else {
-                                Position::Ibid
-                            }
+                    }
+                    // both have locator, but it's the same locator
+                    (Some(pre), Some(cur), x) if pre == cur => {
+                        if x {
+                            Position::IbidNear
+                        } else {
+                            Position::Ibid
                         }
-                        (_, _, x) => {
-                            if x {
-                                Position::IbidWithLocatorNear
-                            } else {
-                                Position::IbidWithLocator
-                            }
+                    }
+                    (_, _, x) => {
+                        if x {
+                            Position::IbidWithLocatorNear
+                        } else {
+                            Position::IbidWithLocator
                         }
                     }
-                });
+                }
+            });
             let seen = first_seen.get(&cite.ref_id).cloned();
             match seen {
                 Some(ClusterNumber::Note(first_note_number)) => {
@@ -1649,11 +2070,14 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                                 first_note_number,
                             );
                             let unsigned = first_note_number.note_number();
+                            let last_note = match last_seen.get(&cite.ref_id) {
+                                Some(ClusterNumber::Note(last)) => last.note_number(),
+                                _ => unsigned,
+                            };
                             if let Some(pos) = matching_prev {
                                 map.insert(cite_id, (pos, Some(unsigned)));
-                            } else if this_intranote == first_note_number || is_near(unsigned) {
-                                // XXX: not sure about this one
-                                // unimplemented!("cite position for same number, but different cluster");
+                            } else if is_near(last_note) {
+                                // Includes earlier clusters in the same note.
                                 map.insert(cite_id, (Position::NearNote, Some(unsigned)));
                             } else {
                                 map.insert(cite_id, (Position::FarNote, Some(unsigned)));
@@ -1679,7 +2103,11 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                             map.insert(cite_id, (Position::First, None));
                         }
                         ClusterNumber::InText(itnum) => {
-                            let diff = itnum.wrapping_sub(seen_in_text_num);
+                            let last = match last_seen.get(&cite.ref_id) {
+                                Some(ClusterNumber::InText(last)) => *last,
+                                _ => seen_in_text_num,
+                            };
+                            let diff = itnum.wrapping_sub(last);
                             let pos = if let Some(pos) = matching_prev {
                                 pos
                             } else if diff <= near_note_distance {
@@ -1704,6 +2132,12 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                     map.insert(cite_id, (Position::First, None));
                 }
             }
+            match cluster.number {
+                ClusterNumber::Note(_) | ClusterNumber::InText(_) => {
+                    last_seen.insert(cite.ref_id.clone(), cluster.number);
+                }
+                ClusterNumber::OutsideFlow => {}
+            }
         }
 
         match cluster.number {
@@ -1745,10 +2179,11 @@ fn intext(db: &dyn IrDatabase, id: CiteId) -> Option<Arc<IrGen>> {
     style.intext.as_ref().map(|intext| {
         let style;
         let locale;
+        let quotes;
         let cite;
         let refr;
         let ctx;
-        preamble!(style, locale, cite, refr, ctx, db, id, None);
+        preamble!(style, locale, quotes, cite, refr, ctx, db, id, None);
         let mut state = IrState::new();
         let mut arena = IrArena::new();
         let root = intext.intermediate(db, &mut state, &ctx, &mut arena);
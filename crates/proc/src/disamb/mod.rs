@@ -75,7 +75,7 @@ pub(crate) mod names;
 mod ref_context;
 
 pub use free::{FreeCond, FreeCondSets};
-pub use names::{DisambName, DisambNameData};
+pub use names::{DisambName, DisambNameData, FormattedNameKey, FormattedNameListKey};
 pub use ref_context::RefContext;
 
 pub use finite_automata::{Dfa, EdgeData, Nfa, NfaEdge};
@@ -274,6 +274,7 @@ pub fn create_ref_ir<O: OutputFormat>(
 ) -> Vec<(FreeCond, RefIR)> {
     let style = db.style();
     let locale = db.default_locale();
+    let quotes = db.default_quotes();
     let ysh_explicit_edge = EdgeData::YearSuffixExplicit;
     let ysh_plain_edge = EdgeData::YearSuffixPlain;
     let ysh_edge = EdgeData::YearSuffix;
@@ -285,8 +286,16 @@ pub fn create_ref_ir<O: OutputFormat>(
         .cloned()
         .flat_map(|fc| {
             // Now we construct one ctx for every different count of disambiguate="X" checks
-            let ctx =
-                RefContext::from_free_cond(fc, &fmt, &style, &locale, refr, CiteOrBib::Citation);
+            let ctx = RefContext::from_free_cond(
+                fc,
+                &fmt,
+                &style,
+                &locale,
+                &quotes,
+                refr,
+                CiteOrBib::Citation,
+                db.text_case_locales(),
+            );
             let count = ctx.disamb_count;
             // 0 = none of them enabled
             // 1 = first disambiguate="X" tests as true
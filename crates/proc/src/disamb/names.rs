@@ -44,6 +44,7 @@ impl Disambiguation<Markup> for Names {
             fmt,
             demote_non_dropping_particle: style.demote_non_dropping_particle,
             initialize_with_hyphen: style.initialize_with_hyphen,
+            name_limit: db.name_limit(),
         };
 
         let mut seq = RefIrSeq {
@@ -55,8 +56,24 @@ impl Disambiguation<Markup> for Names {
         };
 
         let gen = GenericContext::<Markup, Markup>::Ref(ctx);
-        let name_irs =
+        let mut name_irs =
             crate::names::to_individual_name_irs(&gen, self, &names_inheritance, db, state, false);
+        if names_inheritance.name.form == Some(NameForm::Count) {
+            // Consumes all the name IRs, so the loop below does nothing. A count of zero falls
+            // through to substitution, like names that render nothing.
+            let count: u32 = name_irs
+                .by_ref()
+                .map(|nir| runner.count_names(ctx.position, &nir.disamb_names, false))
+                .sum();
+            if count > 0 {
+                let out = fmt.output_in_context(
+                    fmt.text_node(smart_format!("{}", count), None),
+                    child_stack,
+                    None,
+                );
+                seq.contents.push(RefIR::Edge(Some(EdgeData::Output(out))));
+            }
+        }
         for nir in name_irs {
             let mut nfa = Nfa::new();
             let start = nfa.graph.add_node(());
@@ -218,6 +235,54 @@ pub struct DisambNameData {
     pub(crate) all_same_family_name: bool,
 }
 
+/// The parts of a DisambNameData that affect how the name renders. The rest of the name options
+/// come from the style, which the query depends on anyway.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormattedNameKey {
+    pub(crate) el: NameEl,
+    pub(crate) value: PersonName,
+    /// Whether to render it as the first name in the list, which matters for name-as-sort-order.
+    pub(crate) primary: bool,
+}
+
+pub(crate) fn formatted_person_name(
+    db: &dyn IrDatabase,
+    key: FormattedNameKey,
+) -> Arc<MarkupBuild> {
+    let fmt = &db.get_formatter();
+    let style = db.style();
+    let builder = OneNameVar {
+        fmt,
+        name_el: &key.el,
+        bump_name_count: 0,
+        demote_non_dropping_particle: style.demote_non_dropping_particle,
+        initialize_with_hyphen: style.initialize_with_hyphen,
+        name_limit: None,
+    };
+    Arc::new(builder.render_person_name(&key.value, !key.primary))
+}
+
+/// The people's names in a list, up to the last one shown. Literal names don't need rendering, so
+/// they are None.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormattedNameListKey(pub(crate) Vec<Option<FormattedNameKey>>);
+
+pub(crate) fn formatted_name_list(
+    db: &dyn IrDatabase,
+    key: FormattedNameListKey,
+) -> Arc<Vec<MarkupBuild>> {
+    let names = key
+        .0
+        .into_iter()
+        .map(|name| {
+            name.map_or_else(MarkupBuild::default, |name| {
+                db.formatted_person_name(name).as_ref().clone()
+            })
+        })
+        .collect();
+    Arc::new(names)
+}
+
 /// fullstyles_APA.txt
 /// An optimisation of "do all these names render the same under NameForm::Short"
 pub(crate) fn all_same_family_name(names: &[Name]) -> bool {
@@ -278,19 +343,19 @@ impl DisambNameData {
     /// forward in NameIR expansion.
     pub(crate) fn single_name_edge(&self, db: &dyn IrDatabase, stack: Formatting) -> EdgeData {
         let fmt = &db.get_formatter();
-        let style = db.style();
-        let builder = OneNameVar {
-            fmt,
-            name_el: &self.el,
-            bump_name_count: 0,
-            demote_non_dropping_particle: style.demote_non_dropping_particle,
-            initialize_with_hyphen: style.initialize_with_hyphen,
-        };
-        let built = builder.render_person_name(&self.value, !self.primary);
-        let o = fmt.output_in_context(built, stack, None);
+        let built = db.formatted_person_name(self.formatted_name_key());
+        let o = fmt.output_in_context(built.as_ref().clone(), stack, None);
         EdgeData::Output(o)
     }
 
+    pub(crate) fn formatted_name_key(&self) -> FormattedNameKey {
+        FormattedNameKey {
+            el: self.el.clone(),
+            value: self.value.clone(),
+            primary: self.primary,
+        }
+    }
+
     fn disamb_iter(&self, rule: GNDR) -> SingleNameDisambIter {
         let method = SingleNameDisambMethod::from_rule(rule, self.primary);
         SingleNameDisambIter::new(method, &self.el)
@@ -441,6 +506,14 @@ fn test_name_disamb_iter() {
         vec![NameDisambPass::WithFormLong]
     );
     assert_eq!(test(&name, GNDR::PrimaryName, false), vec![]);
+    assert_eq!(
+        test(&name, GNDR::ByCite, false),
+        vec![NameDisambPass::WithFormLong]
+    );
+    // The -with-initials rules can't do anything without initialize-with
+    assert_eq!(test(&name, GNDR::AllNamesWithInitials, true), vec![]);
+    assert_eq!(test(&name, GNDR::PrimaryNameWithInitials, true), vec![]);
+
     name.initialize_with = Some(".".into());
     assert_eq!(
         test(&name, GNDR::AllNames, true),
@@ -449,10 +522,29 @@ fn test_name_disamb_iter() {
             NameDisambPass::WithInitializeFalse
         ]
     );
+    assert_eq!(
+        test(&name, GNDR::ByCite, false),
+        vec![
+            NameDisambPass::WithFormLong,
+            NameDisambPass::WithInitializeFalse
+        ]
+    );
     assert_eq!(
         test(&name, GNDR::AllNamesWithInitials, true),
         vec![NameDisambPass::WithFormLong]
     );
+    assert_eq!(
+        test(&name, GNDR::PrimaryNameWithInitials, true),
+        vec![NameDisambPass::WithFormLong]
+    );
+    assert_eq!(test(&name, GNDR::PrimaryNameWithInitials, false), vec![]);
+    assert_eq!(
+        test(&name, GNDR::PrimaryName, true),
+        vec![
+            NameDisambPass::WithFormLong,
+            NameDisambPass::WithInitializeFalse
+        ]
+    );
 }
 
 /// Original + expansions
@@ -671,7 +763,7 @@ pub struct NameCounter {
     pub current: u16,
 }
 
-use crate::NamesInheritance;
+use crate::{NameLimit, NamesInheritance};
 
 /// The full Names block has-many NameIRs when it is rendered. Each NameIR represents one variable
 /// to be rendered in a Names block. So each NameIR can have multiple actual people's names in it.
@@ -697,6 +789,7 @@ pub struct NameIR<O: OutputFormat> {
     pub initialize_with_hyphen: bool,
     pub etal_term: Option<(SmartString, Option<Formatting>)>,
     pub and_term: Option<SmartString>,
+    pub name_limit: Option<NameLimit>,
 }
 
 impl<O> NameIR<O>
@@ -729,7 +822,23 @@ where
             etal_term,
             and_term,
             built_label,
+            name_limit: None,
+        }
+    }
+
+    pub fn variable(&self) -> NameVariable {
+        self.variable
+    }
+
+    /// How many of the variable's names were left out the last time it was rendered, by et-al or
+    /// by a [NameLimit].
+    pub fn omitted_names(&self) -> usize {
+        if self.names_inheritance.name.form == Some(NameForm::Count) {
+            return 0;
         }
+        self.disamb_names
+            .len()
+            .saturating_sub(self.name_counter.current as usize)
     }
 
     pub fn achieved_count(&mut self, count: u16) {
@@ -740,7 +849,7 @@ where
     }
     pub fn rollback(
         &mut self,
-        _db: &dyn IrDatabase,
+        db: &dyn IrDatabase,
         ctx: &CiteContext<'_, O>,
     ) -> Option<Vec<O::Build>> {
         let (_prev_best, at) = self.achieved_at;
@@ -749,6 +858,7 @@ where
         }
         self.name_counter = at;
         self.intermediate_custom(
+            Some(db),
             &ctx.format,
             ctx.position.0,
             ctx.sort_key.is_some(),
@@ -760,11 +870,12 @@ where
     // returns false if couldn't add any more names
     pub fn add_name(
         &mut self,
-        _db: &dyn IrDatabase,
+        db: &dyn IrDatabase,
         ctx: &CiteContext<'_, O>,
     ) -> Option<Vec<O::Build>> {
         self.name_counter.bump += 1;
         self.intermediate_custom(
+            Some(db),
             &ctx.format,
             ctx.position.0,
             ctx.sort_key.is_some(),
@@ -782,6 +893,7 @@ where
         // We will only need to replace the first element, since names only ever get one child.
         // But keep the arena separate from this.
         self.intermediate_custom(
+            None,
             fmt,
             Position::First,
             false,
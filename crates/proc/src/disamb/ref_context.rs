@@ -1,5 +1,6 @@
 use crate::choose::CondChecker;
 use crate::cite_context::RenderContext;
+use crate::db::TextCaseLocales;
 use crate::prelude::*;
 use citeproc_io::output::markup::Markup;
 use citeproc_io::{DateOrRange, NumericValue, Reference};
@@ -13,6 +14,7 @@ pub struct RefContext<'a, O: OutputFormat = Markup> {
     pub format: &'a O,
     pub style: &'a Style,
     pub locale: &'a Locale,
+    pub quotes: &'a LocalizedQuotes,
     pub reference: &'a Reference,
     pub locator_type: Option<LocatorType>,
     pub position: Position,
@@ -20,6 +22,7 @@ pub struct RefContext<'a, O: OutputFormat = Markup> {
     pub names_delimiter: Option<SmartString>,
     pub name_el: Arc<NameEl>,
     pub disamb_count: u32,
+    pub text_case_locales: TextCaseLocales,
 }
 
 impl From<FreeCond> for Position {
@@ -52,6 +55,7 @@ where
             format: &ctx.format,
             style: ctx.style,
             locale: ctx.locale,
+            quotes: ctx.quotes,
             reference: refr,
             locator_type: ctx.cite.locators.as_ref().and_then(|locs| match locs {
                 Locators::Single(l) => Some(l.loc_type),
@@ -65,6 +69,7 @@ where
             names_delimiter: ctx.names_delimiter.clone(),
             name_el: ctx.name_citation.clone(),
             disamb_count: 0,
+            text_case_locales: ctx.text_case_locales,
         };
         ctx.count_disambiguate_branches(CiteOrBib::Citation);
         ctx
@@ -75,8 +80,10 @@ where
         format: &'c O,
         style: &'c Style,
         locale: &'c Locale,
+        quotes: &'c LocalizedQuotes,
         reference: &'c Reference,
         location: CiteOrBib,
+        text_case_locales: TextCaseLocales,
     ) -> Self {
         let name_info = match location {
             CiteOrBib::Citation => style.name_info_citation(),
@@ -86,6 +93,7 @@ where
             format,
             style,
             locale,
+            quotes,
             reference,
             locator_type: fc.to_loc_type(),
             position: Position::from(fc),
@@ -93,6 +101,7 @@ where
             names_delimiter: name_info.0,
             name_el: name_info.1,
             disamb_count: 0,
+            text_case_locales,
         };
         ctx.count_disambiguate_branches(location);
         ctx
@@ -120,6 +129,9 @@ impl<'c, O: OutputFormat> RenderContext for RefContext<'c, O> {
     fn locale(&self) -> &Locale {
         self.locale
     }
+    fn quotes(&self) -> &LocalizedQuotes {
+        self.quotes
+    }
     fn get_number(&self, var: NumberVariable) -> Option<NumericValue<'_>> {
         let and_term = self.locale.and_term(None).unwrap_or("and");
         let get = |v: NumberVariable| {
@@ -149,9 +161,7 @@ where
         match var {
             AnyVariable::Number(v) => match v {
                 NumberVariable::Locator => self.locator_type.is_some(),
-                NumberVariable::PageFirst => {
-                    self.is_numeric(AnyVariable::Number(NumberVariable::Page))
-                }
+                // PageFirst is covered in get_number, same as CiteContext
                 NumberVariable::FirstReferenceNoteNumber => {
                     self.position.matches(Position::Subsequent)
                 }
@@ -188,6 +198,12 @@ where
     fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange> {
         self.reference.date.get(&dvar)
     }
+    fn jurisdiction(&self) -> Option<&str> {
+        self.reference
+            .ordinary
+            .get(&Variable::Jurisdiction)
+            .map(|j| j.as_str())
+    }
     fn position(&self) -> Option<Position> {
         Some(self.position)
     }
@@ -270,6 +286,7 @@ mod test {
                 let locale = citeproc_db::PredefinedLocales::bundled_en_us()
                     .fetch_locale(&Lang::en_us())
                     .unwrap();
+                let quotes = LocalizedQuotes::from_locale(&locale);
                 use citeproc_io::Reference;
                 let mut reference = Reference::empty(Atom::from("id"), CslType::Book);
                 f(&mut reference);
@@ -278,8 +295,10 @@ mod test {
                     &format,
                     &style,
                     &locale,
+                    &quotes,
                     &reference,
                     CiteOrBib::Citation,
+                    Default::default(),
                 );
                 let mut counter = DisambCounter::new(&ctx);
                 counter.walk_citation(&style)
@@ -194,6 +194,20 @@ pub struct ConditionalDisambIR {
     pub done: bool,
 }
 
+impl ConditionalDisambIR {
+    /// The group vars this conditional contributes to the group around it, given its current
+    /// content. Before disambiguation has re-evaluated it, an empty branch might still render
+    /// something later, so it is only `Unresolved` and can't yet decide whether the enclosing
+    /// group is discarded.
+    pub(crate) fn group_vars<O: OutputFormat>(&self, content: &IrSum<O>) -> GroupVars {
+        match *content {
+            (IR::Rendered(None), GroupVars::Plain) if !self.done => GroupVars::Unresolved,
+            (IR::Rendered(None), GroupVars::Missing) if !self.done => GroupVars::UnresolvedMissing,
+            (_, gv) => gv,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IrNameCounter<O: OutputFormat> {
     pub name_irs: Vec<NameIR<O>>,
@@ -383,11 +397,12 @@ impl IrSeq {
                 let gv = child.get_node().unwrap().get().1;
                 acc.neighbour(gv)
             });
-            // Replicate GroupVars::implicit_conditional
-            if acc != GroupVars::Missing {
-                GroupVars::Important
-            } else {
-                GroupVars::Plain
+            // Replicate GroupVars::implicit_conditional. By the time this is recomputed, anything
+            // still unresolved (a conditional disambiguation never re-evaluated, a year suffix
+            // never assigned) is staying empty, so it counts as missing.
+            match acc {
+                GroupVars::Missing | GroupVars::UnresolvedMissing => GroupVars::Missing,
+                _ => GroupVars::Important,
             }
         })
     }
@@ -424,6 +439,7 @@ impl<'a, O: OutputFormat<Output = SmartString>> IrTreeRef<'a, O> {
         for child in self
             .children()
             .filter_map(|child| child.flatten(fmt, override_delim))
+            .filter(|built| !fmt.is_empty(built))
         {
             group.push(child)
         }
@@ -475,9 +491,12 @@ impl IrSeq {
             should_inherit_delim,
             is_layout: _,
         } = *self;
+        // A child can render to nothing even though its variables were present (e.g. a title of
+        // "." with strip-periods), and it must not get a delimiter either side of it.
         let xs: Vec<_> = tree
             .children()
             .filter_map(|child| child.flatten(fmt, delimiter.as_opt_str()))
+            .filter(|built| !fmt.is_empty(built))
             .collect();
         if xs.is_empty() {
             return None;
@@ -512,9 +531,6 @@ impl IrSeq {
         // Currently recreates the whole markup-formatting infrastructure, but keeps the same
         // granularity of edges that RefIR will produce.
 
-        if node.children(arena).next().is_none() {
-            return;
-        }
         let IrSeq {
             ref affixes,
             ref delimiter,
@@ -538,39 +554,45 @@ impl IrSeq {
         let sub_formatting = formatting
             .map(|mine| format_context.override_with(mine))
             .unwrap_or(format_context);
-        let mut open_tags = SmartString::new();
-        let mut close_tags = SmartString::new();
-        fmt.stack_preorder(&mut open_tags, &stack);
-        fmt.stack_postorder(&mut close_tags, &stack);
-
-        if !affixes.map_or(true, |a| a.prefix.is_empty()) {
-            edges.push(EdgeData::Output(affixes.unwrap().prefix.as_str().into()));
-        }
-
-        if !open_tags.is_empty() {
-            edges.push(EdgeData::Output(open_tags));
-        }
 
-        // push the innards
-        let mut seen = false;
+        // Collect the innards first: children that render nothing (e.g. a choose whose branch
+        // was empty) leave the group empty, and an empty group gets no affixes either.
+        let mut innards = Vec::new();
         let mut sub = Vec::new();
         for child in node.children(arena) {
             IR::append_edges(child, arena, &mut sub, fmt, sub_formatting, delimiter);
             if !sub.is_empty() {
-                if seen {
+                if !innards.is_empty() {
                     if let Some(delimiter) = delimiter {
-                        edges.push(EdgeData::Output(fmt.output_in_context(
+                        innards.push(EdgeData::Output(fmt.output_in_context(
                             fmt.plain(delimiter),
                             sub_formatting,
                             None,
                         )));
                     }
-                } else {
-                    seen = true;
                 }
-                edges.extend(sub.drain(..));
+                innards.extend(sub.drain(..));
             }
         }
+        if innards.is_empty() {
+            return;
+        }
+
+        let mut open_tags = SmartString::new();
+        let mut close_tags = SmartString::new();
+        fmt.stack_preorder(&mut open_tags, &stack);
+        fmt.stack_postorder(&mut close_tags, &stack);
+
+        if !affixes.map_or(true, |a| a.prefix.is_empty()) {
+            edges.push(EdgeData::Output(affixes.unwrap().prefix.as_str().into()));
+        }
+
+        if !open_tags.is_empty() {
+            edges.push(EdgeData::Output(open_tags));
+        }
+
+        edges.extend(innards);
+
         if !close_tags.is_empty() {
             edges.push(EdgeData::Output(close_tags));
         }
@@ -1,4 +1,5 @@
 use crate::cluster::CiteInCluster;
+use crate::db::{AuthorOnlyOutcome, AuthorOnlySubstitute, IrGen};
 use crate::disamb::names::{replace_single_child, NameIR};
 use crate::helpers::slice_group_by::group_by_mut;
 use crate::names::NameToken;
@@ -168,6 +169,24 @@ impl<'a, O: OutputFormat> IrTreeRef<'a, O> {
         }
     }
 
+    /// What the first `<names>` element rendered, for subsequent-author-substitute. This is its
+    /// names block, or if it was substituted with something other than names, e.g. a title, the
+    /// Substitute node. Unlike [Self::first_names_block], it does not look past a substitute to
+    /// the names rendered after it.
+    pub fn first_names_output(&self) -> Option<NodeId> {
+        match self.get_node()?.get().0 {
+            IR::Name(_) => Some(self.node),
+            IR::Substitute => self
+                .children()
+                .find_map(|child| child.first_names_block())
+                .or(Some(self.node)),
+            IR::ConditionalDisamb(_) | IR::Seq(_) => {
+                self.children().find_map(|child| child.first_names_output())
+            }
+            _ => None,
+        }
+    }
+
     fn find_first_year(&self) -> Option<NodeId> {
         match &self.get_node()?.get().0 {
             IR::Rendered(Some(CiteEdgeData::Year(_b))) => Some(self.node),
@@ -386,7 +405,7 @@ impl<O: OutputFormat> NameIR<O> {
             old_ni
         );
         if old_ni.is_some() {
-            let rebuilt = self.intermediate_custom(fmt, cite_pos, false, None, None)?;
+            let rebuilt = self.intermediate_custom(None, fmt, cite_pos, false, None, None)?;
             Some(rebuilt)
         } else {
             None
@@ -429,6 +448,30 @@ impl<'a, O: OutputFormat> IrTreeRef<'a, O> {
     }
 }
 
+/// Finds the node an author-only cite is cut down to, when there is no `<intext>`.
+fn author_only_root(db: &dyn IrDatabase, gen4: &IrGen) -> (AuthorOnlyOutcome, Option<NodeId>) {
+    let tree = gen4.tree_ref();
+    let node = match tree.leading_names_block_or_title(false) {
+        Some(node) => node,
+        None => return (AuthorOnlyOutcome::NoPrintedForm, None),
+    };
+    // Either a names block, or a Substitute that fell back to some other names
+    if tree.with_node(node).first_names_block().is_some() {
+        (AuthorOnlyOutcome::Names, Some(node))
+    } else if db.author_only_substitute() == AuthorOnlySubstitute::Any {
+        (AuthorOnlyOutcome::Substitute, Some(node))
+    } else {
+        (AuthorOnlyOutcome::NoPrintedForm, None)
+    }
+}
+
+pub(crate) fn author_only_outcome(db: &dyn IrDatabase, id: CiteId) -> AuthorOnlyOutcome {
+    if db.intext(id).is_some() {
+        return AuthorOnlyOutcome::Intext;
+    }
+    author_only_root(db, &db.ir_fully_disambiguated(id)).0
+}
+
 fn apply_author_only(
     db: &dyn IrDatabase,
     cite: &mut CiteInCluster<Markup>,
@@ -440,7 +483,7 @@ fn apply_author_only(
         // completely replace with the intext arena, no need to copy
         // into the old arena in gen4.
         cite.gen4 = intext;
-    } else if let Some(new_root) = cite.gen4.tree_ref().leading_names_block_or_title(false) {
+    } else if let (_, Some(new_root)) = author_only_root(db, &cite.gen4) {
         let gen4 = Arc::make_mut(&mut cite.gen4);
         let tree = gen4.tree_mut();
         new_root.detach(&mut tree.arena);
@@ -781,6 +824,32 @@ pub fn subsequent_author_substitute<O: OutputFormat>(
     false
 }
 
+/// Subsequent-author-substitute for a `<names>` element that was substituted with something other
+/// than names, like a title. It applies when the substitute renders the same as the previous
+/// entry's, and then the whole substitute is replaced, whichever rule the style uses.
+pub fn subsequent_author_substitute_for_substitute<O: OutputFormat>(
+    fmt: &O,
+    previous: IrTreeRef<O>,
+    current_id: NodeId,
+    arena: &mut IrArena<O>,
+    sas: &str,
+) -> bool {
+    let current = IrTreeRef::new(current_id, arena).flatten(fmt, None);
+    if current.is_none() || current != previous.flatten(fmt, None) {
+        return false;
+    }
+    let children: Vec<_> = current_id.children(arena).collect();
+    children.into_iter().for_each(|ch| ch.remove_subtree(arena));
+    let replacement = if sas.is_empty() {
+        IR::Rendered(None)
+    } else {
+        IR::Rendered(Some(CiteEdgeData::Output(fmt.plain(sas))))
+    };
+    let sas_node = arena.new_node((replacement, GroupVars::Important));
+    current_id.append(sas_node, arena);
+    true
+}
+
 ///////////////////////
 // MixedNumericStyle //
 ///////////////////////
@@ -1029,6 +1098,40 @@ pub fn fix_left_right_layout_affixes<O: OutputFormat>(root: NodeId, arena: &mut
     }
 }
 
+/// Takes the bibliography layout's own prefix and suffix back off an entry, including from the
+/// left-margin and right-inline blocks [fix_left_right_layout_affixes] moved them into.
+pub fn remove_layout_affixes<O: OutputFormat>(
+    root: NodeId,
+    arena: &mut IrArena<O>,
+    layout_affixes: &Affixes,
+) {
+    match &mut arena[root].get_mut().0 {
+        IR::Seq(seq) if seq.is_layout => seq.affixes = None,
+        _ => return,
+    }
+    let is_display = |node: Option<NodeId>, display: DisplayMode, arena: &IrArena<O>| {
+        node.filter(|&n| matches!(&arena[n].get().0, IR::Seq(s) if s.display == Some(display)))
+    };
+    let left = is_display(arena[root].first_child(), DisplayMode::LeftMargin, arena);
+    let right = is_display(arena[root].last_child(), DisplayMode::RightInline, arena);
+    if let Some(IR::Seq(seq)) = left.map(|n| &mut arena[n].get_mut().0) {
+        if let Some(af) = seq.affixes.as_mut() {
+            let rest = af.prefix.strip_prefix(layout_affixes.prefix.as_str());
+            if let Some(rest) = rest.map(SmartString::from) {
+                af.prefix = rest;
+            }
+        }
+    }
+    if let Some(IR::Seq(seq)) = right.map(|n| &mut arena[n].get_mut().0) {
+        if let Some(af) = seq.affixes.as_mut() {
+            let rest = af.suffix.strip_suffix(layout_affixes.suffix.as_str());
+            if let Some(rest) = rest.map(SmartString::from) {
+                af.suffix = rest;
+            }
+        }
+    }
+}
+
 #[test]
 fn test_left_right_layout() {
     let mut arena = IrArena::<Markup>::new();
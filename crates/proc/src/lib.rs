@@ -40,13 +40,23 @@ mod number;
 mod page_range;
 mod ref_ir;
 mod renderer;
+mod requirements;
 mod sort;
 mod tree;
+mod used_variables;
 mod walker;
 
 pub use crate::cluster::built_cluster_before_output;
-pub use crate::db::safe_default;
-pub use crate::sort::BibNumber;
+pub use crate::db::{
+    safe_default, AuthorOnlyOutcome, AuthorOnlySubstitute, CiteCapitalization,
+    MissingReferencePolicy, NameLimit, NameTruncation, TextCaseLocales,
+};
+pub use crate::requirements::{
+    fallback_terms, style_requirements, style_variables, undefined_terms, FallbackTerm,
+    StyleRequirements, UndefinedTerm,
+};
+pub use crate::sort::{bib_sort_keys, BibNumber, SortKeyValues};
+pub use crate::used_variables::{used_variables, UsedVariables};
 
 pub(crate) mod prelude {
     pub(crate) trait AsRefOptStr {
@@ -71,8 +81,8 @@ pub(crate) mod prelude {
     pub use crate::walker::{StyleWalker, WalkerFoldType};
 
     pub use citeproc_db::{CiteDatabase, CiteId, LocaleDatabase, StyleDatabase};
-    pub use citeproc_io::output::markup::Markup;
-    pub use citeproc_io::output::OutputFormat;
+    pub use citeproc_io::output::markup::{Markup, MarkupInput};
+    pub use citeproc_io::output::{LocalizedQuotes, OutputFormat};
     pub use citeproc_io::IngestOptions;
     pub use citeproc_io::{NumberLike, NumericValue};
     pub use citeproc_io::{SmartCow, SmartString};
@@ -171,7 +181,15 @@ impl NamesInheritance {
             did_supply_name: DidSupplyName::NameEl,
             // The rest will just replace whatever's in the inheritance
             et_al: other.et_al.or_else(|| self.et_al.clone()),
-            label: other.label.or_else(|| self.label.clone()),
+            label: match other.label {
+                // A label without a <name> of its own goes where the names are, i.e. after them if
+                // the <names> being substituted for has a <name>, as in citeproc-js
+                Some(mut label) if other.did_supply_name == DidSupplyName::None => {
+                    label.after_name = self.did_supply_name == DidSupplyName::NameEl;
+                    Some(label)
+                }
+                label => label.or_else(|| self.label.clone()),
+            },
             delimiter: other
                 .delimiter
                 .or_else(|| self.delimiter.clone())
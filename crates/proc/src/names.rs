@@ -7,10 +7,11 @@
 use self::initials::initialize;
 
 use crate::disamb::names::{
-    self as disamb, DisambNameData, DisambNameRatchet, NameIR, PersonDisambNameRatchet,
+    self as disamb, DisambNameData, DisambNameRatchet, FormattedNameListKey, NameIR,
+    PersonDisambNameRatchet,
 };
 use crate::prelude::*;
-use crate::NamesInheritance;
+use crate::{NameLimit, NameTruncation, NamesInheritance};
 use citeproc_io::utils::Intercalate;
 use citeproc_io::{Name, PersonName, Reference};
 use csl::{
@@ -100,7 +101,7 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
                 }
             })
             .collect();
-        NameIR::new(
+        let mut nir = NameIR::new(
             ctx,
             names_inheritance.clone(),
             var,
@@ -111,7 +112,9 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
                 .et_al_term(names_inheritance.et_al.as_ref())
                 .map(|(a, b)| (SmartString::from(a), b)),
             locale.and_term(None).map(|x| x.into()),
-        )
+        );
+        nir.name_limit = db.name_limit();
+        nir
     };
 
     // If multiple variables are selected (separated by single spaces, see example below), each
@@ -126,14 +129,18 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
     // moment), but it doesn't need to: that would accept a single list of names, which makes it
     // more convenient to use for people inputting names in a reference manager.
 
-    let mut var_override = None;
-    let mut slice_override = None;
+    // The one of editor and translator that is rendered (with the editortranslator label) for
+    // both, when they hold the same names.
+    let mut merged = None;
 
-    // Note: won't make editortranslator when you're also rendering a third or even more
-    // variables.
-    let is_editor_translator = &names.variables
-        == &[NameVariable::Editor, NameVariable::Translator]
-        || &names.variables == &[NameVariable::Translator, NameVariable::Editor];
+    // Other variables alongside the pair, like `<names variable="editor translator director">`,
+    // are rendered as usual, and the merged list takes the place of whichever of the pair comes
+    // first.
+    let is_pair =
+        |var: NameVariable| var == NameVariable::Editor || var == NameVariable::Translator;
+    let merged_index = names.variables.iter().position(|&var| is_pair(var));
+    let is_editor_translator = names.variables.contains(&NameVariable::Editor)
+        && names.variables.contains(&NameVariable::Translator);
 
     // name_EditorTranslatorSameEmptyTerm
     // (Although technically the spec isn't worded that way, it is useful to be able to disable
@@ -150,28 +157,32 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
         if let (Some(ed), Some(tr)) = (ed_val, tr_val) {
             // identical
             if ed == tr {
-                let ed_sup = state.is_suppressed_name(NameVariable::Editor);
-                let tran_sup = state.is_suppressed_name(NameVariable::Translator);
-                if ed_sup && tran_sup {
-                    slice_override = Some(&[][..]);
-                } else if ed_sup {
-                    var_override = Some(NameVariable::EditorTranslator);
-                    slice_override = Some(&[NameVariable::Translator][..]);
+                // Both suppressed is handled below, like any other suppressed variable.
+                if state.is_suppressed_name(NameVariable::Editor) {
+                    merged = Some(NameVariable::Translator);
                 } else {
-                    var_override = Some(NameVariable::EditorTranslator);
-                    slice_override = Some(&[NameVariable::Editor][..]);
+                    merged = Some(NameVariable::Editor);
                 }
             }
         }
     }
 
-    slice_override
-        .unwrap_or(&names.variables[..])
+    names
+        .variables
         .iter()
-        .filter(move |var| !state.is_suppressed_name(**var))
+        .enumerate()
+        .filter_map(move |(index, &var)| match merged {
+            Some(kept) if is_pair(var) => Some(kept).filter(|_| Some(index) == merged_index),
+            _ => Some(var),
+        })
+        .filter(move |&var| !state.is_suppressed_name(var))
         .filter_map(move |var| {
-            let ovar = var_override.as_ref().unwrap_or(var);
-            refr.name.get(var).map(|val| (*var, *ovar, val.clone()))
+            let label_var = if merged == Some(var) {
+                NameVariable::EditorTranslator
+            } else {
+                var
+            };
+            refr.name.get(&var).map(|val| (var, label_var, val.clone()))
         })
         .map(get_name_ir)
 }
@@ -206,6 +217,7 @@ pub(crate) fn sort_strings_for_names(
         bump_name_count: 0,
         demote_non_dropping_particle: style.demote_non_dropping_particle,
         initialize_with_hyphen: style.initialize_with_hyphen,
+        name_limit: None,
         fmt: &fmt,
     };
     let mut out = Vec::new();
@@ -256,7 +268,6 @@ pub fn intermediate<'c, O: OutputFormat, I: OutputFormat>(
 
     if names_inheritance.name.form == Some(NameForm::Count) {
         let name_irs = nirs_iterator.collect();
-        // TODO: styling with a surrounding IrSeq
         let mut nc = IrNameCounter {
             name_irs,
             group_vars: GroupVars::new(),
@@ -283,11 +294,24 @@ pub fn intermediate<'c, O: OutputFormat, I: OutputFormat>(
             }
             return arena.new_node((IR::Rendered(None), GroupVars::Missing));
         }
+        state.maybe_suppress_name_vars(&names.variables);
         let (new_ir, gv) = nc.render_cite(ctx);
         nc.group_vars = gv;
         let nc_node = arena.new_node((IR::NameCounter(nc), GroupVars::Important));
         let sub_node = arena.new_node((new_ir, gv));
-        nc_node.append(sub_node, arena);
+        if ctx.sort_key.is_some() {
+            // Affixes would get in the way of sorting the zero-padded count numerically
+            nc_node.append(sub_node, arena);
+        } else {
+            let seq = IrSeq {
+                formatting: names_inheritance.formatting,
+                affixes: names_inheritance.affixes.clone(),
+                ..Default::default()
+            };
+            let seq_node = arena.new_node((IR::Seq(seq), gv));
+            seq_node.append(sub_node, arena);
+            nc_node.append(seq_node, arena);
+        }
         return nc_node;
     }
 
@@ -302,6 +326,7 @@ pub fn intermediate<'c, O: OutputFormat, I: OutputFormat>(
             .map_or(false, |x| x.after_name);
         let built_label = nir.built_label.clone();
         let node = if let Some(result) = nir.intermediate_custom(
+            Some(db),
             &ctx.format,
             ctx.position.0,
             is_sort_key,
@@ -363,9 +388,6 @@ pub fn intermediate<'c, O: OutputFormat, I: OutputFormat>(
         return arena.new_node((IR::Rendered(None), GroupVars::Missing));
     }
 
-    // TODO: &[editor, translator] => &[editor], and use editortranslator on
-    // the label
-
     let seq = IrSeq {
         formatting: names_inheritance.formatting,
         affixes: names_inheritance.affixes.clone(),
@@ -403,20 +425,7 @@ impl<'c, O: OutputFormat> NameIR<O> {
         let position = ctx.position.0;
 
         let runner = self.one_name_var(&self.names_inheritance.name, fmt);
-
-        let name_tokens = runner.name_tokens(
-            position,
-            self.disamb_names.len(),
-            ctx.sort_key.is_some(),
-            self.etal_term.as_ref(),
-        );
-
-        let count: u32 = name_tokens.iter().fold(0, |acc, name| match name {
-            NameToken::Name(_) => acc + 1,
-            // etal, delimiter, etc
-            _ => acc,
-        });
-        count
+        runner.count_names(position, &self.disamb_names, ctx.sort_key.is_some())
     }
 
     // For subsequent-author-substitute
@@ -424,7 +433,7 @@ impl<'c, O: OutputFormat> NameIR<O> {
         let runner = self.one_name_var(&self.names_inheritance.name, fmt);
         let name_tokens = runner.name_tokens(
             Position::First, // All bib entries are First
-            self.disamb_names.len(),
+            &self.disamb_names,
             false, // not in sort key, we're transforming bib ir
             self.etal_term.as_ref(),
         );
@@ -438,12 +447,16 @@ impl<'c, O: OutputFormat> NameIR<O> {
             bump_name_count: self.name_counter.bump,
             demote_non_dropping_particle: self.demote_non_dropping_particle,
             initialize_with_hyphen: self.initialize_with_hyphen,
+            name_limit: self.name_limit,
         }
     }
 
-    /// Render each of the people's names for this NameIR (i.e. for this one variable)
+    /// Render each of the people's names for this NameIR (i.e. for this one variable). With a
+    /// `db`, the names come from the shared [IrDatabase::formatted_name_list] cache where the
+    /// output format can use it.
     pub fn intermediate_custom(
         &mut self,
+        db: Option<&dyn IrDatabase>,
         fmt: &O,
         position: Position,
         is_sort_key: bool,
@@ -461,12 +474,7 @@ impl<'c, O: OutputFormat> NameIR<O> {
         };
 
         let runner = self.one_name_var(&self.names_inheritance.name, fmt);
-        let count_instead = runner.ntb_count_instead(
-            &self.disamb_names,
-            position,
-            is_sort_key,
-            self.etal_term.as_ref(),
-        );
+        let count_instead = runner.ntb_count_instead(&self.disamb_names, position, is_sort_key);
         if count_instead.is_some() {
             // Don't care about disambiguation with count. It's for sorting.
             return count_instead;
@@ -489,6 +497,14 @@ impl<'c, O: OutputFormat> NameIR<O> {
         }
         self.name_counter.max_recorded = self.name_counter.current;
 
+        let ntbs: Vec<_> = ntbs.collect();
+        let cached = match db {
+            // Substituted names and sort keys are rendered differently, so don't bother
+            Some(db) if substitute.is_none() && !is_sort_key => {
+                self.cached_person_names(db, fmt, &ntbs)
+            }
+            _ => None,
+        };
         let mut cloned_runner = runner.clone();
         let mut rendered = Vec::new();
         let mut iter = ntbs.into_iter().peekable();
@@ -507,10 +523,16 @@ impl<'c, O: OutputFormat> NameIR<O> {
                         }
                     }
                     DisambNameRatchet::Person(pn) => {
-                        cloned_runner.name_el = &pn.data.el;
-                        let ret =
-                            cloned_runner.render_person_name(&pn.data.value, !pn.data.primary);
-                        cloned_runner.name_el = &self.names_inheritance.name;
+                        let ret = match cached.as_ref().and_then(|names| names.get(index)) {
+                            Some(built) => built.clone(),
+                            None => {
+                                cloned_runner.name_el = &pn.data.el;
+                                let ret = cloned_runner
+                                    .render_person_name(&pn.data.value, !pn.data.primary);
+                                cloned_runner.name_el = &self.names_inheritance.name;
+                                ret
+                            }
+                        };
                         Some(maybe_subst(ret)).filter(|x| !fmt.is_empty(&x))
                     }
                 },
@@ -533,6 +555,34 @@ impl<'c, O: OutputFormat> NameIR<O> {
         Some(rendered)
     }
 
+    /// The people's names up to the last one in `ntbs`, from one query for the whole list. None
+    /// if the output format can't use names cached as Markup.
+    fn cached_person_names(
+        &self,
+        db: &dyn IrDatabase,
+        fmt: &O,
+        ntbs: &[NameTokenBuilt<O::Build>],
+    ) -> Option<Vec<O::Build>> {
+        // Check before running the query for nothing
+        fmt.reuse_markup(&[])?;
+        let shown = ntbs
+            .iter()
+            .filter_map(|ntb| match ntb {
+                NameTokenBuilt::Ratchet(index) => Some(index + 1),
+                _ => None,
+            })
+            .max()?;
+        let key = self.disamb_names[..shown]
+            .iter()
+            .map(|ratchet| match ratchet {
+                DisambNameRatchet::Person(pn) => Some(pn.data.formatted_name_key()),
+                DisambNameRatchet::Literal { .. } => None,
+            })
+            .collect();
+        let names = db.formatted_name_list(FormattedNameListKey(key));
+        names.iter().map(|built| fmt.reuse_markup(built)).collect()
+    }
+
     pub(crate) fn rendered_ntbs_to_node(
         rendered_ntbs: Vec<O::Build>,
         arena: &mut IrArena<O>,
@@ -646,16 +696,19 @@ fn pn_filter_part(pn: &PersonName, token: NamePartToken) -> bool {
     }
 }
 
-fn should_delimit_after<O: OutputFormat>(
+fn should_delimit_after<O: OutputFormat, B>(
     prec: DelimiterPrecedes,
     name: &OneNameVar<'_, O>,
+    names: &[DisambNameRatchet<B>],
     count_before_spot: usize,
 ) -> bool {
     match prec {
         DelimiterPrecedes::Contextual => count_before_spot >= 2,
-        // anticipate whether name_as_sort_order would kick in for the
-        // name just before the delimiter would go
-        DelimiterPrecedes::AfterInvertedName => name.naso(count_before_spot > 1),
+        // anticipate whether the name just before the delimiter would go will actually be
+        // rendered inverted, which with name-as-sort-order="first" is only the first one
+        DelimiterPrecedes::AfterInvertedName => count_before_spot
+            .checked_sub(1)
+            .map_or(false, |ix| name.renders_inverted(names, ix)),
         DelimiterPrecedes::Always => true,
         DelimiterPrecedes::Never => false,
     }
@@ -680,6 +733,8 @@ pub struct OneNameVar<'a, O: OutputFormat> {
     // From Style
     pub demote_non_dropping_particle: DemoteNonDroppingParticle,
     pub initialize_with_hyphen: bool,
+    /// The processor-wide cap, which sort keys and `form="count"` ignore
+    pub name_limit: Option<NameLimit>,
     pub fmt: &'a O,
 }
 
@@ -693,6 +748,25 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         }
     }
 
+    /// Whether the name at `index` comes out in sort order, i.e. with a sort separator between
+    /// the family and given names. Literal names, names with only one part, non-Latin names and
+    /// short forms never do, whatever `name-as-sort-order` says.
+    fn renders_inverted<B>(&self, names: &[DisambNameRatchet<B>], index: usize) -> bool {
+        match names.get(index) {
+            Some(DisambNameRatchet::Person(ratchet)) if self.naso(index > 0) => {
+                let pn = &ratchet.data.value;
+                let order = get_display_order(
+                    pn.is_latin_cyrillic,
+                    self.name_el.form == Some(NameForm::Long),
+                    true,
+                    self.demote_non_dropping_particle,
+                );
+                pn_filtered_parts(pn, order).contains(&NamePartToken::SortSeparator)
+            }
+            _ => false,
+        }
+    }
+
     #[inline]
     fn ea_min(&self, pos: Position) -> usize {
         let first = self.name_el.et_al_min.unwrap_or(0);
@@ -716,14 +790,98 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         use_first + self.bump_name_count as usize
     }
 
+    /// What `form="count"` renders: the number of names left after et-al truncation. With
+    /// `et-al-use-last`, the last name is counted too. Outside sort keys, the [NameLimit] caps it
+    /// as well.
+    pub(crate) fn count_names<B>(
+        &self,
+        position: Position,
+        names: &[DisambNameRatchet<B>],
+        is_sort_key: bool,
+    ) -> u32 {
+        self.name_tokens(position, names, is_sort_key, None)
+            .iter()
+            .filter(|token| matches!(token, NameToken::Name(_)))
+            .count() as u32
+    }
+
+    /// How many names the style's et-al settings leave in, before any [NameLimit].
+    fn shown_names(&self, position: Position, name_count: usize) -> usize {
+        let ea_use_first = self.ea_use_first(position);
+        if self.name_el.enable_et_al() && name_count >= self.ea_min(position) {
+            if self.name_el.et_al_use_last == Some(true) && ea_use_first + 2 <= name_count {
+                ea_use_first + 1
+            } else {
+                ea_use_first.min(name_count)
+            }
+        } else {
+            name_count
+        }
+    }
+
+    fn push_et_al<B>(
+        &self,
+        nms: &mut Vec<NameToken>,
+        names: &[DisambNameRatchet<B>],
+        use_first: usize,
+        etal_term: Option<&(SmartString, Option<Formatting>)>,
+    ) {
+        if let Some((term_text, formatting)) = etal_term {
+            let dpea = self
+                .name_el
+                .delimiter_precedes_et_al
+                .unwrap_or(DelimiterPrecedes::Contextual);
+            if should_delimit_after(dpea, self, names, use_first) {
+                nms.push(NameToken::Delimiter);
+            } else {
+                nms.push(NameToken::Space);
+            }
+            nms.push(NameToken::EtAl(term_text.clone(), *formatting));
+        }
+    }
+
+    fn capped_name_tokens<B>(
+        &self,
+        limit: NameLimit,
+        names: &[DisambNameRatchet<B>],
+        etal_term: Option<&(SmartString, Option<Formatting>)>,
+    ) -> Vec<NameToken> {
+        let name_count = names.len();
+        let max = limit.max_names as usize;
+        if limit.truncation == NameTruncation::Ellipsis && max >= 2 {
+            let mut nms = (0..max - 1)
+                .map(NameToken::Name)
+                .intercalate(&NameToken::Delimiter);
+            nms.push(NameToken::Delimiter);
+            nms.push(NameToken::Ellipsis);
+            nms.push(NameToken::Space);
+            nms.push(NameToken::Name(name_count - 1));
+            return nms;
+        }
+        if max == 0 {
+            return Vec::new();
+        }
+        let mut nms = (0..max)
+            .map(NameToken::Name)
+            .intercalate(&NameToken::Delimiter);
+        self.push_et_al(&mut nms, names, max, etal_term);
+        nms
+    }
+
     /// Any returned NameToken::Name(ix) will index into the names_slice.
-    fn name_tokens(
+    fn name_tokens<B>(
         &self,
         position: Position,
-        name_count: usize,
+        names: &[DisambNameRatchet<B>],
         is_sort_key: bool,
         etal_term: Option<&(SmartString, Option<Formatting>)>,
     ) -> Vec<NameToken> {
+        let name_count = names.len();
+        if let Some(limit) = self.name_limit {
+            if !is_sort_key && self.shown_names(position, name_count) > limit.max_names as usize {
+                return self.capped_name_tokens(limit, names, etal_term);
+            }
+        }
         let ea_min = self.ea_min(position);
         let ea_use_first = self.ea_use_first(position);
         if self.name_el.enable_et_al() && name_count >= ea_min {
@@ -748,18 +906,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                     .take(ea_use_first)
                     .intercalate(&NameToken::Delimiter);
                 if !is_sort_key {
-                    if let Some((term_text, formatting)) = etal_term {
-                        let dpea = self
-                            .name_el
-                            .delimiter_precedes_et_al
-                            .unwrap_or(DelimiterPrecedes::Contextual);
-                        if should_delimit_after(dpea, self, ea_use_first) {
-                            nms.push(NameToken::Delimiter);
-                        } else {
-                            nms.push(NameToken::Space);
-                        }
-                        nms.push(NameToken::EtAl(term_text.clone(), *formatting));
-                    }
+                    self.push_et_al(&mut nms, names, ea_use_first, etal_term);
                 }
                 nms
             }
@@ -775,7 +922,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                         .name_el
                         .delimiter_precedes_last
                         .unwrap_or(DelimiterPrecedes::Contextual);
-                    if should_delimit_after(dpl, self, name_count - 1) {
+                    if should_delimit_after(dpl, self, names, name_count - 1) {
                         nms.insert(last_delim + 1, NameToken::And);
                     } else {
                         nms[last_delim] = NameToken::Space;
@@ -1076,14 +1223,9 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         names_slice: &[DisambNameRatchet<O::Build>],
         position: Position,
         is_sort_key: bool,
-        etal_term: Option<&(SmartString, Option<Formatting>)>,
     ) -> Option<Vec<O::Build>> {
         if self.name_el.form == Some(NameForm::Count) {
-            let name_tokens = self.name_tokens(position, names_slice.len(), is_sort_key, etal_term);
-            let count: u32 = name_tokens.iter().fold(0, |acc, name| match name {
-                NameToken::Name(_) => acc + 1,
-                _ => acc,
-            });
+            let count = self.count_names(position, names_slice, is_sort_key);
             if is_sort_key {
                 let b = self.fmt.affixed_text(
                     smart_format!("{:08}", count),
@@ -1110,7 +1252,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         etal_term: Option<&(SmartString, Option<Formatting>)>,
     ) -> (impl Iterator<Item = NameTokenBuilt<O::Build>> + 'a, u16) {
         let fmt = self.fmt.clone();
-        let name_tokens = self.name_tokens(position, names_slice.len(), is_sort_key, etal_term);
+        let name_tokens = self.name_tokens(position, names_slice, is_sort_key, etal_term);
 
         let ntb_len = name_tokens.iter().fold(0, |acc, n| match n {
             NameToken::Name(_ratchet) => acc + 1,
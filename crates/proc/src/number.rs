@@ -15,17 +15,29 @@ pub fn render_ordinal(
     gender: Gender,
     long: bool,
 ) -> SmartString {
+    let ordinal_suffix = |n: u32, long: bool| {
+        let term = OrdinalTerm::from_number_for_selector(n, long);
+        locale.get_ordinal_term(OrdinalTermSelector(term, gender))
+    };
     let mut s = SmartString::new();
+    let write_ordinal = |s: &mut SmartString, n: u32| {
+        if !long || n == 0 || n > 10 {
+            write!(s, "{}", n).unwrap();
+        }
+        if let Some(suffix) = ordinal_suffix(n, long) {
+            s.push_str(suffix);
+        }
+    };
     for token in ts {
         match *token {
-            Num(n) | Roman(n, _) => {
-                if !long || n == 0 || n > 10 {
-                    write!(s, "{}", n).unwrap();
-                }
-                let term = OrdinalTerm::from_number_for_selector(n, long);
-                if let Some(suffix) = locale.get_ordinal_term(OrdinalTermSelector(term, gender)) {
-                    s.push_str(suffix);
-                }
+            Num(n) | Roman(n, _) => write_ordinal(&mut s, n),
+            // Already an ordinal (`2nd`, or `2.` in a locale that writes them that way), so
+            // replace its suffix instead of adding another.
+            Affixed(ref pre, num, ref suf)
+                if token.ordinal_num().is_some()
+                    || (pre.is_empty() && ordinal_suffix(num, false) == Some(suf.as_str())) =>
+            {
+                write_ordinal(&mut s, num)
             }
             Affixed(ref pre, num, ref suf) => {
                 write!(s, "{}{}{}", pre, num, suf).unwrap();
@@ -86,6 +98,20 @@ pub fn get_hyphen(locale: &Locale, variable: NumberVariable) -> &str {
     }
 }
 
+#[test]
+fn test_suffixed_range() {
+    let loc = &Locale::default();
+    let ts = &[
+        NumericToken::Affixed("".into(), 3, "rd".into()),
+        NumericToken::Hyphen,
+        NumericToken::Affixed("".into(), 5, "th".into()),
+    ];
+    assert_eq!(
+        &tokens_to_string(ts, loc, NumberVariable::Edition, None),
+        "3rd\u{2013}5th"
+    );
+}
+
 #[test]
 fn test_get_hyphen() {
     let loc = &Locale::default();
@@ -128,10 +154,13 @@ impl NumBefore {
         }
     }
 }
+/// `Suffixed` comes after a number with a suffix (`3rd`, `2b`), which can't be cropped, but can
+/// still start a range (`3rd–5th`).
 #[derive(Debug, Copy, Clone)]
 enum State<'a> {
     Normal,
     Hyphenating { prefix: &'a str, last: NumBefore },
+    Suffixed { hyphen: bool },
 }
 #[derive(Debug, Copy, Clone)]
 enum HyphenInsert {
@@ -182,11 +211,21 @@ impl<'a> State<'a> {
             State::Hyphenating { prefix: _, last: _ } => {
                 (pfx, num, HyphenInsert::Simple, State::Normal)
             }
+            State::Suffixed { hyphen } => {
+                let (pfx, num, _, newstate) = State::Normal.crop(prf, num, is_roman, pfx, sfx);
+                let insert = if hyphen {
+                    HyphenInsert::Locale
+                } else {
+                    HyphenInsert::None
+                };
+                (pfx, num, insert, newstate)
+            }
         }
     }
     fn see_hyphen(&self) -> Self {
         match self {
             State::Normal => State::Normal,
+            State::Suffixed { .. } => State::Suffixed { hyphen: true },
             State::Hyphenating { last, prefix } => {
                 let neu = match *last {
                     NumBefore::SeenNum(n) => NumBefore::SeenNumHyphen(n),
@@ -203,6 +242,7 @@ impl<'a> State<'a> {
                 NumBefore::SeenNumHyphen(_) | NumBefore::SeenRomanHyphen(_) => HyphenInsert::Simple,
                 _ => HyphenInsert::None,
             },
+            State::Suffixed { hyphen: true } => HyphenInsert::Simple,
             _ => HyphenInsert::None,
         }
     }
@@ -247,7 +287,10 @@ fn tokens_to_string(
                 let (prefix, cropped, hyphen, newstate) = state.crop(prf, num, false, pre, suf);
                 hyphen.write(&mut s, locale, variable);
                 write!(s, "{}{}{}", prefix, cropped, suf).unwrap();
-                newstate
+                match newstate {
+                    State::Normal if !suf.is_empty() => State::Suffixed { hyphen: false },
+                    newstate => newstate,
+                }
             }
             Roman(i, upper) => {
                 let (_, _, hyphen, newstate) = state.crop(prf, i, true, "", "");
@@ -1,13 +1,14 @@
 use crate::cite_context::RenderContext;
+use crate::db::TextCaseLocales;
 use crate::number::{arabic_number, render_ordinal, roman_lower, roman_representable};
 use crate::prelude::*;
 use citeproc_io::output::LocalizedQuotes;
-use citeproc_io::{Name, NumericToken, NumericValue, Reference};
+use citeproc_io::{Locator, Locators, Name, NumericToken, NumericValue, Reference};
 use csl::{
     Features, GenderedTermSelector, LabelElement, Lang, Locale, LocatorType, NameLabel,
     NameVariable, NumberElement, NumberVariable, NumericForm, PageRangeFormat, Plural,
-    RoleTermSelector, SortKey, StandardVariable, Style, TextElement, TextTermSelector, Variable,
-    VariableForm,
+    RoleTermSelector, SortKey, StandardVariable, Style, TermForm, TextElement, TextTermSelector,
+    Variable, VariableForm,
 };
 
 use crate::choose::CondChecker;
@@ -40,6 +41,7 @@ impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for GenericContext<'a,
         fn style(&self) -> &Style;
         fn reference(&self) -> &Reference;
         fn locale(&self) -> &Locale;
+        fn quotes(&self) -> &LocalizedQuotes;
         fn cite_lang(&self) -> Option<&Lang>;
         fn get_number(&self, var: NumberVariable) -> Option<NumericValue<'_>>;
         fn get_ordinary(&self, var: Variable, form: VariableForm) -> Option<Cow<'_, str>>;
@@ -57,6 +59,7 @@ impl<'a, O: OutputFormat, I: OutputFormat> CondChecker for GenericContext<'a, O,
         fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange>;
         fn position(&self) -> Option<Position>;
         fn features(&self) -> &Features;
+        fn jurisdiction(&self) -> Option<&str>;
         fn has_year_only(&self, dvar: DateVariable) -> bool;
         fn has_month_or_season(&self, dvar: DateVariable) -> bool;
         fn has_day(&self, dvar: DateVariable) -> bool;
@@ -73,14 +76,35 @@ impl<'a, O: OutputFormat, I: OutputFormat> GenericContext<'a, O, I> {
 
     /// https://docs.citationstyles.org/en/stable/specification.html#non-english-items
     pub fn is_english(&self) -> bool {
-        let sty = self.style();
         let cite = self.cite_lang();
+        let text_case_locales = match self {
+            GenericContext::Cit(ctx) => ctx.text_case_locales,
+            GenericContext::Ref(ctx) => ctx.text_case_locales,
+        };
         // Bit messy but matches the spec wording
-        // If a style doesn't have a default, it's en-US, which is English.
-        let default_is_english = sty.default_locale.as_ref().map_or(true, |x| x.is_english());
+        // The locale is the style's default-locale, or the processor's override of it. If neither
+        // says anything, it's en-US, which is English.
+        let default_is_english = text_case_locales == TextCaseLocales::Lenient
+            || self.locale().lang.as_ref().map_or(true, |x| x.is_english());
         cite.map_or(default_is_english, |l| l.is_english())
     }
 
+    /// With the `multiple-locators` feature, every locator after the first. The first one is
+    /// still the only one visible to `get_number`, `locator_type` and therefore to labels and
+    /// conditions.
+    pub fn subsequent_locators(&self) -> &[Locator] {
+        match self {
+            GenericContext::Cit(ctx) if ctx.style.features.multiple_locators => {
+                match ctx.cite.locators {
+                    Some(ref locs @ Locators::Multiple { .. }) => &locs.as_slice()[1..],
+                    _ => &[],
+                }
+            }
+            // RefIR renders the locator as a single EdgeData::Locator regardless.
+            _ => &[],
+        }
+    }
+
     /// For setting display="X" on elements, where this should only take effect in the
     /// bibliography.
     pub fn in_bibliography(&self) -> bool {
@@ -135,15 +159,21 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
     }
 
     fn page_range_format(&self, var: NumberVariable) -> Option<PageRangeFormat> {
-        let style = self.ctx.style();
-        style.page_range_format.filter(|_| {
-            var == NumberVariable::Page
-                || (var == NumberVariable::Locator
-                    && self
-                        .ctx
-                        .locator_type()
-                        .map_or(false, |l| l == LocatorType::Page))
-        })
+        match var {
+            NumberVariable::Page => self.ctx.style().page_range_format,
+            NumberVariable::Locator => self
+                .ctx
+                .locator_type()
+                .and_then(|loc_type| self.locator_page_range_format(loc_type)),
+            _ => None,
+        }
+    }
+
+    fn locator_page_range_format(&self, loc_type: LocatorType) -> Option<PageRangeFormat> {
+        self.ctx
+            .style()
+            .page_range_format
+            .filter(|_| loc_type.is_paginated())
     }
 
     /// The spec is slightly impractical to implement:
@@ -220,13 +250,81 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
             is_english: self.ctx.is_english(),
             ..Default::default()
         };
-        let b = fmt.ingest(&string, &options);
+        let mut b = fmt.ingest(&string, &options);
+        if number.variable == NumberVariable::Locator {
+            b = self.with_subsequent_locators(b, &options);
+        }
         let b = fmt.with_format(b, number.formatting);
         let b = fmt.affixed(b, number.affixes.as_ref());
         fmt.with_display(b, number.display, self.ctx.in_bibliography())
     }
+
+    /// CSL-M multiple locators. The first locator's label is the style's business (via `<label
+    /// variable="locator">`), but the rest get their own labels through
+    /// [`Renderer::label_for_locator_type`], each pluralised according to its own value: `pp. 3,
+    /// 5; chap. 2`. Those labels are rendered with the style's own locator `<label>` (its form,
+    /// formatting and affixes), or in the short form if the style has none.
+    ///
+    /// The locators are separated by `"; "`, the CSL-M default. This is not the layout delimiter,
+    /// which goes between cites.
+    ///
+    /// Anything the style puts after the locator (a suffix, `locator-extra`) therefore follows the
+    /// last locator rather than the first.
+    fn with_subsequent_locators(&self, first: O::Build, options: &IngestOptions) -> O::Build {
+        let subsequent = self.ctx.subsequent_locators();
+        if subsequent.is_empty() {
+            return first;
+        }
+        let fmt = self.fmt();
+        let locale = self.ctx.locale();
+        let and_term = locale.and_term(None).unwrap_or("and");
+        let label_el = self.locator_label().unwrap_or_else(|| LabelElement {
+            variable: NumberVariable::Locator,
+            form: TermForm::Short,
+            formatting: None,
+            affixes: None,
+            strip_periods: false,
+            text_case: Default::default(),
+            plural: Plural::Contextual,
+        });
+        // A label with a suffix brings its own space, like it does before the first locator.
+        let has_suffix = label_el
+            .affixes
+            .as_ref()
+            .map_or(false, |a| !a.suffix.is_empty());
+        let label_delim = if has_suffix { "" } else { " " };
+        let mut nodes = vec![first];
+        for locator in subsequent {
+            let loc_type = locator.type_of();
+            let val = NumericValue::from_localized(and_term)(locator.value());
+            let prf = self.locator_page_range_format(loc_type);
+            let num = fmt.ingest(
+                &arabic_number(&val, locale, NumberVariable::Locator, prf),
+                options,
+            );
+            let num = match self.label_for_locator_type(&label_el, Some(loc_type), &val) {
+                Some(label) => fmt.join_delim(label, label_delim, num),
+                None => num,
+            };
+            nodes.push(fmt.plain("; "));
+            nodes.push(num);
+        }
+        fmt.seq(nodes)
+    }
+
+    /// The `<label variable="locator">` in the parts of the citation layout this cite renders,
+    /// looking through macros.
+    fn locator_label(&self) -> Option<LabelElement> {
+        let mut finder = LocatorLabelFinder {
+            ctx: &self.ctx,
+            found: None,
+        };
+        finder.walk_citation(self.ctx.style());
+        finder.found
+    }
+
     pub fn quotes(&self) -> LocalizedQuotes {
-        LocalizedQuotes::from_locale(self.ctx.locale())
+        self.ctx.quotes().clone()
     }
     pub fn quotes_if(&self, quo: bool) -> Option<LocalizedQuotes> {
         let q = self.quotes();
@@ -237,14 +335,15 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
         }
     }
 
+    /// `<text variable="page">` and `<text variable="locator">` are rendered like `<number>`, so
+    /// they get the same hyphen replacement and page range formatting, whether they are reached
+    /// directly, through a macro, or while building a sort key.
     pub fn text_number_variable(
         &self,
         text: &TextElement,
         variable: NumberVariable,
         val: &NumericValue<'_>,
     ) -> O::Build {
-        let style = self.ctx.style();
-        let _mod_page = style.page_range_format.is_some();
         if variable == NumberVariable::Locator || variable == NumberVariable::Page {
             let number = csl::NumberElement {
                 variable,
@@ -388,15 +487,28 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
         &self,
         label: &LabelElement,
         num_val: &NumericValue<'_>,
+    ) -> Option<O::Build> {
+        self.label_for_locator_type(label, self.ctx.locator_type(), num_val)
+    }
+
+    /// [`Renderer::numeric_label`], but with the locator type given explicitly, for locators
+    /// other than the cite's first one.
+    fn label_for_locator_type(
+        &self,
+        label: &LabelElement,
+        locator_type: Option<LocatorType>,
+        num_val: &NumericValue<'_>,
     ) -> Option<O::Build> {
         let fmt = self.fmt();
-        let selector = GenderedTermSelector::from_number_variable(
-            self.ctx.locator_type(),
-            label.variable,
-            label.form,
-        );
+        let selector =
+            GenderedTermSelector::from_number_variable(locator_type, label.variable, label.form);
         let plural = match label.plural {
-            Plural::Contextual => num_val.is_multiple(label.variable),
+            Plural::Contextual => match locator_type {
+                Some(loc_type) if label.variable == NumberVariable::Locator => {
+                    num_val.is_multiple_locator(loc_type)
+                }
+                _ => num_val.is_multiple(label.variable),
+            },
             Plural::Always => true,
             Plural::Never => false,
         };
@@ -419,3 +531,41 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
         })
     }
 }
+
+struct LocatorLabelFinder<'a, 'c, O: OutputFormat, I: OutputFormat> {
+    ctx: &'a GenericContext<'c, O, I>,
+    found: Option<LabelElement>,
+}
+
+impl<'a, 'c, O: OutputFormat, I: OutputFormat> StyleWalker for LocatorLabelFinder<'a, 'c, O, I> {
+    type Output = ();
+    type Checker = GenericContext<'c, O, I>;
+
+    fn default(&mut self) {}
+
+    fn get_checker(&self) -> Option<&Self::Checker> {
+        Some(self.ctx)
+    }
+
+    fn fold(&mut self, elements: &[Element], _fold_type: WalkerFoldType) {
+        for el in elements {
+            if self.found.is_some() {
+                return;
+            }
+            self.element(el);
+        }
+    }
+
+    fn text_macro(&mut self, text: &TextElement, name: &SmartString) {
+        let ctx = self.ctx;
+        if let Some(elements) = ctx.style().macros.get(name) {
+            self.fold(elements, WalkerFoldType::Macro(text));
+        }
+    }
+
+    fn label(&mut self, label: &LabelElement) {
+        if label.variable == NumberVariable::Locator {
+            self.found = Some(label.clone());
+        }
+    }
+}
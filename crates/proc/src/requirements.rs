@@ -0,0 +1,298 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+use crate::prelude::*;
+use csl::variables::*;
+use csl::{
+    BodyDate, Choose, Cond, Conditions, IfThen, LabelElement, Lang, Locale, Names, NumberElement,
+    SortSource, Style, StyleClass, TextElement, TextTermSelector, VariableForm,
+};
+use fnv::FnvHashSet;
+
+/// Which parts of the document a style actually depends on. A host can use this to skip tracking
+/// note numbers for an in-text style, or to decide whether to offer footnote insertion at all.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde_derive::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleRequirements {
+    /// The style is a note style, or it uses `first-reference-note-number`. Either way, clusters
+    /// need to be given note numbers.
+    pub requires_note_numbers: bool,
+    /// The style renders, tests or sorts by `citation-number`.
+    pub uses_citation_number: bool,
+    /// The style can produce year suffixes, via `disambiguate-add-year-suffix` or by using the
+    /// `year-suffix` variable directly.
+    pub uses_year_suffix: bool,
+    /// The citation sets `disambiguate-add-year-suffix`, but its layout renders neither a date nor
+    /// the `year-suffix` variable, so there is nowhere for a suffix to go. Suffixes are appended
+    /// to the end of the cite instead, which the style author probably did not intend.
+    pub year_suffix_without_date: bool,
+    pub has_bibliography: bool,
+}
+
+/// A term that the style renders with `<text term="...">`, but which the locale does not define, so
+/// it renders as nothing.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde_derive::Serialize)]
+pub struct UndefinedTerm {
+    pub name: SmartString,
+    pub form: SmartString,
+    pub plural: bool,
+}
+
+/// Checks every term the style could render against a (merged) locale, in every branch. Sorted
+/// by name.
+pub fn undefined_terms(style: &Style, locale: &Locale) -> Vec<UndefinedTerm> {
+    let walker = RequirementsWalker::walk(style);
+    let mut undefined: Vec<UndefinedTerm> = walker
+        .terms
+        .iter()
+        .filter(|&&(sel, plural)| locale.get_text_term(sel, plural).is_none())
+        .map(|&(sel, plural)| {
+            let (name, form) = sel.name_and_form();
+            UndefinedTerm {
+                name: name.into(),
+                form: form.into(),
+                plural,
+            }
+        })
+        .collect();
+    undefined.sort();
+    undefined
+}
+
+/// A term that the style renders and the requested locale does not define, so it is taken from
+/// the en-US locale instead. The locale file (or the style's `<locale>`) has a gap to fill.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde_derive::Serialize)]
+pub struct FallbackTerm {
+    /// The requested locale, e.g. `de-AT`
+    pub locale: SmartString,
+    pub name: SmartString,
+    pub form: SmartString,
+    pub plural: bool,
+}
+
+/// Checks every term the style could render against the `requested` locale, and lists the ones
+/// that only the `merged` locale, with en-US merged in, defines. Sorted by name.
+pub fn fallback_terms(
+    style: &Style,
+    lang: &Lang,
+    requested: &Locale,
+    merged: &Locale,
+) -> Vec<FallbackTerm> {
+    let walker = RequirementsWalker::walk(style);
+    let mut fallback: Vec<FallbackTerm> = walker
+        .terms
+        .iter()
+        .filter(|&&(sel, plural)| {
+            requested.get_text_term(sel, plural).is_none()
+                && merged.get_text_term(sel, plural).is_some()
+        })
+        .map(|&(sel, plural)| {
+            let (name, form) = sel.name_and_form();
+            FallbackTerm {
+                locale: lang.to_string().into(),
+                name: name.into(),
+                form: form.into(),
+                plural,
+            }
+        })
+        .collect();
+    fallback.sort();
+    fallback
+}
+
+/// Walks every branch of the citation, bibliography and in-text layouts, including macros and sort
+/// keys, recording the variables they refer to.
+pub fn style_requirements(style: &Style) -> StyleRequirements {
+    let walker = RequirementsWalker::walk(style);
+    let uses_num = |var: NumberVariable| walker.variables.contains(&AnyVariable::Number(var));
+    let uses_ord = |var: Variable| walker.variables.contains(&AnyVariable::Ordinary(var));
+    StyleRequirements {
+        requires_note_numbers: style.class == StyleClass::Note
+            || uses_num(NumberVariable::FirstReferenceNoteNumber),
+        uses_citation_number: uses_num(NumberVariable::CitationNumber),
+        uses_year_suffix: style.citation.disambiguate_add_year_suffix
+            || uses_ord(Variable::YearSuffix),
+        year_suffix_without_date: style.citation.disambiguate_add_year_suffix
+            && !citation_has_year_suffix_hook(style),
+        has_bibliography: style.bibliography.is_some(),
+    }
+}
+
+/// Every variable the style could render, test or sort by, in any branch of any layout. A field
+/// that isn't in here cannot change the output.
+pub fn style_variables(style: &Style) -> FnvHashSet<AnyVariable> {
+    RequirementsWalker::walk(style).variables
+}
+
+/// Whether the citation layout, in any branch, renders something a year suffix can attach to: a
+/// date, or the `year-suffix` variable itself.
+fn citation_has_year_suffix_hook(style: &Style) -> bool {
+    let mut walker = RequirementsWalker::new(style);
+    walker.walk_citation(style);
+    walker.year_suffix_hook
+}
+
+struct RequirementsWalker<'a> {
+    style: &'a Style,
+    seen_macros: FnvHashSet<SmartString>,
+    variables: FnvHashSet<AnyVariable>,
+    terms: FnvHashSet<(TextTermSelector, bool)>,
+    /// Rendered a date or the `year-suffix` variable
+    year_suffix_hook: bool,
+}
+
+impl<'a> RequirementsWalker<'a> {
+    fn new(style: &'a Style) -> Self {
+        RequirementsWalker {
+            style,
+            seen_macros: FnvHashSet::default(),
+            variables: FnvHashSet::default(),
+            terms: FnvHashSet::default(),
+            year_suffix_hook: false,
+        }
+    }
+
+    fn walk(style: &'a Style) -> Self {
+        let mut walker = RequirementsWalker::new(style);
+        walker.walk_citation(style);
+        walker.walk_bibliography(style);
+        if let Some(intext) = &style.intext {
+            walker.layout(&intext.layout);
+        }
+        let sorts = style
+            .citation
+            .sort
+            .iter()
+            .chain(style.bibliography.as_ref().and_then(|b| b.sort.as_ref()));
+        for key in sorts.flat_map(|sort| sort.keys.iter()) {
+            match &key.sort_source {
+                SortSource::Variable(var) => {
+                    walker.variables.insert(*var);
+                }
+                SortSource::Macro(name) => walker.walk_macro(name),
+            }
+        }
+        walker
+    }
+
+    fn walk_macro(&mut self, name: &SmartString) {
+        // Each macro only needs walking once, and this also stops any recursion.
+        if !self.seen_macros.insert(name.clone()) {
+            return;
+        }
+        if let Some(elements) = self.style.macros.get(name) {
+            for el in elements {
+                self.element(el);
+            }
+        }
+    }
+
+    fn insert_number(&mut self, var: NumberVariable) {
+        self.variables.insert(AnyVariable::Number(var));
+        // page-first is taken from page
+        if var == NumberVariable::PageFirst {
+            self.variables
+                .insert(AnyVariable::Number(NumberVariable::Page));
+        }
+    }
+
+    fn conditions(&mut self, conditions: &Conditions) {
+        for cond in conditions.1.iter().flat_map(|set| set.conds.iter()) {
+            match cond {
+                Cond::Variable(var) | Cond::IsNumeric(var) => {
+                    self.variables.insert(*var);
+                }
+                Cond::IsUncertainDate(var)
+                | Cond::HasYearOnly(var)
+                | Cond::HasMonthOrSeason(var)
+                | Cond::HasDay(var) => {
+                    self.variables.insert(AnyVariable::Date(*var));
+                }
+                Cond::IsPlural(var) => {
+                    self.variables.insert(AnyVariable::Name(*var));
+                }
+                Cond::Jurisdiction(_) | Cond::SubJurisdiction(_) => {
+                    self.variables
+                        .insert(AnyVariable::Ordinary(Variable::Jurisdiction));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'a> StyleWalker for RequirementsWalker<'a> {
+    type Output = ();
+    type Checker = crate::choose::UselessCondChecker;
+
+    fn default(&mut self) {}
+
+    /// Every branch, not just the ones a particular reference would take
+    fn choose(&mut self, choose: &Choose) {
+        let Choose(head, rest, last) = choose;
+        for IfThen(conditions, elements) in std::iter::once(head).chain(rest.iter()) {
+            self.conditions(conditions);
+            self.fold(elements, WalkerFoldType::IfThen);
+        }
+        self.fold(&last.0, WalkerFoldType::Else);
+    }
+
+    fn text_variable(&mut self, _text: &TextElement, svar: StandardVariable, form: VariableForm) {
+        match svar {
+            StandardVariable::Number(var) => self.insert_number(var),
+            StandardVariable::Ordinary(var) => {
+                self.year_suffix_hook |= var == Variable::YearSuffix;
+                self.variables.insert(AnyVariable::Ordinary(var));
+            }
+        }
+        // The same fallbacks as RenderContext::get_ordinary
+        let fallbacks: &[Variable] = match (svar, form) {
+            (StandardVariable::Ordinary(Variable::Title), VariableForm::Short) => {
+                &[Variable::TitleShort]
+            }
+            (StandardVariable::Ordinary(Variable::ContainerTitleShort), _) => {
+                &[Variable::JournalAbbreviation]
+            }
+            (StandardVariable::Ordinary(Variable::ContainerTitle), VariableForm::Short) => {
+                &[Variable::ContainerTitleShort, Variable::JournalAbbreviation]
+            }
+            _ => &[],
+        };
+        for &var in fallbacks {
+            self.variables.insert(AnyVariable::Ordinary(var));
+        }
+    }
+
+    fn text_macro(&mut self, _text: &TextElement, name: &SmartString) {
+        self.walk_macro(name);
+    }
+
+    fn text_term(&mut self, _text: &TextElement, sel: TextTermSelector, plural: bool) {
+        self.terms.insert((sel, plural));
+    }
+
+    fn number(&mut self, number: &NumberElement) {
+        self.insert_number(number.variable);
+    }
+
+    fn label(&mut self, label: &LabelElement) {
+        self.insert_number(label.variable);
+    }
+
+    fn date(&mut self, date: &BodyDate) {
+        self.year_suffix_hook = true;
+        self.variables.insert(AnyVariable::Date(date.variable()));
+    }
+
+    fn names(&mut self, names: &Names) {
+        for &var in &names.variables {
+            self.variables.insert(AnyVariable::Name(var));
+        }
+        if let Some(substitute) = &names.substitute {
+            self.fold(&substitute.0, WalkerFoldType::Substitute);
+        }
+    }
+}
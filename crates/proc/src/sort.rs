@@ -1,7 +1,7 @@
 use crate::db::{with_bib_context, with_cite_context};
 use crate::prelude::*;
 use citeproc_db::{ClusterData, ClusterId, ClusterNumber};
-use citeproc_io::{ClusterMode, DateOrRange};
+use citeproc_io::{ClusterMode, Date, DateOrRange};
 use csl::{style::*, terms::*, variables::*, Atom};
 use fnv::FnvHashMap;
 use std::sync::Arc;
@@ -69,12 +69,19 @@ impl BibNumber {
     }
 }
 
+/// The bibliography's references in order, with their citation numbers.
+///
+/// When the bibliography is sorted, entries whose sort keys are all equal keep a fixed order, so
+/// rebuilding a document never shuffles them: cited references come first, in the order they
+/// were first cited, then uncited ones in order of reference id. Neither depends on the order
+/// references were inserted.
 pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)> {
     let style = db.style();
     let bib = match style.bibliography {
         None => None,
         Some(ref b) => b.sort.as_ref(),
     };
+    let sorting = bib.is_some() && !db.bibliography_no_sort();
 
     let mut citation_numbers = FnvHashMap::default();
 
@@ -98,8 +105,13 @@ pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibN
             i += 1;
         }
     }
-    // "The rest" ie the uncited items
-    for id in disamb_participants.difference(&cited_keys) {
+    // "The rest" ie the uncited items. If they are about to be sorted, put them in id order
+    // first, so any ties come out the same however the references were inserted.
+    let mut uncited: Vec<&Atom> = disamb_participants.difference(&cited_keys).collect();
+    if sorting {
+        uncited.sort_unstable();
+    }
+    for id in uncited {
         if !citation_numbers.contains_key(id) {
             preordered.push(id.clone());
             citation_numbers.insert(id.clone(), BibNumber::Uncited(i as u32));
@@ -236,6 +248,72 @@ pub fn cluster_data_sorted(db: &dyn IrDatabase, id: ClusterId) -> Option<Cluster
     })
 }
 
+/// The values a bibliography entry was sorted by, as plain text, so a host can put headings
+/// between entries (by first letter, by year) without reimplementing `cs:sort`.
+///
+/// There is one value per `<key>` in the bibliography's `<sort>`, in order, except that a macro
+/// key rendering a citation number is preceded by that number. A value is `None` where the entry
+/// had nothing for that key. Dates are written `YYYY-MM-DD` as far as they go, with ranges as
+/// `start/end`, and names are given in sort order, e.g. `Smith John`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortKeyValues {
+    pub keys: Vec<Option<SmartString>>,
+}
+
+impl SortKeyValues {
+    /// The value of the first sort key, if the entry had one.
+    pub fn primary(&self) -> Option<&str> {
+        self.keys.get(0)?.as_deref()
+    }
+}
+
+/// The [SortKeyValues] for every bibliography entry, in bibliography order. Entries have no
+/// values if the bibliography isn't sorted.
+pub fn bib_sort_keys(db: &dyn IrDatabase) -> Vec<(Atom, SortKeyValues)> {
+    let style = db.style();
+    let sort = style
+        .bibliography
+        .as_ref()
+        .and_then(|bib| bib.sort.as_ref())
+        .filter(|_| !db.bibliography_no_sort());
+    let sorted_refs = db.sorted_refs();
+    let (ids, citation_numbers) = &*sorted_refs;
+    let max_cnum = ids.len() as u32;
+    ids.iter()
+        .map(|id| {
+            let demoting = sort.and_then(|sort| {
+                let cnum = *citation_numbers.get(id)?;
+                with_bib_context(
+                    db,
+                    id.clone(),
+                    cnum.cited_only(),
+                    None,
+                    None,
+                    |_, mut ctx| {
+                        Some(ctx_sort_items(
+                            db,
+                            CiteOrBib::Bibliography,
+                            &mut ctx,
+                            cnum,
+                            sort,
+                            max_cnum,
+                        ))
+                    },
+                    |_, _, _| None,
+                )
+            });
+            let keys = demoting.map_or_else(Vec::new, |demoting| {
+                demoting
+                    .items
+                    .iter()
+                    .map(|item| item.value.to_plain())
+                    .collect()
+            });
+            (id.clone(), SortKeyValues { keys })
+        })
+        .collect()
+}
+
 /// May be None if the cite's reference does not exist.
 pub fn bib_number(db: &dyn IrDatabase, id: CiteId) -> Option<BibNumber> {
     let cite = id.lookup(db);
@@ -256,7 +334,72 @@ enum SortValue {
     OrdinaryVariable(Option<Natural<SmartString>>),
     Number(Option<citeproc_io::NumericValueOwned>),
     Names(Option<Vec<Natural<SmartString>>>),
-    Date(Option<DateOrRange>),
+    Date(Option<DateSortKey>),
+}
+
+impl SortValue {
+    fn to_plain(&self) -> Option<SmartString> {
+        match self {
+            SortValue::Macro(m) => m.as_ref().map(NaturalCmp::to_plain),
+            SortValue::Cnum(n) => n.map(|n| n.to_string().into()),
+            SortValue::OrdinaryVariable(v) => v.as_ref().map(|v| v.as_str().into()),
+            SortValue::Number(n) => n.as_ref().map(|n| n.to_plain()),
+            SortValue::Names(names) => names.as_ref().map(|names| {
+                let parts: Vec<&str> = names.iter().map(Natural::as_str).collect();
+                parts.join(" ").into()
+            }),
+            SortValue::Date(d) => d.as_ref().map(DateSortKey::to_plain),
+        }
+    }
+}
+
+/// A date variable, normalised for sorting. Dates compare by year (negative for BCE), then month,
+/// then day, where a missing month or day comes before any present one, so `2000` < `May 2000` <
+/// `1 May 2000`. Seasons have no month for this purpose, and uncertain dates sort like certain
+/// ones.
+///
+/// A range sorts by its start date, then its end date. A single date is a range that ends where it
+/// starts, so it comes before a range with the same start.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DateSortKey {
+    start: (i32, u32, u32),
+    end: (i32, u32, u32),
+}
+
+impl DateSortKey {
+    /// Literal dates have no key, so like missing dates, they sort last.
+    fn new(date: &DateOrRange) -> Option<Self> {
+        fn ymd(date: &Date) -> (i32, u32, u32) {
+            let month = if date.month > 12 { 0 } else { date.month };
+            (date.year, month, date.day)
+        }
+        match date {
+            DateOrRange::Single(d) => Some(DateSortKey {
+                start: ymd(d),
+                end: ymd(d),
+            }),
+            DateOrRange::Range(d1, d2) => Some(DateSortKey {
+                start: ymd(d1),
+                end: ymd(d2),
+            }),
+            DateOrRange::Literal { .. } => None,
+        }
+    }
+
+    fn to_plain(&self) -> SmartString {
+        fn ymd((year, month, day): (i32, u32, u32)) -> String {
+            match (month, day) {
+                (0, _) => format!("{}", year),
+                (_, 0) => format!("{}-{:02}", year, month),
+                _ => format!("{}-{:02}-{:02}", year, month, day),
+            }
+        }
+        if self.start == self.end {
+            ymd(self.start).into()
+        } else {
+            format!("{}/{}", ymd(self.start), ymd(self.end)).into()
+        }
+    }
 }
 
 use std::cmp::Ordering;
@@ -422,10 +565,9 @@ fn ctx_sort_items(
                     );
                     SortValue::Names(a_strings)
                 }
-                // TODO: compare dates, using details from spec for ranges
                 AnyVariable::Date(v) => {
                     let a_date = a_ctx.reference.date.get(&v);
-                    SortValue::Date(a_date.cloned())
+                    SortValue::Date(a_date.and_then(DateSortKey::new))
                 }
             },
         };
@@ -536,7 +678,8 @@ impl<'a, O: OutputFormat> StyleWalker for SortingWalker<'a, O> {
                 if nvar == NumberVariable::CitationNumber {
                     renderer.number_sort_string(nvar, NumericForm::Numeric, &nval)
                 } else {
-                    renderer.text_variable(text, svar, nval.verbatim())
+                    // Same page range formatting as when it's rendered for real
+                    renderer.text_number_variable(text, nvar, &nval)
                 }
             }),
             StandardVariable::Ordinary(var) => self
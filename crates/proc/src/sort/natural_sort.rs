@@ -276,6 +276,36 @@ impl NaturalCmp {
             Some(NaturalCmp(s))
         }
     }
+
+    /// The sort string as readable text: numbers lose their zero padding, and dates are written
+    /// `YYYY-MM-DD`, as far as they go, with `/` between the ends of a range.
+    pub fn to_plain(&self) -> SmartString {
+        fn write_date(out: &mut SmartString, date: &CmpDate) {
+            if let Some(year) = date.year {
+                out.push_str(&year.to_string());
+            }
+            let mut rest = date.rest;
+            while rest.len() >= 2 && rest.is_char_boundary(2) {
+                out.push('-');
+                out.push_str(&rest[..2]);
+                rest = &rest[2..];
+            }
+        }
+        let mut out = SmartString::new();
+        for token in (TokenIterator { remain: &self.0 }) {
+            match token {
+                Token::Str(s) => out.push_str(s),
+                Token::Num(n) | Token::CitationNumber(n) => out.push_str(&n.to_string()),
+                Token::Date(CmpRange::Single(date)) => write_date(&mut out, &date),
+                Token::Date(CmpRange::Range(start, end)) => {
+                    write_date(&mut out, &start);
+                    out.push('/');
+                    write_date(&mut out, &end);
+                }
+            }
+        }
+        out
+    }
 }
 impl PartialOrd for NaturalCmp {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -304,6 +334,17 @@ fn natural_cmp(a: &str, b: &str) -> Ordering {
     o
 }
 
+#[test]
+fn to_plain() {
+    let plain = |s: &str| NaturalCmp::new(s.into()).unwrap().to_plain();
+    assert_eq!(plain("Smith John"), "Smith John");
+    assert_eq!(plain("Vol. \u{E002}00000012\u{E003}"), "Vol. 12");
+    assert_eq!(plain("\u{E000}2009_0407\u{E001}"), "2009-04-07");
+    assert_eq!(plain("\u{E000}2000_\u{E001} x"), "2000 x");
+    assert_eq!(plain("\u{E000}2000_04/2001_\u{E001}"), "2000-04/2001");
+    assert_eq!(plain("\u{E000}-0100_\u{E001}"), "-100");
+}
+
 #[test]
 fn natural_cmp_strings() {
     assert_eq!(natural_cmp("a", "z"), Ordering::Less, "a - z");
@@ -114,6 +114,7 @@ impl MockProcessor {
             self.set_cluster_cites(cluster_id, Arc::new(ids));
             self.set_cluster_note_number(cluster_id, Some(note_number));
             self.set_cluster_mode(cluster_id, None);
+            self.set_cluster_suppress_trailing_punctuation(cluster_id, false);
             cluster_ids.push(cluster_id);
         }
         self.set_cluster_ids(Arc::new(cluster_ids));
@@ -134,6 +135,7 @@ impl MockProcessor {
             self.set_cluster_ids(Arc::new(new_cluster_ids));
             self.set_cluster_note_number(cluster_id, None);
             self.set_cluster_mode(cluster_id, None);
+            self.set_cluster_suppress_trailing_punctuation(cluster_id, false);
         }
 
         let mut ids = Vec::new();
@@ -271,18 +271,25 @@ impl<'a, O: OutputFormat> IrTreeMut<'a, O> {
         let mut queue = Vec::new();
         for node in self.node.descendants(&self.arena) {
             match &self.get(node).unwrap().get().0 {
-                IR::Seq(seq) => {
-                    queue.push((node, seq.dropped_gv));
+                IR::Seq(_) | IR::ConditionalDisamb(_) => {
+                    queue.push(node);
                 }
                 _ => {}
             }
         }
         // Reverse, such that descendants are recalculated first
-        for (seq_node, dropped_gv) in queue.into_iter().rev() {
-            // let data = arena.get_mut(node).unwrap().get_mut();
-            let seq_tree = self.tree_at_node(seq_node);
-            if let Some(force) = IrSeq::overall_group_vars(dropped_gv, seq_tree) {
-                self.arena.get_mut(seq_node).unwrap().get_mut().1 = force;
+        for node in queue.into_iter().rev() {
+            let force = match &self.get(node).unwrap().get().0 {
+                IR::Seq(seq) => IrSeq::overall_group_vars(seq.dropped_gv, self.tree_at_node(node)),
+                // A conditional passes on whatever its branch's group vars have become.
+                IR::ConditionalDisamb(cond) => node
+                    .children(&self.arena)
+                    .next()
+                    .map(|child| cond.group_vars(self.get(child).unwrap().get())),
+                _ => None,
+            };
+            if let Some(force) = force {
+                self.arena.get_mut(node).unwrap().get_mut().1 = force;
             }
         }
     }
@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+use crate::disamb::FreeCond;
+use crate::prelude::*;
+use citeproc_io::Reference;
+use csl::variables::*;
+use csl::{Atom, BodyDate, LabelElement, Names, NumberElement, Style, TextElement, VariableForm};
+use fnv::FnvHashSet;
+use serde::Serializer;
+
+/// The fields of one reference that the current style renders, in a first cite to it and in its
+/// bibliography entry. A reference manager can use this to highlight the fields that matter, or
+/// to point out that a style expects something the reference doesn't have.
+///
+/// Only fields the reference actually has are included, and only from the `<choose>` branches it
+/// takes. Variables that are only tested in conditions are not counted.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde_derive::Serialize)]
+pub struct UsedVariables {
+    #[serde(serialize_with = "sorted_names")]
+    pub citation: FnvHashSet<AnyVariable>,
+    #[serde(serialize_with = "sorted_names")]
+    pub bibliography: FnvHashSet<AnyVariable>,
+}
+
+fn sorted_names<S: Serializer>(set: &FnvHashSet<AnyVariable>, s: S) -> Result<S::Ok, S::Error> {
+    let mut names: Vec<&str> = set.iter().map(AnyVariable::as_str).collect();
+    names.sort_unstable();
+    s.collect_seq(names)
+}
+
+/// Returns None if there is no such reference.
+pub fn used_variables(db: &dyn IrDatabase, ref_id: Atom) -> Option<UsedVariables> {
+    let refr = db.reference(ref_id)?;
+    let style = db.style();
+    let locale = db.default_locale();
+    let quotes = db.default_quotes();
+    let fmt = db.get_formatter();
+    let walk = |location: CiteOrBib| {
+        let ctx = RefContext::from_free_cond(
+            FreeCond::empty(),
+            &fmt,
+            &style,
+            &locale,
+            &quotes,
+            &refr,
+            location,
+            db.text_case_locales(),
+        );
+        let mut walker = UsageWalker {
+            ctx: &ctx,
+            style: &style,
+            reference: &refr,
+            used: FnvHashSet::default(),
+            found: 0,
+        };
+        match location {
+            CiteOrBib::Citation => walker.walk_citation(&style),
+            CiteOrBib::Bibliography => walker.walk_bibliography(&style).unwrap_or(()),
+        }
+        walker.used
+    };
+    Some(UsedVariables {
+        citation: walk(CiteOrBib::Citation),
+        bibliography: walk(CiteOrBib::Bibliography),
+    })
+}
+
+struct UsageWalker<'a> {
+    ctx: &'a RefContext<'a, Markup>,
+    style: &'a Style,
+    reference: &'a Reference,
+    used: FnvHashSet<AnyVariable>,
+    /// Counts every field found, so `<substitute>` can tell whether an element rendered anything
+    found: usize,
+}
+
+impl<'a> UsageWalker<'a> {
+    fn record(&mut self, var: AnyVariable) {
+        let refr = self.reference;
+        let (var, present) = match var {
+            // page-first is taken from page
+            AnyVariable::Number(NumberVariable::PageFirst) => {
+                let page = NumberVariable::Page;
+                (AnyVariable::Number(page), refr.number.contains_key(&page))
+            }
+            AnyVariable::Number(v) => (var, refr.number.contains_key(&v)),
+            AnyVariable::Ordinary(v) => (var, refr.ordinary.contains_key(&v)),
+            AnyVariable::Name(v) => (var, refr.name.contains_key(&v)),
+            AnyVariable::Date(v) => (var, refr.date.contains_key(&v)),
+        };
+        if present {
+            self.used.insert(var);
+            self.found += 1;
+        }
+    }
+}
+
+impl<'a> StyleWalker for UsageWalker<'a> {
+    type Output = ();
+    type Checker = RefContext<'a, Markup>;
+
+    fn default(&mut self) {}
+
+    fn get_checker(&self) -> Option<&Self::Checker> {
+        Some(self.ctx)
+    }
+
+    fn text_variable(&mut self, _text: &TextElement, svar: StandardVariable, form: VariableForm) {
+        use StandardVariable::Ordinary;
+        // The same fallbacks as RenderContext::get_ordinary
+        let candidates: &[Variable] = match (svar, form) {
+            (Ordinary(Variable::Title), VariableForm::Short) => {
+                &[Variable::TitleShort, Variable::Title]
+            }
+            (Ordinary(Variable::ContainerTitleShort), _) => {
+                &[Variable::ContainerTitleShort, Variable::JournalAbbreviation]
+            }
+            (Ordinary(Variable::ContainerTitle), VariableForm::Short) => &[
+                Variable::ContainerTitleShort,
+                Variable::JournalAbbreviation,
+                Variable::ContainerTitle,
+            ],
+            _ => return self.record((&svar).into()),
+        };
+        let refr = self.reference;
+        if let Some(&var) = candidates.iter().find(|v| refr.ordinary.contains_key(v)) {
+            self.record(AnyVariable::Ordinary(var));
+        }
+    }
+
+    fn text_macro(&mut self, _text: &TextElement, name: &SmartString) {
+        if let Some(elements) = self.style.macros.get(name) {
+            for el in elements {
+                self.element(el);
+            }
+        }
+    }
+
+    fn number(&mut self, number: &NumberElement) {
+        self.record(AnyVariable::Number(number.variable));
+    }
+
+    fn label(&mut self, label: &LabelElement) {
+        self.record(AnyVariable::Number(label.variable));
+    }
+
+    fn date(&mut self, date: &BodyDate) {
+        self.record(AnyVariable::Date(date.variable()));
+    }
+
+    fn names(&mut self, names: &Names) {
+        let before = self.found;
+        for &var in &names.variables {
+            self.record(AnyVariable::Name(var));
+        }
+        if self.found > before {
+            return;
+        }
+        if let Some(substitute) = &names.substitute {
+            for el in &substitute.0 {
+                self.element(el);
+                if self.found > before {
+                    return;
+                }
+            }
+        }
+    }
+}
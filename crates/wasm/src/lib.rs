@@ -26,7 +26,7 @@ use wasm_bindgen_futures::{future_to_promise, JsFuture};
 
 use citeproc::prelude::*;
 use citeproc::string_id;
-use csl::{Lang, StyleMeta};
+use csl::{CslType, GetAttribute, Lang, StyleMeta};
 
 /// Parses a CSL style, either independent or dependent, and returns its metadata.
 #[wasm_bindgen(js_name = "parseStyleMetadata")]
@@ -50,6 +50,8 @@ pub enum DriverError {
     UnknownOutputFormat(String),
     #[error("Unknown CSL feature {0:?}")]
     UnknownCSLFeature(String),
+    #[error("Unknown CSL type {0:?}")]
+    UnknownCSLType(String),
     /// Never serialized as a CiteprocRsDriverError, only serialized as a CslStyleError.
     #[error("Style error: {0}")]
     StyleError(#[from] csl::StyleError),
@@ -77,6 +79,12 @@ pub enum DriverError {
         #[serde(skip_serializing)]
         citeproc::ReorderingError,
     ),
+    #[error("Invalid cluster: {0}")]
+    ClusterError(
+        #[from]
+        #[serde(skip_serializing)]
+        string_id::ClusterError,
+    ),
 }
 
 #[wasm_bindgen]
@@ -101,11 +109,33 @@ impl Driver {
             let fetcher = Fetcher::from_options_object(&options_js)?;
             let csl_features = csl::version::read_features(options.csl_features.iter().map(|x| x.as_str()))
                 .map_err(|x| DriverError::UnknownCSLFeature(x.to_owned()))?;
+            let unknown_type_fallback = options
+                .unknown_type_fallback
+                .as_deref()
+                .map(|t| {
+                    CslType::get_attr(t, &csl_features)
+                        .map_err(|_| DriverError::UnknownCSLType(t.to_owned()))
+                })
+                .transpose()?;
             let init = InitOptions {
                 style: options.style.as_ref(),
                 fetcher: Some(us_fetcher),
                 format: options.format,
                 bibliography_no_sort: options.bibliography_no_sort,
+                small_caps: options.small_caps,
+                links: options.links,
+                missing_reference_policy: options.missing_reference_policy,
+                unknown_type_fallback,
+                normalize_initials: options.normalize_initials,
+                note_markers: options.note_markers,
+                bib_entry_template: options.bib_entry_template,
+                raw_cite_affixes: options.raw_cite_affixes,
+                verbatim_affixes: options.verbatim_affixes,
+                cite_capitalization: options.cite_capitalization,
+                in_text_no_ibid: options.in_text_no_ibid,
+                name_limit: options.name_limit,
+                author_only_substitute: options.author_only_substitute,
+                text_case_locales: options.text_case_locales,
                 locale_override: options.locale_override,
                 test_mode: false,
                 csl_features: Some(csl_features),
@@ -183,6 +213,13 @@ impl Driver {
         })
     }
 
+    /// Continues citation numbering from a previous part of the document, which was processed by
+    /// another Driver. With an offset of 12, the first reference is numbered 13.
+    #[wasm_bindgen(js_name = "setCitationNumberOffset")]
+    pub fn set_citation_number_offset(&self, offset: u32) {
+        self.engine.borrow_mut().set_citation_number_offset(offset);
+    }
+
     /// Sets the references to be included in the bibliography despite not being directly cited.
     ///
     /// * `refr` is a
@@ -211,7 +248,23 @@ impl Driver {
         })
     }
 
+    /// Reference ids in the order they are first cited in the document, regardless of how the
+    /// bibliography is sorted. Missing and uncited references are not included.
+    #[wasm_bindgen(js_name = "citedKeysInCitationOrder")]
+    pub fn cited_keys_in_citation_order(&self) -> StringArrayResult {
+        typescript_serde_result(|| {
+            let eng = self.engine.borrow();
+            let keys: Vec<_> = eng
+                .cited_keys_in_citation_order()
+                .iter()
+                .map(|k| k.to_string())
+                .collect();
+            Ok(keys)
+        })
+    }
+
     /// Returns a random cluster id, with an extra guarantee that it isn't already in use.
+    #[cfg(feature = "random-cluster-id")]
     #[wasm_bindgen(js_name = "randomClusterId")]
     pub fn random_cluster_id(&self) -> String {
         let eng = self.engine.borrow();
@@ -224,7 +277,32 @@ impl Driver {
         typescript_serde_result(|| {
             let cluster: string_id::Cluster = cluster.into_serde()?;
             let mut eng = self.engine.borrow_mut();
-            eng.insert_cluster_str(cluster);
+            eng.insert_cluster_str(cluster)?;
+            Ok(())
+        })
+    }
+
+    /// Points the cite at `index` in a cluster at a different reference. The cite keeps its
+    /// locator, affixes and so on, and the rest of the cluster is left alone.
+    #[wasm_bindgen(js_name = "swapCiteReference")]
+    pub fn swap_cite_reference(&self, cluster_id: &str, index: u32, ref_id: &str) -> EmptyResult {
+        typescript_serde_result(|| {
+            let mut eng = self.engine.borrow_mut();
+            eng.swap_cite_reference_str(cluster_id, index, Atom::from(ref_id))?;
+            Ok(())
+        })
+    }
+
+    /// Replaces the cites of an existing cluster, keeping its mode and place in the document.
+    /// Cites that haven't changed are not recomputed.
+    ///
+    /// * `cites` is a Cite[]
+    #[wasm_bindgen(js_name = "replaceClusterCites")]
+    pub fn replace_cluster_cites(&self, cluster_id: &str, cites: Box<[JsValue]>) -> EmptyResult {
+        typescript_serde_result(|| {
+            let cites: Vec<Cite<Markup>> = utils::read_js_array_2(cites)?;
+            let mut eng = self.engine.borrow_mut();
+            eng.replace_cluster_cites_str(cluster_id, cites)?;
             Ok(())
         })
     }
@@ -246,7 +324,7 @@ impl Driver {
     pub fn init_clusters(&self, clusters: Box<[JsValue]>) -> EmptyResult {
         typescript_serde_result(|| {
             let clusters: Vec<_> = utils::read_js_array_2(clusters)?;
-            self.engine.borrow_mut().init_clusters_str(clusters);
+            self.engine.borrow_mut().init_clusters_str(clusters)?;
             Ok(())
         })
     }
@@ -273,6 +351,8 @@ impl Driver {
     ///   cluster's id set to zero. The cluster with id=0 is the position to preview the cite. It
     ///   can replace another cluster, or be inserted before/after/between existing clusters, in
     ///   any location you can think of.
+    /// - `delimiter`: Optional. Goes between cites instead of the style's citation layout
+    ///   delimiter.
     ///
     #[wasm_bindgen(js_name = "previewCitationCluster")]
     pub fn preview_citation_cluster(
@@ -280,19 +360,23 @@ impl Driver {
         cites: Box<[JsValue]>,
         positions: Box<[JsValue]>,
         format: &str,
+        delimiter: Option<String>,
     ) -> StringResult {
         typescript_serde_result(|| {
             let cites: Vec<Cite<Markup>> = utils::read_js_array_2(cites)?;
             let positions: Vec<string_id::ClusterPosition> = utils::read_js_array_2(positions)?;
-            let mut eng = self.engine.borrow_mut();
-            let preview = eng.preview_citation_cluster(
-                &cites,
-                PreviewPosition::MarkWithZeroStr(&positions),
-                Some(
-                    SupportedFormat::from_str(format)
-                        .map_err(|()| DriverError::UnknownOutputFormat(format.to_owned()))?,
-                ),
+            let format = Some(
+                SupportedFormat::from_str(format)
+                    .map_err(|()| DriverError::UnknownOutputFormat(format.to_owned()))?,
             );
+            let position = PreviewPosition::MarkWithZeroStr(&positions);
+            let mut eng = self.engine.borrow_mut();
+            let preview = match delimiter {
+                Some(delim) => {
+                    eng.preview_citation_cluster_with_delimiter(&cites, position, format, &delim)
+                }
+                None => eng.preview_citation_cluster(&cites, position, format),
+            };
             Ok(preview?)
         })
     }
@@ -305,6 +389,16 @@ impl Driver {
         })
     }
 
+    /// Like `makeBibliography`, but returns null if the style has no bibliography at all, rather
+    /// than an empty list.
+    #[wasm_bindgen(js_name = "makeBibliographyIfAny")]
+    pub fn make_bibliography_if_any(&self) -> OptionalBibEntriesResult {
+        typescript_serde_result(|| {
+            let eng = self.engine.borrow();
+            Ok(eng.get_bibliography_if_any())
+        })
+    }
+
     #[wasm_bindgen(js_name = "bibliographyMeta")]
     pub fn bibliography_meta(&self) -> BibliographyMetaResult {
         typescript_serde_result(|| {
@@ -314,6 +408,27 @@ impl Driver {
         })
     }
 
+    /// Which document metadata the current style depends on. For example, an in-text style that
+    /// does not use first-reference-note-number has no need for note numbers.
+    #[wasm_bindgen(js_name = "styleRequirements")]
+    pub fn style_requirements(&self) -> StyleRequirementsResult {
+        typescript_serde_result(|| {
+            let eng = self.engine.borrow();
+            Ok(eng.style_requirements())
+        })
+    }
+
+    /// Which fields of a reference the current style renders, in a cite and in its bibliography
+    /// entry. Useful for highlighting the fields that matter to the chosen style. Returns null if
+    /// there is no such reference.
+    #[wasm_bindgen(js_name = "usedVariables")]
+    pub fn used_variables(&self, ref_id: &str) -> UsedVariablesResult {
+        typescript_serde_result(|| {
+            let eng = self.engine.borrow();
+            Ok(eng.used_variables(Atom::from(ref_id)))
+        })
+    }
+
     /// Specifies which clusters are actually considered to be in the document, and sets their
     /// order. You may insert as many clusters as you like, but the ones provided here are the only
     /// ones used.
@@ -363,6 +478,26 @@ impl Driver {
         })
     }
 
+    /// Starts a batch of edits. Until the matching `endBatch`, `batchedUpdates` returns an empty
+    /// summary instead of recomputing, so calling it after every keystroke is cheap. Batches can
+    /// be nested.
+    #[wasm_bindgen(js_name = "beginBatch")]
+    pub fn begin_batch(&self) {
+        let mut eng = self.engine.borrow_mut();
+        eng.begin_batch();
+    }
+
+    /// Ends a batch started with `beginBatch`. If this closes the outermost batch, returns a
+    /// single `UpdateSummary` covering every edit made during it; otherwise it is empty.
+    #[wasm_bindgen(js_name = "endBatch")]
+    pub fn end_batch(&self) -> UpdateSummaryResult {
+        typescript_serde_result(|| {
+            let mut eng = self.engine.borrow_mut();
+            let summary = eng.end_batch_str();
+            Ok(summary)
+        })
+    }
+
     /// Returns all the clusters and bibliography entries in the document.
     /// Also drains the queue, just like batchedUpdates().
     /// Use this to rehydrate a document or run non-interactively.
@@ -449,6 +584,19 @@ extern "C" {
 // TODO: include note about free()-ing the Driver before an async fetchLocale() call comes back (in
 // which case the Driver reference held to by the promise handler function is now a dangling
 // wasm-bindgen pointer).
+//
+// These definitions are written by hand rather than generated with ts-rs or schemars:
+//
+// - The API types are generic over `OutputFormat`, and their fields use its associated types
+//   (`O::Input`, `O::Output`, `O::BibMeta`), which those derives cannot name. Here they are
+//   always `Markup`, whose inputs and outputs are strings.
+// - `Cite` and `Cluster` deserialize with `deserialize_with` and flattened modes, so they accept
+//   more shapes than they serialize, and a derive only describes the serialized one.
+// - This version of wasm-bindgen only takes a string literal for `typescript_custom_section`, so
+//   generated files could not be included here without a separate build step for the package.
+//
+// Instead, `serialization::shapes_match_typescript` in the citeproc crate pins the JSON these
+// describe. Change both together.
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT_1: &'static str = r#"
 interface InitOptions {
@@ -473,6 +621,73 @@ interface InitOptions {
 
     /** Disables sorting in the bibliography; items appear in cited order. */
     bibliographyNoSort?: bool,
+
+    /** CSL-M and other extensions to allow, e.g. `["condition-date-parts", "multiple-locators"]`.
+      * Everything else is parsed as plain CSL 1.0.1, and a style that uses a feature that isn't
+      * enabled here or in its own `<features>` fails to parse, with a hint naming the feature. */
+    cslFeatures?: string[],
+
+    /** What to do with small caps in "plain" and "rtf" output, which either can't express them,
+      * or are often pasted somewhere that ignores them. Defaults to "native": RTF's \scaps, and
+      * nothing at all for plain text. */
+    smallCaps?: "native" | "uppercase" | "ignore",
+
+    /** Attributes to add to the `<a>` tags written for URLs and DOIs in "html" output, e.g.
+      * `{ target: "_blank", rel: "noopener noreferrer" }`. */
+    links?: { target?: string, rel?: string, class?: string },
+
+    /** What to render in place of a cite to a reference that isn't in the library. Defaults to
+      * "questionMarks", i.e. `???`. "bracketedKey" gives `[smith2008?]`, and with a template,
+      * `{id}` is replaced with the reference id. */
+    missingReferencePolicy?: "questionMarks" | "bracketedKey" | "empty" | { template: string },
+
+    /** The CSL type to give references whose `type` is not one citeproc-rs knows about. By
+      * default they are treated as "article". */
+    unknownTypeFallback?: CslType,
+
+    /** Treat given names made of initials as the same name however they were typed, e.g.
+      * "J.R.R.", "J. R. R." and "JRR" are all read as "J. R. R.". Off by default. */
+    normalizeInitials?: boolean,
+
+    /** For note styles, also render the superscripted note number that marks each cluster's place
+      * in the document, and return it alongside the note text in `UpdateSummary.notes`. */
+    noteMarkers?: boolean,
+
+    /** Markup to put around every bibliography entry, in the output format. `{id}` and
+      * `{citation-number}` are replaced with the entry's reference id and citation number, e.g.
+      * `{ prefix: '<span id="ref-{id}">', suffix: "</span>" }`. */
+    bibEntryTemplate?: { prefix?: string, suffix?: string },
+
+    /** Use cite prefixes and suffixes exactly as given. By default a space is put between an affix
+      * and its cite. */
+    rawCiteAffixes?: boolean,
+
+    /** Insert `{ verbatim }` cite prefixes and suffixes as-is. Off by default, when they are escaped
+      * like string ones; only turn it on if you build every cite yourself. */
+    verbatimAffixes?: boolean,
+
+    /** Which styles capitalize the first term of a cite whose prefix ends in a full stop (e.g.
+      * "See the discussion above."). "noteStyles" is the default. */
+    citeCapitalization?: "noteStyles" | "always" | "never",
+
+    /** Never give cites in in-text clusters an ibid position. A cite that would be ibid is only
+      * subsequent there, like any other repeated cite. */
+    inTextNoIbid?: boolean,
+
+    /** Render at most `maxNames` names from any one name variable, whatever the style's et-al
+      * settings, for references with thousands of authors. The names left out are replaced by the
+      * et-al term, or with `truncation: "ellipsis"`, by an ellipsis and the last name. */
+    nameLimit?: { maxNames: number, truncation?: "etAl" | "ellipsis" },
+
+    /** When an author-only cite's reference has no author, "any" (the default, as in citeproc-js)
+      * shows whatever the style substitutes, even a title. "namesOnly" only shows the names it
+      * falls back to, e.g. editors, and otherwise gives "[NO_PRINTED_FORM]". */
+    authorOnlySubstitute?: "any" | "namesOnly",
+
+    /** "strict" (the default, as in the spec) only title-cases items without a `language` if the
+      * style's locale is English. "lenient" title-cases them regardless, as citeproc-js does by
+      * default. Items whose `language` is not English are never title-cased. */
+    textCaseLocales?: "strict" | "lenient",
 }
 
 /** This interface lets citeproc retrieve locales or modules asynchronously,
@@ -506,7 +721,9 @@ export type Locator = {
 export type CiteLocator = Locator | { locator: undefined; locators: Locator[]; };
 export type CiteMode = { mode?: "SuppressAuthor" | "AuthorOnly"; };
 
-export type Cite<Affix = string> = {
+/** A string prefix/suffix is parsed as micro-html and escaped. `{ verbatim }` is trusted markup
+  * in the output format, and is inserted as-is if the driver was created with `verbatimAffixes`. */
+export type Cite<Affix = string | { verbatim: string }> = {
     id: string;
     prefix?: Affix;
     suffix?: Affix;
@@ -520,10 +737,14 @@ export type ClusterMode
 export type Cluster = {
     id: string;
     cites: Cite[];
+    /** Strip the full stop (or other terminal punctuation) from the end of the rendered cluster,
+      * e.g. to place a note style's cluster in the middle of a sentence. */
+    suppressTrailingPunctuation?: boolean;
 } & ClusterMode;
 
 export type ClusterPosition = {
-    id: string;
+    /** Only left off in previewCitationCluster, to mark the position being previewed. */
+    id?: string;
     /** Leaving off this field means this cluster is in-text. */
     note?: number;
 }
@@ -544,13 +765,47 @@ export type CslType = "book" | "article" | "legal_case" | "article-journal" | st
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT_4: &'static str = r#"
 export interface BibliographyUpdate {
-    updatedEntries: Map<string, string>;
-    entryIds?: string[];
+    updatedEntries: { [id: string]: string };
+    /** `BibEntry.content` for the updated entries, if the bibliography layout has affixes */
+    updatedContent?: { [id: string]: string };
+    /** null if the order of the bibliography did not change */
+    entryIds: string[] | null;
+    /** Entries to delete from the document, if any */
+    removedEntries?: string[];
 }
 
 export type UpdateSummary<Output = string> = {
     clusters: [string, Output][];
-    bibliography?: BibliographyUpdate;
+    /** Clusters that are no longer in the document, if any */
+    removedClusters?: string[];
+    bibliography: BibliographyUpdate | null;
+    warnings?: UpdateWarning[];
+    notes?: NoteUpdate<Output>[];
+};
+
+export type NoteUpdate<Output = string> = {
+    id: string;
+    marker: Output;
+    noteText: Output;
+};
+
+export type UpdateWarning =
+    | { type: "missingReferences", ids: string[] }
+    | { type: "undefinedTerms", terms: UndefinedTerm[] }
+    | { type: "fallbackTerms", terms: FallbackTerm[] }
+    | { type: "yearSuffixWithoutDate" };
+
+export type UndefinedTerm = {
+    name: string;
+    form: string;
+    plural: boolean;
+};
+
+export type FallbackTerm = {
+    locale: string;
+    name: string;
+    form: string;
+    plural: boolean;
 };
 
 type IncludeUncited = "None" | "All" | { Specific: string[] };
@@ -558,12 +813,25 @@ type IncludeUncited = "None" | "All" | { Specific: string[] };
 type BibEntry = {
     id: string;
     value: string;
+    /** The entry without the bibliography layout's prefix and suffix, if it has any */
+    content?: string;
+    /** Only for RTF output */
+    paragraphProps?: ParagraphProps;
+};
+
+/** Indents and tab stops are in twips (1/1440 inch) */
+type ParagraphProps = {
+    leftIndent: number;
+    firstLineIndent: number;
+    tabStop: number | null;
+    lineSpacing: number;
+    entrySpacing: number;
 };
 
 type BibEntries = BibEntry[];
 
 type FullRender = {
-    allClusters: Map<string, string>,
+    allClusters: { [id: string]: string },
     bibEntries: BibEntries,
 };
 
@@ -619,6 +887,8 @@ type DriverError = {
     tag: "ReorderingError"
 } | {
     tag: "ReorderingErrorNumericId"
+} | {
+    tag: "ClusterError"
 };
 
 declare global {
@@ -714,6 +984,20 @@ interface IndependentMeta {
     localeOverrides: string[],
     hasBibliography: bool,
 }
+interface StyleRequirements {
+    /** A note style, or one that uses first-reference-note-number */
+    requiresNoteNumbers: boolean,
+    usesCitationNumber: boolean,
+    usesYearSuffix: boolean,
+    /** disambiguate-add-year-suffix, but no date or year-suffix in the citation layout */
+    yearSuffixWithoutDate: boolean,
+    hasBibliography: boolean,
+}
+interface UsedVariables {
+    /** CSL variable names, e.g. "container-title", sorted alphabetically */
+    citation: string[],
+    bibliography: string[],
+}
 interface StyleMeta {
     info: StyleInfo,
     features: { [feature: string]: bool },
@@ -736,6 +1020,11 @@ result_type!(
     BibEntriesResult,
     "WasmResult<BibEntries>"
 );
+result_type!(
+    Option<Vec<citeproc::BibEntry>>,
+    OptionalBibEntriesResult,
+    "WasmResult<BibEntries | null>"
+);
 result_type!(
     string_id::FullRender,
     FullRenderResult,
@@ -745,7 +1034,17 @@ result_type!(Driver, DriverResult, "WasmResult<Driver>");
 result_type!(
     Option<citeproc::BibliographyMeta>,
     BibliographyMetaResult,
-    "WasmResult<BibliographyMeta>"
+    "WasmResult<BibliographyMeta | null>"
+);
+result_type!(
+    StyleRequirements,
+    StyleRequirementsResult,
+    "WasmResult<StyleRequirements>"
+);
+result_type!(
+    Option<UsedVariables>,
+    UsedVariablesResult,
+    "WasmResult<UsedVariables | null>"
 );
 result_type!((), EmptyResult, "WasmResult<undefined>");
 result_type!(Arc<SmartString>, StringResult, "WasmResult<string>");
@@ -23,6 +23,48 @@ pub struct WasmInitOptions {
     /// Disables sorting on the bibliography
     #[serde(default)]
     pub bibliography_no_sort: bool,
+    /// How plain text and RTF output should handle small caps
+    #[serde(default)]
+    pub small_caps: SmallCapsFallback,
+    /// Extra attributes for `<a>` tags in HTML output
+    #[serde(default)]
+    pub links: LinkOptions,
+    /// What to render for cites to references that are not in the library
+    #[serde(default)]
+    pub missing_reference_policy: MissingReferencePolicy,
+    /// The CSL type to read unknown reference types as
+    #[serde(default)]
+    pub unknown_type_fallback: Option<String>,
+    /// Read `J.R.R.`, `J. R. R.` and `JRR` as the same given name
+    #[serde(default)]
+    pub normalize_initials: bool,
+    /// For note styles, also return the superscripted note number for each cluster
+    #[serde(default)]
+    pub note_markers: bool,
+    /// Markup to put around every bibliography entry
+    #[serde(default)]
+    pub bib_entry_template: Option<BibEntryTemplate>,
+    /// Use cite prefixes and suffixes as given, without adding spaces
+    #[serde(default)]
+    pub raw_cite_affixes: bool,
+    /// Pass `{ verbatim }` cite affixes through unescaped
+    #[serde(default)]
+    pub verbatim_affixes: bool,
+    /// Which styles capitalize a cite after a prefix ending in a full stop
+    #[serde(default)]
+    pub cite_capitalization: CiteCapitalization,
+    /// Never give cites in in-text clusters an ibid position, only subsequent
+    #[serde(default)]
+    pub in_text_no_ibid: bool,
+    /// The most names to render from any one name variable
+    #[serde(default)]
+    pub name_limit: Option<NameLimit>,
+    /// Whether author-only cites may show a substituted title, or only names
+    #[serde(default)]
+    pub author_only_substitute: AuthorOnlySubstitute,
+    /// Whether title case applies to items without a language in non-English styles
+    #[serde(default)]
+    pub text_case_locales: TextCaseLocales,
 }
 
 